@@ -0,0 +1,104 @@
+//! Aging. Every agent carries an `Age`; agents of types with a `lifespan` block also get
+//! a lifespan drawn at birth. Past `old_age` of it, their speed and hunger rate drift from
+//! their genome's values toward `old_speed` and `old_hunger` times them, and at the end
+//! of it `agent_death_system` removes them (`DeathCause::OldAge`).
+
+use legion::{IntoQuery, SystemBuilder};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::agent::AgentType;
+use crate::agent::genome::Genome;
+use crate::util::lerp;
+use crate::world_clock::BaseTraits;
+
+/// Per-type lifespan parameters (the YAML `lifespan` block).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LifespanConfig {
+    /// Average lifespan in ticks.
+    #[serde(default = "default_mean")]
+    pub mean: u32,
+    /// Lifespans are drawn uniformly within ±`spread` of `mean`.
+    #[serde(default = "default_spread")]
+    pub spread: u32,
+    /// Share of the lifespan after which the agent starts to decline.
+    #[serde(default = "default_old_age")]
+    pub old_age: f32,
+    /// Speed multiplier reached at the end of the lifespan.
+    #[serde(default = "default_old_speed")]
+    pub old_speed: f32,
+    /// Hunger rate multiplier reached at the end of the lifespan.
+    #[serde(default = "default_old_hunger")]
+    pub old_hunger: f32,
+}
+
+fn default_mean() -> u32 { 3000 }
+fn default_spread() -> u32 { 500 }
+fn default_old_age() -> f32 { 0.75 }
+fn default_old_speed() -> f32 { 0.5 }
+fn default_old_hunger() -> f32 { 1.5 }
+
+impl Default for LifespanConfig {
+    fn default() -> Self {
+        Self {
+            mean: default_mean(),
+            spread: default_spread(),
+            old_age: default_old_age(),
+            old_speed: default_old_speed(),
+            old_hunger: default_old_hunger(),
+        }
+    }
+}
+
+/// ECS component: how long the agent has lived, and how long it will.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Age {
+    pub ticks: u32,
+    /// `None` for types without a `lifespan` block; such agents never die of old age.
+    pub lifespan: Option<u32>,
+}
+
+impl Age {
+    /// A newborn's age. Draws from `rng` only if the type has a `lifespan` block.
+    pub fn new<R: Rng>(agent_type: &AgentType, rng: &mut R) -> Self {
+        let lifespan = agent_type.lifespan.map(|c| {
+            let low = c.mean.saturating_sub(c.spread).max(1);
+            rng.gen_range(low..=c.mean + c.spread)
+        });
+        Self { ticks: 0, lifespan }
+    }
+
+    /// 0 until `old_age` of the lifespan, rising linearly to 1 at its end.
+    pub fn decline(&self, config: &LifespanConfig) -> f32 {
+        let Some(lifespan) = self.lifespan else { return 0.0 };
+        let onset = lifespan as f32 * config.old_age;
+        let span = (lifespan as f32 - onset).max(1.0);
+        ((self.ticks as f32 - onset) / span).clamp(0.0, 1.0)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifespan.is_some_and(|lifespan| self.ticks >= lifespan)
+    }
+}
+
+/// Ages every agent by one tick and applies old-age decline to its expressed speed and
+/// hunger rate. Agents under a `WorldClock` get the decline in their `BaseTraits`, which
+/// `clock_factor_system` scales into the expressed ones.
+pub fn aging_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("AgingSystem")
+        .with_query(<(&mut Age, &mut AgentType, Option<&Genome>, Option<&mut BaseTraits>)>::query())
+        .build(|_cmd, world, _, query| {
+            for (age, agent_type, genome, base) in query.iter_mut(world) {
+                age.ticks = age.ticks.saturating_add(1);
+                let (Some(config), Some(genome)) = (agent_type.lifespan, genome) else { continue };
+                let decline = age.decline(&config);
+                if decline > 0.0 {
+                    let speed = genome.speed * lerp(1.0, config.old_speed, decline);
+                    let hunger_rate = genome.hunger_rate * lerp(1.0, config.old_hunger, decline);
+                    match base {
+                        Some(base) => (base.speed, base.hunger_rate) = (speed, hunger_rate),
+                        None => (agent_type.movement_profile.speed, agent_type.hunger_rate) = (speed, hunger_rate),
+                    }
+                }
+            }
+        })
+}
@@ -1,7 +1,17 @@
 use serde::{Serialize, Deserialize};
 use legion::Entity;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 use crate::agent::mlp::MLPConfig;
+use crate::agent::reproduction::ReproductionConfig;
+use crate::agent::genome::GeneticsConfig;
+use crate::agent::aging::LifespanConfig;
+use crate::agent::disease::DiseaseConfig;
+use crate::food::Diet;
+use crate::agent::work::WorkRole;
+use crate::interaction::InteractionConfig;
+use crate::map::Terrain;
 
 pub mod agent_state;
 /// Represents the state of an agent in the simulation.
@@ -10,6 +20,7 @@ pub mod agent_state;
 /// - `Moving`: Agent is moving on land.
 /// - `Arrived`: Agent has reached its destination.
 /// - `Swimming`: Agent is in water and can move through water tiles. Only agents in this state can traverse water.
+/// - `Resting`: Agent is recovering energy and stays put until rested.
 pub use agent_state::AgentState;
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -19,6 +30,40 @@ pub enum MovementEffect {
     Slowed(f32),
 }
 
+impl FromStr for MovementEffect {
+    type Err = String;
+
+    /// Parses the per-terrain syntax of agent_types.yaml: `Normal`, `Blocked` or `Slow(x)`,
+    /// where `x` is how many times slower the agent is (and how much more a tile costs).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "Normal" => return Ok(MovementEffect::None),
+            "Blocked" => return Ok(MovementEffect::Blocked),
+            _ => {}
+        }
+        let factor = s
+            .strip_prefix("Slow(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| format!("unknown movement effect '{}' (expected Normal, Blocked or Slow(x))", s))?;
+        let factor: f32 = factor.trim().parse().map_err(|_| format!("invalid slow factor in '{}'", s))?;
+        if factor <= 0.0 {
+            return Err(format!("slow factor must be positive in '{}'", s));
+        }
+        Ok(MovementEffect::Slowed(factor))
+    }
+}
+
+impl fmt::Display for MovementEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovementEffect::None => write!(f, "Normal"),
+            MovementEffect::Blocked => write!(f, "Blocked"),
+            MovementEffect::Slowed(factor) => write!(f, "Slow({})", factor),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MovementProfile {
     pub speed: f32,
@@ -44,8 +89,118 @@ pub struct AgentType {
     pub color: (u8, u8, u8),
     pub movement_profile: MovementProfile,
     pub decision_engine: DecisionEngineConfig,
+    #[serde(default = "default_hunger_rate")]
     pub hunger_rate: f32,
+    #[serde(default = "default_hunger_threshold")]
     pub hunger_threshold: f32,
+    /// Fraction of a food item's nutrition that is turned into hunger (1.0 = all of it).
+    #[serde(default = "default_digestion_efficiency")]
+    pub digestion_efficiency: f32,
+    /// Hunger can't be raised above this by eating; agents spawn at the cap.
+    #[serde(default = "default_hunger_cap")]
+    pub hunger_cap: f32,
+    /// Overeaters keep eating when full; whatever exceeds the cap is wasted.
+    #[serde(default)]
+    pub overeating: bool,
+    /// Maximum energy; agents spawn with a full tank.
+    #[serde(default = "default_stamina")]
+    pub stamina: f32,
+    #[serde(default = "default_one")]
+    pub strength: f32,
+    /// How far the agent can see, in tiles.
+    #[serde(default = "default_vision")]
+    pub vision: f32,
+    /// Multiplies the output of the type's `role`.
+    #[serde(default = "default_one")]
+    pub work_rate: f32,
+    /// The work agents of this type do when they have nothing better to do; `None` means none.
+    #[serde(default)]
+    pub role: Option<WorkRole>,
+    /// Short label for the agent's sprite/ASCII cell; empty means none.
+    #[serde(default)]
+    pub icon: String,
+    /// Fraction of `movement_profile.speed` actually covered each tick (1.0 = all of it).
+    #[serde(default = "default_one")]
+    pub damping: f32,
+    /// Chance per idle tick of setting off to wander.
+    #[serde(default = "default_one")]
+    pub move_probability: f32,
+    /// Per-terrain overrides of `movement_profile.effect` (the YAML `movement_profile` map).
+    #[serde(default)]
+    pub terrain_effects: BTreeMap<Terrain, MovementEffect>,
+    /// How this type reproduces; `None` means it doesn't.
+    #[serde(default)]
+    pub reproduction: Option<ReproductionConfig>,
+    /// Trait variance and mutation (the YAML `genetics` block).
+    #[serde(default)]
+    pub genetics: GeneticsConfig,
+    /// How long agents of this type live; `None` means they don't age out.
+    #[serde(default)]
+    pub lifespan: Option<LifespanConfig>,
+    /// Interaction duration, cooldown and outcome weights (the YAML `interaction` block).
+    #[serde(default)]
+    pub interaction: InteractionConfig,
+    /// Whether and how agents of this type fall ill; `None` means they don't.
+    #[serde(default)]
+    pub disease: Option<DiseaseConfig>,
+    /// Food kinds this type prefers or won't eat (the YAML `diet` block).
+    #[serde(default)]
+    pub diet: Diet,
+}
+
+fn default_hunger_rate() -> f32 { 0.01 }
+fn default_hunger_threshold() -> f32 { 50.0 }
+fn default_digestion_efficiency() -> f32 { 1.0 }
+fn default_hunger_cap() -> f32 { 100.0 }
+fn default_stamina() -> f32 { 100.0 }
+fn default_vision() -> f32 { 5.0 }
+fn default_one() -> f32 { 1.0 }
+
+impl AgentType {
+    /// Movement effect on `terrain`: the per-terrain override if there is one, else the profile default.
+    pub fn terrain_effect(&self, terrain: Terrain) -> MovementEffect {
+        self.terrain_effects.get(&terrain).copied().unwrap_or(self.movement_profile.effect)
+    }
+
+    /// Tiles per tick on `terrain` (0 where blocked).
+    pub fn speed_on(&self, terrain: Terrain) -> f32 {
+        match self.terrain_effect(terrain) {
+            MovementEffect::None => self.movement_profile.speed,
+            MovementEffect::Slowed(factor) => self.movement_profile.speed / factor,
+            MovementEffect::Blocked => 0.0,
+        }
+    }
+}
+
+impl Default for AgentType {
+    fn default() -> Self {
+        Self {
+            name: "agent".to_string(),
+            color: (255, 255, 255),
+            movement_profile: MovementProfile { speed: 1.0, effect: MovementEffect::None },
+            decision_engine: DecisionEngineConfig::Simple,
+            hunger_rate: default_hunger_rate(),
+            hunger_threshold: default_hunger_threshold(),
+            digestion_efficiency: default_digestion_efficiency(),
+            hunger_cap: default_hunger_cap(),
+            overeating: false,
+            stamina: default_stamina(),
+            strength: default_one(),
+            vision: default_vision(),
+            work_rate: default_one(),
+            role: None,
+            icon: String::new(),
+            damping: default_one(),
+            move_probability: default_one(),
+            terrain_effects: BTreeMap::new(),
+            reproduction: None,
+            genetics: GeneticsConfig::default(),
+            lifespan: None,
+            interaction: InteractionConfig::default(),
+            disease: None,
+            diet: Diet::default(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -54,6 +209,22 @@ pub struct Hunger {
     pub threshold: f32, // Configurable per agent type
 }
 
+impl Hunger {
+    /// True when eating would not raise hunger any further.
+    pub fn is_full(&self, agent_type: &AgentType) -> bool {
+        self.value >= agent_type.hunger_cap
+    }
+
+    /// Applies `nutrition` scaled by the type's digestion efficiency, capped at `hunger_cap`.
+    /// Returns the hunger actually gained.
+    pub fn eat(&mut self, nutrition: f32, agent_type: &AgentType) -> f32 {
+        let before = self.value;
+        let gained = nutrition * agent_type.digestion_efficiency;
+        self.value = (self.value + gained).min(agent_type.hunger_cap.max(before));
+        self.value - before
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Energy {
     pub value: f32,
@@ -63,6 +234,9 @@ pub struct Energy {
 pub struct RecentInteraction {
     pub partner: Option<Entity>,
     pub ticks_since: u32,
+    /// How the interaction ended (see `crate::social`).
+    #[serde(default)]
+    pub outcome: Option<crate::interaction::InteractionOutcome>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -72,6 +246,9 @@ pub struct InteractionState {
     pub last_partner: Option<Entity>,
     pub cooldown: u32,
     pub recent_partners: VecDeque<RecentInteraction>,
+    /// Food position a partner shared (`InteractionOutcome::ShareInformation`), until acted on.
+    #[serde(default)]
+    pub food_tip: Option<(f32, f32)>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -99,4 +276,7 @@ impl MovementHistory {
 pub struct SwimmingProfile {
     pub swim_chance_percent: u8, // 1-30, how likely this agent is to want to swim
     pub swim_ticks_remaining: u32, // how many ticks left to swim, 0 if not swimming
+    /// Water tile the agent is walking to the shore of, if it has decided to swim.
+    #[serde(default)]
+    pub destination: Option<(i32, i32)>,
 }
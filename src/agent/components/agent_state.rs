@@ -1,7 +1,11 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentState {
     Idle,
     Moving,
     Arrived,
     Swimming, // New state for agents in water
+    Resting,  // Recovering energy; doesn't move
+    Working,  // At a work site doing its role's task (see agent::work)
 }
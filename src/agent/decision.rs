@@ -0,0 +1,96 @@
+//! Decision engines for idle agents.
+//!
+//! `DecisionEngineConfig::Simple` agents use the hard-coded seek-food/wander rules in
+//! `action_selection_system`. `DecisionEngineConfig::MLP` agents feed an `Observation`
+//! through their network and take the action with the highest output.
+//!
+//! Observation layout (`OBSERVATION_SIZE` = 11 inputs, all roughly in 0..1):
+//!
+//! | index | value |
+//! |-------|-------|
+//! | 0 | hunger / `hunger_cap` |
+//! | 1 | energy / `stamina` |
+//! | 2 | x component of the unit vector to the nearest food within `vision` (0 if none) |
+//! | 3 | y component of the unit vector to the nearest food within `vision` (0 if none) |
+//! | 4 | distance to that food / `vision` (1 if none) |
+//! | 5 | other agents within `vision` / `NEARBY_AGENT_SCALE` |
+//! | 6..=9 | terrain under the agent, one-hot: grass, forest, water, mountain |
+//! | 10 | bias input, always 1 |
+//!
+//! Outputs are read in `Action` order: wander, seek food, rest, stay. A network with
+//! fewer outputs can only choose among the first ones.
+
+use crate::agent::{AgentType, MLPConfig};
+use crate::map::Terrain;
+
+pub const OBSERVATION_SIZE: usize = 11;
+/// Nearby-agent counts are divided by this to keep the input near 0..1.
+pub const NEARBY_AGENT_SCALE: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Walk to a random nearby tile.
+    Wander,
+    /// Walk to the nearest food.
+    SeekFood,
+    /// Switch to `AgentState::Resting`.
+    Rest,
+    /// Do nothing this tick.
+    Stay,
+}
+
+impl Action {
+    /// Output-neuron order.
+    pub const ALL: [Action; 4] = [Action::Wander, Action::SeekFood, Action::Rest, Action::Stay];
+}
+
+/// What an agent knows when it decides. See the module docs for the vector layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub hunger: f32,
+    pub energy: f32,
+    /// Offset to the nearest unclaimed food within vision, if there is any.
+    pub nearest_food: Option<(f32, f32)>,
+    pub nearby_agents: usize,
+    pub terrain: Terrain,
+}
+
+impl Observation {
+    pub fn to_vector(&self, agent_type: &AgentType) -> Vec<f32> {
+        let mut v = vec![0.0; OBSERVATION_SIZE];
+        v[0] = self.hunger / agent_type.hunger_cap;
+        v[1] = self.energy / agent_type.stamina;
+        v[4] = 1.0;
+        if let Some((dx, dy)) = self.nearest_food {
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > 0.0 {
+                v[2] = dx / dist;
+                v[3] = dy / dist;
+            }
+            v[4] = (dist / agent_type.vision.max(1.0)).min(1.0);
+        }
+        v[5] = self.nearby_agents as f32 / NEARBY_AGENT_SCALE;
+        let terrain_slot = match self.terrain {
+            Terrain::Grass => 6,
+            Terrain::Forest => 7,
+            Terrain::Water => 8,
+            Terrain::Mountain => 9,
+        };
+        v[terrain_slot] = 1.0;
+        v[10] = 1.0;
+        v
+    }
+}
+
+/// Runs `config` on `observation` and returns the highest-scoring action, or `None` if
+/// the network doesn't take `OBSERVATION_SIZE` inputs.
+pub fn decide(config: &MLPConfig, agent_type: &AgentType, observation: &Observation) -> Option<Action> {
+    if config.input_size != OBSERVATION_SIZE {
+        return None;
+    }
+    let outputs = config.forward(observation.to_vector(agent_type));
+    outputs.iter()
+        .zip(Action::ALL)
+        .max_by(|a, b| a.0.partial_cmp(b.0).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(_, action)| action)
+}
@@ -0,0 +1,240 @@
+//! Disease. Agents of types with a `disease` block carry a `Health` and can catch an
+//! illness that spreads along the contacts `entity_interaction_system` detects (see
+//! `AgentContacts`). It runs SIR-fashion: a susceptible agent meeting an ill one is
+//! infected with its own type's `transmission` chance; after `incubation_ticks` it is ill
+//! itself (contagious, and hungry `hunger_multiplier` times as fast) for `recovery_ticks`,
+//! and then recovered and immune. Doctors at work shorten illnesses (see `agent::work`).
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use legion::{Entity, IntoQuery, SystemBuilder};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentType, Hunger};
+use crate::ecs_components::AgentContacts;
+use crate::sim_rng::{RngStream, SimRng};
+
+/// Per-type disease parameters (the YAML `disease` block).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DiseaseConfig {
+    /// Chance that a contact with an ill agent infects a susceptible one.
+    #[serde(default = "default_transmission")]
+    pub transmission: f32,
+    /// Ticks from infection until the agent is ill (and contagious).
+    #[serde(default = "default_incubation_ticks")]
+    pub incubation_ticks: u32,
+    /// Ticks the illness lasts before the agent recovers.
+    #[serde(default = "default_recovery_ticks")]
+    pub recovery_ticks: u32,
+    /// Hunger rate multiplier while ill.
+    #[serde(default = "default_hunger_multiplier")]
+    pub hunger_multiplier: f32,
+    /// Share of the initial population that starts out infected.
+    #[serde(default)]
+    pub initial_infected: f32,
+}
+
+fn default_transmission() -> f32 { 0.3 }
+fn default_incubation_ticks() -> u32 { 20 }
+fn default_recovery_ticks() -> u32 { 100 }
+fn default_hunger_multiplier() -> f32 { 1.5 }
+
+impl Default for DiseaseConfig {
+    fn default() -> Self {
+        Self {
+            transmission: default_transmission(),
+            incubation_ticks: default_incubation_ticks(),
+            recovery_ticks: default_recovery_ticks(),
+            hunger_multiplier: default_hunger_multiplier(),
+            initial_infected: 0.0,
+        }
+    }
+}
+
+impl DiseaseConfig {
+    /// Ticks from infection to recovery.
+    pub fn course(&self) -> u32 {
+        self.incubation_ticks + self.recovery_ticks
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    #[default]
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+/// ECS component: where the agent is in the course of the illness.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    pub status: HealthStatus,
+    /// Ticks since infection.
+    pub ticks: u32,
+}
+
+impl Health {
+    /// Health of a new agent. Members of the initial population (`seed`) start out
+    /// infected with chance `initial_infected`; draws from `rng` only if that is above 0.
+    pub fn new<R: Rng>(config: &DiseaseConfig, seed: bool, rng: &mut R) -> Self {
+        let mut health = Self::default();
+        if seed && config.initial_infected > 0.0 && rng.gen::<f32>() < config.initial_infected {
+            health.infect();
+        }
+        health
+    }
+
+    pub fn infect(&mut self) {
+        self.status = HealthStatus::Infected;
+        self.ticks = 0;
+    }
+
+    /// Infected and past incubation: contagious and hungrier.
+    pub fn is_ill(&self, config: &DiseaseConfig) -> bool {
+        self.status == HealthStatus::Infected && self.ticks >= config.incubation_ticks
+    }
+
+    /// Moves an illness `ticks` closer to its end. Returns true if that finished it; the
+    /// agent recovers on its next `disease_system` tick.
+    pub fn treat(&mut self, ticks: u32, config: &DiseaseConfig) -> bool {
+        if !self.is_ill(config) || self.ticks >= config.course() {
+            return false;
+        }
+        self.ticks = (self.ticks + ticks).min(config.course());
+        self.ticks >= config.course()
+    }
+}
+
+/// ECS resource: this tick's SIR counts and running totals.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiseaseStats {
+    pub susceptible: usize,
+    pub infected: usize,
+    pub recovered: usize,
+    /// Infections passed on since the start.
+    pub infections: usize,
+    /// Recoveries since the start.
+    pub recoveries: usize,
+    /// Infected counts of the last 100 ticks, for the stats window.
+    #[serde(default)]
+    pub infected_history: VecDeque<usize>,
+}
+
+impl DiseaseStats {
+    /// "S 40, I 5, R 12; 17 infections, 12 recoveries"
+    pub fn summary(&self) -> String {
+        format!("S {}, I {}, R {}; {} infections, {} recoveries", self.susceptible, self.infected, self.recovered, self.infections, self.recoveries)
+    }
+}
+
+/// ECS resource: optional CSV output of per-tick SIR counts (`--disease-out`).
+pub struct DiseaseSink {
+    out: Option<BufWriter<File>>,
+    /// Tick the next row belongs to.
+    pub tick: u64,
+}
+
+impl Default for DiseaseSink {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl DiseaseSink {
+    pub fn disabled() -> Self {
+        Self { out: None, tick: 0 }
+    }
+
+    /// Creates (truncates) `path` and writes the CSV header.
+    pub fn create<P: AsRef<Path>>(path: P, start_tick: u64) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "tick,susceptible,infected,recovered")?;
+        Ok(Self { out: Some(writer), tick: start_tick })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    fn write_row(&mut self, stats: &DiseaseStats) -> io::Result<()> {
+        let tick = self.tick;
+        let Some(writer) = self.out.as_mut() else { return Ok(()) };
+        writeln!(writer, "{},{},{},{}", tick, stats.susceptible, stats.infected, stats.recovered)?;
+        writer.flush()
+    }
+}
+
+/// Advances every infection by a tick (drawing extra hunger from the ill and recovering
+/// those at the end of it), passes the illness on along this tick's contacts, and records
+/// the SIR counts.
+pub fn disease_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("DiseaseSystem")
+        .read_resource::<AgentContacts>()
+        .write_resource::<DiseaseStats>()
+        .write_resource::<DiseaseSink>()
+        .write_resource::<SimRng>()
+        .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
+        .read_resource::<crate::log_config::LogConfig>()
+        .with_query(<(Entity, &AgentType, &mut Hunger, &mut Health)>::query())
+        .build(|_cmd, world, (contacts, stats, sink, sim_rng, event_log, log_config), query| {
+            for (entity, agent_type, hunger, health) in query.iter_mut(world) {
+                let Some(config) = &agent_type.disease else { continue };
+                if health.status != HealthStatus::Infected {
+                    continue;
+                }
+                health.ticks = health.ticks.saturating_add(1);
+                if health.ticks >= config.course() {
+                    health.status = HealthStatus::Recovered;
+                    stats.recoveries += 1;
+                    if !log_config.quiet {
+                        event_log.lock().unwrap().push(format!("[DISEASE] Agent {:?} recovers", entity));
+                    }
+                } else if health.is_ill(config) {
+                    hunger.value -= agent_type.hunger_rate * (config.hunger_multiplier - 1.0);
+                }
+            }
+            let rng = sim_rng.stream(RngStream::Disease);
+            for &(a, b) in &contacts.0 {
+                for (from, to) in [(a, b), (b, a)] {
+                    let contagious = query.get_mut(world, from).ok()
+                        .is_some_and(|(_, agent_type, _, health)| agent_type.disease.as_ref().is_some_and(|c| health.is_ill(c)));
+                    if !contagious {
+                        continue;
+                    }
+                    let Ok((_, agent_type, _, health)) = query.get_mut(world, to) else { continue };
+                    let Some(config) = &agent_type.disease else { continue };
+                    if health.status == HealthStatus::Susceptible && rng.gen::<f32>() < config.transmission {
+                        health.infect();
+                        stats.infections += 1;
+                        if !log_config.quiet {
+                            event_log.lock().unwrap().push(format!("[DISEASE] Agent {:?} infects {:?}", from, to));
+                        }
+                    }
+                }
+            }
+            let (mut s, mut i, mut r) = (0, 0, 0);
+            for (_, _, _, health) in query.iter_mut(world) {
+                match health.status {
+                    HealthStatus::Susceptible => s += 1,
+                    HealthStatus::Infected => i += 1,
+                    HealthStatus::Recovered => r += 1,
+                }
+            }
+            (stats.susceptible, stats.infected, stats.recovered) = (s, i, r);
+            if stats.infected_history.len() >= 100 {
+                stats.infected_history.pop_front();
+            }
+            stats.infected_history.push_back(i);
+            if sink.is_enabled() {
+                if let Err(e) = sink.write_row(stats) {
+                    log::error!("[DISEASE] Failed to write SIR counts: {}", e);
+                }
+            }
+            sink.tick += 1;
+        })
+}
@@ -0,0 +1,58 @@
+//! Energy and stamina.
+//!
+//! `AgentType::stamina` is an agent's maximum energy. Walking and swimming spend
+//! energy in proportion to the terrain cost of the tile being entered; agents that
+//! run low switch to `AgentState::Resting` and recover until they are nearly full.
+
+use legion::*;
+use crate::agent::{AgentState, AgentType, Energy};
+use crate::map::{Map, Terrain};
+use crate::structure::HOUSE_REST_BONUS;
+
+/// Energy spent per tile walked on cost-1 terrain (grass).
+pub const MOVE_ENERGY_PER_TILE: f32 = 0.5;
+/// Terrain cost used for water while swimming (water has no walking cost).
+pub const SWIM_TERRAIN_COST: f32 = 3.0;
+/// Agents start resting below this fraction of their stamina...
+pub const REST_START_FRACTION: f32 = 0.2;
+/// ...and get up again once recovered to this fraction.
+pub const REST_END_FRACTION: f32 = 0.9;
+/// Fraction of stamina recovered per tick of rest.
+pub const REST_RECOVERY_FRACTION: f32 = 0.05;
+
+/// Terrain cost multiplier for spending energy on `terrain`.
+pub fn terrain_energy_cost(terrain: Terrain) -> f32 {
+    match terrain {
+        Terrain::Water => SWIM_TERRAIN_COST,
+        other => other.movement_cost().unwrap_or(SWIM_TERRAIN_COST),
+    }
+}
+
+/// Energy needed to cover `distance` tiles ending at (`x`, `y`).
+pub fn movement_energy(map: &Map, x: f32, y: f32, distance: f32) -> f32 {
+    distance * MOVE_ENERGY_PER_TILE * terrain_energy_cost(map.terrain_at(x, y))
+}
+
+/// True when an agent should stop and rest.
+pub fn needs_rest(energy: &Energy, agent_type: &AgentType) -> bool {
+    energy.value < agent_type.stamina * REST_START_FRACTION
+}
+
+// --- ECS Agent Rest System ---
+/// Resting agents recover energy (faster inside a house) and return to `Idle` once rested.
+pub fn rest_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("RestSystem")
+        .read_resource::<Map>()
+        .with_query(<(&crate::ecs_components::Position, &AgentType, &mut Energy, &mut AgentState)>::query())
+        .build(|_cmd, world, map, query| {
+            for (pos, agent_type, energy, agent_state) in query.iter_mut(world) {
+                if *agent_state == AgentState::Resting {
+                    let bonus = if map.structures.is_shelter(pos.x as i32, pos.y as i32) { HOUSE_REST_BONUS } else { 1.0 };
+                    energy.value = (energy.value + agent_type.stamina * REST_RECOVERY_FRACTION * bonus).min(agent_type.stamina);
+                    if energy.value >= agent_type.stamina * REST_END_FRACTION {
+                        *agent_state = AgentState::Idle;
+                    }
+                }
+            }
+        })
+}
@@ -1,4 +1,32 @@
 use legion::Entity;
+use serde::{Serialize, Deserialize};
+use crate::interaction::InteractionOutcome;
+
+/// Why an agent died.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Hunger ran out.
+    Starvation,
+    /// Energy ran out on land.
+    Exhaustion,
+    /// Energy ran out in the water.
+    Drowning,
+    /// Reached the end of its lifespan.
+    OldAge,
+}
+
+impl DeathCause {
+    pub const ALL: [DeathCause; 4] = [DeathCause::Starvation, DeathCause::Exhaustion, DeathCause::Drowning, DeathCause::OldAge];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::Exhaustion => "exhaustion",
+            DeathCause::Drowning => "drowning",
+            DeathCause::OldAge => "old_age",
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum AgentEvent {
@@ -6,6 +34,8 @@ pub enum AgentEvent {
         agent: Entity,
         agent_type: String,
         pos: (f32, f32),
+        /// Mother then father for agents born in the simulation; empty otherwise.
+        parents: Vec<Entity>,
     },
     Moved {
         agent: Entity,
@@ -17,31 +47,58 @@ pub enum AgentEvent {
         food: Entity,
         nutrition: f32,
     },
+    /// A meal from the agent's pack or a stockpile (see `agent::inventory`).
+    AteCarriedFood {
+        agent: Entity,
+        nutrition: f32,
+    },
+    /// A food item put into the agent's pack instead of eaten (see `agent::inventory`).
+    PickedUpFood {
+        agent: Entity,
+        food: Entity,
+    },
+    /// A completed interaction; `agent` is the one that started it.
     Interacted {
         agent: Entity,
         with: Entity,
+        outcome: InteractionOutcome,
     },
     StateChanged {
         agent: Entity,
         from: String,
         to: String,
     },
+    Died {
+        agent: Entity,
+        pos: (f32, f32),
+        cause: DeathCause,
+        /// Age in ticks.
+        age: u32,
+    },
     // Add more event types as needed
 }
 
 impl AgentEvent {
     pub fn to_log_string(&self) -> String {
         match self {
-            AgentEvent::Spawned { agent, agent_type, pos } =>
+            AgentEvent::Spawned { agent, agent_type, pos, parents } if parents.is_empty() =>
                 format!("[SPAWNED] Agent {:?} (type: {}) at ({:.2}, {:.2})", agent, agent_type, pos.0, pos.1),
+            AgentEvent::Spawned { agent, agent_type, pos, parents } =>
+                format!("[SPAWNED] Agent {:?} (type: {}) at ({:.2}, {:.2}), parents {:?}", agent, agent_type, pos.0, pos.1, parents),
             AgentEvent::Moved { agent, from, to } =>
                 format!("[MOVED] Agent {:?} from ({:.2}, {:.2}) to ({:.2}, {:.2})", agent, from.0, from.1, to.0, to.1),
             AgentEvent::AteFood { agent, food, nutrition } =>
                 format!("[ATE] Agent {:?} ate food {:?} (+{:.1})", agent, food, nutrition),
-            AgentEvent::Interacted { agent, with } =>
-                format!("[INTERACTED] Agent {:?} with {:?}", agent, with),
+            AgentEvent::AteCarriedFood { agent, nutrition } =>
+                format!("[ATE] Agent {:?} ate carried food (+{:.1})", agent, nutrition),
+            AgentEvent::PickedUpFood { agent, food } =>
+                format!("[PICKED UP] Agent {:?} picked up food {:?}", agent, food),
+            AgentEvent::Interacted { agent, with, outcome } =>
+                format!("[INTERACTED] Agent {:?} with {:?}: {}", agent, with, outcome.as_str()),
             AgentEvent::StateChanged { agent, from, to } =>
                 format!("[STATE] Agent {:?} changed state from {} to {}", agent, from, to),
+            AgentEvent::Died { agent, pos, cause, age } =>
+                format!("[DIED] Agent {:?} died of {} at ({:.2}, {:.2}), aged {}", agent, cause.as_str(), pos.0, pos.1, age),
         }
     }
 }
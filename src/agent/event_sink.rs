@@ -0,0 +1,301 @@
+//! Persistent agent event stream (`--events-out`).
+//!
+//! `AgentEventLog` only lives for one tick, so this system writes every event to
+//! a JSON Lines or CSV file before the GUI bridge clears it. Entities are written
+//! as stable ids (see `StableIds`) instead of legion handles, so the same seed
+//! gives the same ids in every run.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use legion::Entity;
+use legion::systems::SystemBuilder;
+use serde::Serialize;
+use crate::agent::event::{AgentEvent, AgentEventLog};
+
+/// Sequential ids for entities that appear in the event stream, assigned on first
+/// sight. Saved in snapshots so a resumed run keeps numbering where it stopped.
+#[derive(Clone, Debug, Default)]
+pub struct StableIds {
+    pub next: u64,
+    pub by_entity: HashMap<Entity, u64>,
+}
+
+impl StableIds {
+    pub fn get(&self, entity: Entity) -> Option<u64> {
+        self.by_entity.get(&entity).copied()
+    }
+
+    pub fn get_or_assign(&mut self, entity: Entity) -> u64 {
+        let next = &mut self.next;
+        *self.by_entity.entry(entity).or_insert_with(|| {
+            let id = *next;
+            *next += 1;
+            id
+        })
+    }
+
+    /// Drops the id of an entity that no longer exists (legion never reuses handles).
+    pub fn forget(&mut self, entity: Entity) {
+        self.by_entity.remove(&entity);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventFormat {
+    Jsonl,
+    Csv,
+}
+
+impl EventFormat {
+    /// `.csv` selects CSV; anything else is JSON Lines.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => EventFormat::Csv,
+            _ => EventFormat::Jsonl,
+        }
+    }
+}
+
+/// One flattened event row. Fields that don't apply to an event kind are left empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct EventRecord {
+    pub tick: u64,
+    pub event: &'static str,
+    pub agent: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_x: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_y: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nutrition: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_state: Option<String>,
+    /// Stable ids of a newborn's parents, mother first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<&'static str>,
+}
+
+pub const CSV_HEADER: &str = "tick,event,agent,other,agent_type,x,y,from_x,from_y,nutrition,from_state,to_state,parents,cause,age,outcome";
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl EventRecord {
+    pub fn from_event(event: &AgentEvent, tick: u64, ids: &mut StableIds) -> Self {
+        match event {
+            AgentEvent::Spawned { agent, agent_type, pos, parents } => EventRecord {
+                tick,
+                event: "Spawned",
+                agent: ids.get_or_assign(*agent),
+                agent_type: Some(agent_type.clone()),
+                x: Some(pos.0),
+                y: Some(pos.1),
+                parents: parents.iter().map(|p| ids.get_or_assign(*p)).collect(),
+                ..Default::default()
+            },
+            AgentEvent::Moved { agent, from, to } => EventRecord {
+                tick,
+                event: "Moved",
+                agent: ids.get_or_assign(*agent),
+                x: Some(to.0),
+                y: Some(to.1),
+                from_x: Some(from.0),
+                from_y: Some(from.1),
+                ..Default::default()
+            },
+            AgentEvent::AteFood { agent, food, nutrition } => {
+                let record = EventRecord {
+                    tick,
+                    event: "AteFood",
+                    agent: ids.get_or_assign(*agent),
+                    other: Some(ids.get_or_assign(*food)),
+                    nutrition: Some(*nutrition),
+                    ..Default::default()
+                };
+                // Eaten food is gone; its id is never needed again.
+                ids.forget(*food);
+                record
+            }
+            AgentEvent::AteCarriedFood { agent, nutrition } => EventRecord {
+                tick,
+                event: "AteCarriedFood",
+                agent: ids.get_or_assign(*agent),
+                nutrition: Some(*nutrition),
+                ..Default::default()
+            },
+            AgentEvent::PickedUpFood { agent, food } => {
+                let record = EventRecord {
+                    tick,
+                    event: "PickedUpFood",
+                    agent: ids.get_or_assign(*agent),
+                    other: Some(ids.get_or_assign(*food)),
+                    ..Default::default()
+                };
+                // The item now lives in the pack, not as an entity.
+                ids.forget(*food);
+                record
+            }
+            AgentEvent::Interacted { agent, with, outcome } => EventRecord {
+                tick,
+                event: "Interacted",
+                agent: ids.get_or_assign(*agent),
+                other: Some(ids.get_or_assign(*with)),
+                outcome: Some(outcome.as_str()),
+                ..Default::default()
+            },
+            AgentEvent::StateChanged { agent, from, to } => EventRecord {
+                tick,
+                event: "StateChanged",
+                agent: ids.get_or_assign(*agent),
+                from_state: Some(from.clone()),
+                to_state: Some(to.clone()),
+                ..Default::default()
+            },
+            AgentEvent::Died { agent, pos, cause, age } => {
+                let record = EventRecord {
+                    tick,
+                    event: "Died",
+                    agent: ids.get_or_assign(*agent),
+                    x: Some(pos.0),
+                    y: Some(pos.1),
+                    cause: Some(cause.as_str()),
+                    age: Some(*age),
+                    ..Default::default()
+                };
+                // Dead agents never come back.
+                ids.forget(*agent);
+                record
+            }
+        }
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.tick,
+            self.event,
+            self.agent,
+            opt(&self.other),
+            opt(&self.agent_type),
+            opt(&self.x),
+            opt(&self.y),
+            opt(&self.from_x),
+            opt(&self.from_y),
+            opt(&self.nutrition),
+            opt(&self.from_state),
+            opt(&self.to_state),
+            // ';'-separated so the column stays a single CSV field.
+            self.parents.iter().map(u64::to_string).collect::<Vec<_>>().join(";"),
+            opt(&self.cause),
+            opt(&self.age),
+            opt(&self.outcome),
+        )
+    }
+}
+
+/// `events.jsonl` + `med_run` -> `events.med_run.jsonl`; used when one invocation runs several profiles.
+pub fn per_profile_path(path: &str, label: &str) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("events");
+    let file_name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, label, ext),
+        None => format!("{}.{}", stem, label),
+    };
+    path.with_file_name(file_name).to_string_lossy().into_owned()
+}
+
+/// ECS resource: where events go, if anywhere. Always present; disabled unless `--events-out` is set.
+pub struct EventSink {
+    out: Option<(BufWriter<File>, EventFormat)>,
+    /// Tick the next batch of events belongs to.
+    pub tick: u64,
+}
+
+impl Default for EventSink {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl EventSink {
+    pub fn disabled() -> Self {
+        Self { out: None, tick: 0 }
+    }
+
+    /// Creates (truncates) `path`; the format follows the extension. `start_tick` is
+    /// non-zero when resuming from a snapshot.
+    pub fn create<P: AsRef<Path>>(path: P, start_tick: u64) -> io::Result<Self> {
+        let format = EventFormat::from_path(path.as_ref());
+        let mut writer = BufWriter::new(File::create(path)?);
+        if format == EventFormat::Csv {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        Ok(Self { out: Some((writer, format)), tick: start_tick })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    pub fn write(&mut self, record: &EventRecord) -> io::Result<()> {
+        let Some((writer, format)) = self.out.as_mut() else { return Ok(()) };
+        match format {
+            EventFormat::Jsonl => {
+                serde_json::to_writer(&mut *writer, record)?;
+                writeln!(writer)
+            }
+            EventFormat::Csv => writeln!(writer, "{}", record.to_csv_row()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.out.as_mut() {
+            Some((writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes this tick's `AgentEventLog` to the `EventSink`. Must run before
+/// `agent_event_log_to_gui_system`, which clears the log.
+pub fn agent_event_sink_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("AgentEventSink")
+        .write_resource::<EventSink>()
+        .write_resource::<StableIds>()
+        .read_resource::<AgentEventLog>()
+        .build(|_cmd, _world, (sink, ids, agent_event_log), _| {
+            if sink.is_enabled() {
+                let tick = sink.tick;
+                for event in &agent_event_log.0 {
+                    let record = EventRecord::from_event(event, tick, ids);
+                    if let Err(e) = sink.write(&record) {
+                        log::error!("[EVENTS] Failed to write event: {}", e);
+                    }
+                }
+                // Flush every tick: the GUI exits via process::exit, which skips destructors.
+                if let Err(e) = sink.flush() {
+                    log::error!("[EVENTS] Failed to flush event file: {}", e);
+                }
+            }
+            sink.tick += 1;
+        })
+}
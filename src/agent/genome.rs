@@ -0,0 +1,233 @@
+//! Heritable traits. Each agent carries a `Genome`; `spawn_agent` writes its values into
+//! the agent's own `AgentType` copy (and `Hunger::threshold`, `SwimmingProfile`), so
+//! every system that reads the agent's type sees its individual traits.
+//!
+//! Initial agents draw each trait uniformly within `GeneticsConfig::variance` (relative)
+//! of their type's value. Newborns take each trait from one parent at random, then each
+//! trait mutates with `mutation_rate`, by up to `mutation_scale` (relative).
+//!
+//! `--traits-out` writes one histogram row per tick, agent type and trait (see
+//! `trait_histogram_system`).
+
+use legion::{IntoQuery, SystemBuilder, World};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use crate::agent::AgentType;
+
+/// Trait names, in `Genome::values` order.
+pub const TRAITS: [&str; 5] = ["speed", "hunger_rate", "hunger_threshold", "vision", "swim_chance"];
+/// Bins per histogram row.
+pub const TRAIT_BINS: usize = 10;
+
+/// Per-type genetics parameters (the YAML `genetics` block).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GeneticsConfig {
+    /// Initial agents' traits are within ±`variance` (relative) of the type's values.
+    #[serde(default)]
+    pub variance: f32,
+    /// Chance that each of a newborn's traits mutates.
+    #[serde(default = "default_mutation_rate")]
+    pub mutation_rate: f32,
+    /// Largest relative change a mutation makes.
+    #[serde(default = "default_mutation_scale")]
+    pub mutation_scale: f32,
+}
+
+fn default_mutation_rate() -> f32 { 0.1 }
+fn default_mutation_scale() -> f32 { 0.1 }
+
+impl Default for GeneticsConfig {
+    fn default() -> Self {
+        Self { variance: 0.0, mutation_rate: default_mutation_rate(), mutation_scale: default_mutation_scale() }
+    }
+}
+
+/// ECS component: an agent's heritable trait values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    /// `movement_profile.speed`
+    pub speed: f32,
+    pub hunger_rate: f32,
+    pub hunger_threshold: f32,
+    pub vision: f32,
+    /// `SwimmingProfile::swim_chance_percent`
+    pub swim_chance: f32,
+}
+
+impl Genome {
+    /// The type's own values.
+    pub fn from_type(agent_type: &AgentType, swim_chance: f32) -> Self {
+        Self {
+            speed: agent_type.movement_profile.speed,
+            hunger_rate: agent_type.hunger_rate,
+            hunger_threshold: agent_type.hunger_threshold,
+            vision: agent_type.vision,
+            swim_chance,
+        }
+    }
+
+    /// The type's values, each spread by `agent_type.genetics.variance`. Draws nothing
+    /// from `rng` when the variance is zero.
+    pub fn sample<R: Rng>(agent_type: &AgentType, swim_chance: f32, rng: &mut R) -> Self {
+        let genome = Self::from_type(agent_type, swim_chance);
+        let variance = agent_type.genetics.variance;
+        if variance <= 0.0 {
+            return genome;
+        }
+        Self::from_values(genome.values().map(|v| v * (1.0 + rng.gen_range(-variance..=variance))))
+    }
+
+    pub fn values(&self) -> [f32; 5] {
+        [self.speed, self.hunger_rate, self.hunger_threshold, self.vision, self.swim_chance]
+    }
+
+    /// Builds a genome from `values` (in `TRAITS` order), clamped to workable ranges.
+    pub fn from_values(values: [f32; 5]) -> Self {
+        let [speed, hunger_rate, hunger_threshold, vision, swim_chance] = values;
+        Self {
+            speed: speed.max(0.1),
+            hunger_rate: hunger_rate.max(0.0),
+            hunger_threshold: hunger_threshold.max(0.0),
+            vision: vision.max(1.0),
+            swim_chance: swim_chance.clamp(0.0, 100.0),
+        }
+    }
+
+    /// Each trait from `self` or `other` with equal chance.
+    pub fn crossover<R: Rng>(&self, other: &Genome, rng: &mut R) -> Self {
+        let (a, b) = (self.values(), other.values());
+        Self::from_values(std::array::from_fn(|i| if rng.gen_bool(0.5) { a[i] } else { b[i] }))
+    }
+
+    pub fn mutate<R: Rng>(&mut self, config: &GeneticsConfig, rng: &mut R) {
+        let scale = config.mutation_scale;
+        let values = self.values().map(|v| {
+            if scale > 0.0 && rng.gen::<f32>() < config.mutation_rate {
+                v * (1.0 + rng.gen_range(-scale..=scale))
+            } else {
+                v
+            }
+        });
+        *self = Self::from_values(values);
+    }
+
+    /// Writes the traits into an agent's own `AgentType` copy.
+    pub fn express(&self, agent_type: &mut AgentType) {
+        agent_type.movement_profile.speed = self.speed;
+        agent_type.hunger_rate = self.hunger_rate;
+        agent_type.hunger_threshold = self.hunger_threshold;
+        agent_type.vision = self.vision;
+    }
+
+    pub fn swim_chance_percent(&self) -> u8 {
+        self.swim_chance.round() as u8
+    }
+}
+
+/// Count, mean, min, max and `TRAIT_BINS` equal-width bins over [min, max].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraitHistogram {
+    pub count: usize,
+    pub mean: f32,
+    pub min: f32,
+    pub max: f32,
+    pub bins: [usize; TRAIT_BINS],
+}
+
+impl TraitHistogram {
+    pub fn from_values(values: &[f32]) -> Self {
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mut bins = [0; TRAIT_BINS];
+        for v in values {
+            let bin = if max > min { ((v - min) / (max - min) * TRAIT_BINS as f32) as usize } else { 0 };
+            bins[bin.min(TRAIT_BINS - 1)] += 1;
+        }
+        Self { count: values.len(), mean: values.iter().sum::<f32>() / values.len() as f32, min, max, bins }
+    }
+}
+
+pub fn trait_csv_header() -> String {
+    let bins: Vec<String> = (0..TRAIT_BINS).map(|i| format!("bin{}", i)).collect();
+    format!("tick,agent_type,trait,count,mean,min,max,{}", bins.join(","))
+}
+
+/// Histograms of every trait, per agent type (sorted by name), for agents with a `Genome`.
+pub fn trait_histograms(world: &World) -> Vec<(String, &'static str, TraitHistogram)> {
+    histograms(<(&AgentType, &Genome)>::query().iter(world))
+}
+
+fn histograms<'a>(agents: impl Iterator<Item = (&'a AgentType, &'a Genome)>) -> Vec<(String, &'static str, TraitHistogram)> {
+    let mut by_type: BTreeMap<String, Vec<[f32; 5]>> = BTreeMap::new();
+    for (agent_type, genome) in agents {
+        by_type.entry(agent_type.name.clone()).or_default().push(genome.values());
+    }
+    let mut rows = Vec::new();
+    for (name, genomes) in by_type {
+        for (i, trait_name) in TRAITS.iter().enumerate() {
+            let values: Vec<f32> = genomes.iter().map(|g| g[i]).collect();
+            rows.push((name.clone(), *trait_name, TraitHistogram::from_values(&values)));
+        }
+    }
+    rows
+}
+
+/// ECS resource: where trait histograms go, if anywhere. Disabled unless `--traits-out` is set.
+pub struct TraitSink {
+    out: Option<BufWriter<File>>,
+    /// Tick the next rows belong to.
+    pub tick: u64,
+}
+
+impl Default for TraitSink {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl TraitSink {
+    pub fn disabled() -> Self {
+        Self { out: None, tick: 0 }
+    }
+
+    /// Creates (truncates) `path` and writes the CSV header.
+    pub fn create<P: AsRef<Path>>(path: P, start_tick: u64) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", trait_csv_header())?;
+        Ok(Self { out: Some(writer), tick: start_tick })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    fn write_rows(&mut self, rows: &[(String, &'static str, TraitHistogram)]) -> io::Result<()> {
+        let tick = self.tick;
+        let Some(writer) = self.out.as_mut() else { return Ok(()) };
+        for (agent_type, trait_name, h) in rows {
+            let bins: Vec<String> = h.bins.iter().map(usize::to_string).collect();
+            writeln!(writer, "{},{},{},{},{},{},{},{}", tick, agent_type, trait_name, h.count, h.mean, h.min, h.max, bins.join(","))?;
+        }
+        writer.flush()
+    }
+}
+
+/// Writes this tick's trait histograms to the `TraitSink`.
+pub fn trait_histogram_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("TraitHistogramSystem")
+        .write_resource::<TraitSink>()
+        .with_query(<(&AgentType, &Genome)>::query())
+        .build(|_cmd, world, sink, query| {
+            if sink.is_enabled() {
+                let rows = histograms(query.iter(world));
+                if let Err(e) = sink.write_rows(&rows) {
+                    log::error!("[TRAITS] Failed to write trait histograms: {}", e);
+                }
+            }
+            sink.tick += 1;
+        })
+}
@@ -0,0 +1,199 @@
+//! Carrying food. Every agent has an `Inventory` holding as many food items as its
+//! `strength` (one per point). An agent that isn't hungry and has room goes out to gather
+//! food it sees and picks it up instead of eating it (`entity_interaction_system`); once
+//! its pack is full, or there is nothing left to gather, it carries the food back to the
+//! nearest `Stockpile` and deposits it there. Hungry agents eat from their pack first,
+//! and otherwise walk to a stockpile with something they eat. An agent with a full pack
+//! and no stockpile within `STOCKPILE_RANGE` starts a new one where it stands.
+//!
+//! `action_selection_system` makes the gather and return decisions; `inventory_system`
+//! does the eating, depositing and withdrawing. Carried and stockpiled food keeps.
+
+use std::sync::{Arc, Mutex};
+use legion::{Entity, IntoQuery, SystemBuilder, World};
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentState, AgentType, Hunger};
+use crate::agent::event::{AgentEvent, AgentEventLog};
+use crate::ecs_components::{FoodStats, Position};
+use crate::food::{Diet, Food};
+
+/// Food items an agent carries per point of strength.
+pub const CARRY_PER_STRENGTH: f32 = 1.0;
+/// How far (in tiles) agents look for a stockpile to bring food to or eat from.
+pub const STOCKPILE_RANGE: f32 = 20.0;
+/// Agents closer than this to a stockpile on both axes can use it.
+pub const STOCKPILE_REACH: f32 = 1.0;
+
+/// Index of the item in `items` an agent on `diet` would rather eat: preferred kinds
+/// first, then the most nutritious. `None` if it eats none of them.
+fn best_meal(items: &[Food], diet: &Diet) -> Option<usize> {
+    items.iter().enumerate()
+        .filter(|(_, food)| diet.eats(food.kind))
+        .max_by(|(_, a), (_, b)| diet.prefers(a.kind).cmp(&diet.prefers(b.kind)).then(a.nutrition.total_cmp(&b.nutrition)))
+        .map(|(i, _)| i)
+}
+
+/// "berries 1, fish 2", in `FoodKind::ALL` order; "empty" if there is nothing.
+fn contents(items: &[Food]) -> String {
+    if items.is_empty() {
+        return "empty".to_string();
+    }
+    crate::food::FoodKind::ALL.iter()
+        .map(|kind| (kind, items.iter().filter(|food| food.kind == *kind).count()))
+        .filter(|(_, n)| *n > 0)
+        .map(|(kind, n)| format!("{} {}", kind.as_str(), n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// ECS component: the food an agent carries.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub items: Vec<Food>,
+    pub capacity: usize,
+}
+
+impl Inventory {
+    /// An empty inventory for an agent of `strength`; everyone can carry at least one item.
+    pub fn for_strength(strength: f32) -> Self {
+        Self { items: Vec::new(), capacity: ((strength * CARRY_PER_STRENGTH).round() as usize).max(1) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn has_room(&self) -> bool {
+        self.items.len() < self.capacity
+    }
+
+    /// Whether it holds anything an agent on `diet` eats.
+    pub fn has_meal(&self, diet: &Diet) -> bool {
+        best_meal(&self.items, diet).is_some()
+    }
+
+    /// Takes out the item an agent on `diet` would rather eat.
+    pub fn take_meal(&mut self, diet: &Diet) -> Option<Food> {
+        best_meal(&self.items, diet).map(|i| self.items.remove(i))
+    }
+
+    /// "2/5: berries 1, fish 1"
+    pub fn summary(&self) -> String {
+        format!("{}/{}: {}", self.items.len(), self.capacity, contents(&self.items))
+    }
+}
+
+/// ECS component: a shared food store on the tile of its `Position`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stockpile {
+    pub items: Vec<Food>,
+}
+
+impl Stockpile {
+    /// Whether it holds anything an agent on `diet` eats.
+    pub fn has_meal(&self, diet: &Diet) -> bool {
+        best_meal(&self.items, diet).is_some()
+    }
+
+    /// Takes out the item an agent on `diet` would rather eat.
+    pub fn take_meal(&mut self, diet: &Diet) -> Option<Food> {
+        best_meal(&self.items, diet).map(|i| self.items.remove(i))
+    }
+}
+
+/// Whether the stockpile at `pos` is within `STOCKPILE_RANGE` of (`x`, `y`).
+fn in_range(pos: &Position, x: f32, y: f32) -> bool {
+    (pos.x - x).powi(2) + (pos.y - y).powi(2) <= STOCKPILE_RANGE * STOCKPILE_RANGE
+}
+
+/// The stockpile closest to (`x`, `y`) within `STOCKPILE_RANGE` that `accept` allows.
+pub fn nearest_stockpile(stockpiles: &[(Position, Stockpile)], x: f32, y: f32, accept: impl Fn(&Stockpile) -> bool) -> Option<&(Position, Stockpile)> {
+    let dist_sq = |pos: &Position| (pos.x - x).powi(2) + (pos.y - y).powi(2);
+    stockpiles.iter()
+        .filter(|(pos, stockpile)| in_range(pos, x, y) && accept(stockpile))
+        .min_by(|(a, _), (b, _)| dist_sq(a).total_cmp(&dist_sq(b)))
+}
+
+/// Whether (`x`, `y`) is close enough to use the stockpile at `pos`.
+pub fn at_stockpile(pos: &Position, x: f32, y: f32) -> bool {
+    (pos.x - x).abs() < STOCKPILE_REACH && (pos.y - y).abs() < STOCKPILE_REACH
+}
+
+/// "3 stockpiles holding 17 items (berries 4, grain 13)"
+pub fn stockpile_summary(world: &World) -> String {
+    let stockpiles: Vec<&Stockpile> = <&Stockpile>::query().iter(world).collect();
+    let items: Vec<Food> = stockpiles.iter().flat_map(|s| s.items.iter().copied()).collect();
+    format!("{} stockpiles holding {} items ({})", stockpiles.len(), items.len(), contents(&items))
+}
+
+/// Hungry agents eat from their pack, or failing that take a meal from the stockpile
+/// they stand at. Agents that aren't hungry empty their pack into the stockpile they
+/// stand at; those on land with a full pack and no stockpile in range start one.
+pub fn inventory_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("InventorySystem")
+        .write_resource::<FoodStats>()
+        .write_resource::<AgentEventLog>()
+        .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
+        .read_resource::<crate::log_config::LogConfig>()
+        .with_query(<(Entity, &Position, &AgentType, &mut Hunger, &AgentState, &mut Inventory)>::query())
+        .with_query(<(Entity, &Position, &mut Stockpile)>::query())
+        .build(|cmd, world, (food_stats, agent_event_log, event_log, log_config), (agent_query, stockpile_query)| {
+            // Worked on in a copy; stockpiles started this tick have no entity until the end.
+            let mut stockpiles: Vec<(Option<Entity>, Position, Stockpile)> = stockpile_query.iter_mut(world)
+                .map(|(entity, pos, stockpile)| (Some(*entity), *pos, stockpile.clone()))
+                .collect();
+            for (entity, pos, agent_type, hunger, agent_state, inventory) in agent_query.iter_mut(world) {
+                let diet = &agent_type.diet;
+                let here = stockpiles.iter().position(|(_, s_pos, _)| at_stockpile(s_pos, pos.x, pos.y));
+                if hunger.value < agent_type.hunger_threshold {
+                    let meal = inventory.take_meal(diet).map(|food| (food, "its pack")).or_else(|| {
+                        let food = stockpiles[here?].2.take_meal(diet)?;
+                        food_stats.withdrawn += 1;
+                        Some((food, "the stockpile"))
+                    });
+                    if let Some((food, from)) = meal {
+                        hunger.eat(food.nutrition, agent_type);
+                        food_stats.collected_per_tick += 1;
+                        food_stats.eaten_carried += 1;
+                        agent_event_log.push(AgentEvent::AteCarriedFood { agent: *entity, nutrition: food.nutrition });
+                        if !log_config.quiet {
+                            event_log.lock().unwrap().push(format!("[INVENTORY] Agent {:?} eats {} from {}", entity, food.kind.as_str(), from));
+                        }
+                    }
+                    continue;
+                }
+                if inventory.is_empty() {
+                    continue;
+                }
+                let n = inventory.items.len();
+                if let Some(i) = here {
+                    stockpiles[i].2.items.append(&mut inventory.items);
+                    food_stats.deposited += n;
+                    if !log_config.quiet {
+                        event_log.lock().unwrap().push(format!("[INVENTORY] Agent {:?} deposits {} items at ({:.0}, {:.0})", entity, n, stockpiles[i].1.x, stockpiles[i].1.y));
+                    }
+                } else if !inventory.has_room() && *agent_state != AgentState::Swimming
+                    && !stockpiles.iter().any(|(_, s_pos, _)| in_range(s_pos, pos.x, pos.y)) {
+                    let at = Position { x: pos.x.floor(), y: pos.y.floor() };
+                    stockpiles.push((None, at, Stockpile { items: std::mem::take(&mut inventory.items) }));
+                    food_stats.deposited += n;
+                    food_stats.stockpiles_started += 1;
+                    if !log_config.quiet {
+                        event_log.lock().unwrap().push(format!("[INVENTORY] Agent {:?} starts a stockpile at ({:.0}, {:.0}) with {} items", entity, at.x, at.y, n));
+                    }
+                }
+            }
+            for (entity, pos, stockpile) in stockpiles {
+                match entity {
+                    Some(entity) => {
+                        if let Ok((_, _, s)) = stockpile_query.get_mut(world, entity) {
+                            *s = stockpile;
+                        }
+                    }
+                    None => {
+                        cmd.push((pos, stockpile));
+                    }
+                }
+            }
+        })
+}
@@ -1,3 +1,4 @@
+use rand::Rng;
 use serde::{Serialize, Deserialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -9,6 +10,79 @@ pub struct MLPConfig {
     pub biases: Vec<Vec<f32>>,  // Each layer's biases
 }
 
+impl MLPConfig {
+    /// Checks that `weights`/`biases` match `input_size`, `hidden_sizes` and `output_size`.
+    pub fn validate(&self) -> Result<(), String> {
+        let sizes: Vec<usize> = std::iter::once(self.input_size)
+            .chain(self.hidden_sizes.iter().copied())
+            .chain(std::iter::once(self.output_size))
+            .collect();
+        let layers = sizes.len() - 1;
+        if self.weights.len() != layers || self.biases.len() != layers {
+            return Err(format!("expected {} weight and bias layers, got {} and {}", layers, self.weights.len(), self.biases.len()));
+        }
+        for (l, (w, b)) in self.weights.iter().zip(&self.biases).enumerate() {
+            let (inputs, outputs) = (sizes[l], sizes[l + 1]);
+            if w.len() != outputs || b.len() != outputs || w.iter().any(|row| row.len() != inputs) {
+                return Err(format!("layer {} should be {} outputs x {} inputs", l, outputs, inputs));
+            }
+        }
+        Ok(())
+    }
+
+    /// A network of the given shape with weights and biases drawn uniformly from -1..1.
+    pub fn random<R: Rng>(input_size: usize, hidden_sizes: &[usize], output_size: usize, rng: &mut R) -> Self {
+        let sizes: Vec<usize> = std::iter::once(input_size)
+            .chain(hidden_sizes.iter().copied())
+            .chain(std::iter::once(output_size))
+            .collect();
+        let mut weights = Vec::with_capacity(sizes.len() - 1);
+        let mut biases = Vec::with_capacity(sizes.len() - 1);
+        for pair in sizes.windows(2) {
+            let (inputs, outputs) = (pair[0], pair[1]);
+            weights.push((0..outputs).map(|_| (0..inputs).map(|_| rng.gen_range(-1.0..=1.0)).collect()).collect());
+            biases.push((0..outputs).map(|_| rng.gen_range(-1.0..=1.0)).collect());
+        }
+        Self { input_size, hidden_sizes: hidden_sizes.to_vec(), output_size, weights, biases }
+    }
+
+    /// Nudges each weight and bias, with probability `rate`, by a uniform amount in -`scale`..`scale`.
+    pub fn mutate<R: Rng>(&mut self, rng: &mut R, rate: f64, scale: f32) {
+        let params = self.weights.iter_mut().flatten().flatten()
+            .chain(self.biases.iter_mut().flatten());
+        for param in params {
+            if rng.gen_bool(rate) {
+                *param += rng.gen_range(-scale..=scale);
+            }
+        }
+    }
+
+    /// Runs the network without building an `MLP` (no weight copies).
+    pub fn forward(&self, input: Vec<f32>) -> Vec<f32> {
+        forward_layers(self.weights.iter().zip(self.biases.iter()), input)
+    }
+}
+
+/// Dense layers with ReLU between them and a linear output layer.
+fn forward_layers<'a>(layers: impl ExactSizeIterator<Item = (&'a Vec<Vec<f32>>, &'a Vec<f32>)>, mut input: Vec<f32>) -> Vec<f32> {
+    let last = layers.len().saturating_sub(1);
+    for (layer_idx, (weights, biases)) in layers.enumerate() {
+        let mut output = vec![0.0; biases.len()];
+        for (j, bias) in biases.iter().enumerate() {
+            output[j] = *bias;
+            for (i, inp) in input.iter().enumerate() {
+                output[j] += weights[j][i] * inp;
+            }
+            // Simple ReLU activation except for last layer
+            if layer_idx != last {
+                output[j] = output[j].max(0.0);
+            }
+        }
+        input = output;
+    }
+    input
+}
+
 #[derive(Clone, Debug)]
 pub struct MLP {
     pub layers: Vec<(Vec<Vec<f32>>, Vec<f32>)>, // (weights, biases) for each layer
@@ -22,21 +96,7 @@ impl MLP {
         MLP { layers }
     }
 
-    pub fn forward(&self, mut input: Vec<f32>) -> Vec<f32> {
-        for (layer_idx, (weights, biases)) in self.layers.iter().enumerate() {
-            let mut output = vec![0.0; biases.len()];
-            for (j, bias) in biases.iter().enumerate() {
-                output[j] = *bias;
-                for (i, inp) in input.iter().enumerate() {
-                    output[j] += weights[j][i] * inp;
-                }
-                // Simple ReLU activation except for last layer
-                if layer_idx != self.layers.len() - 1 {
-                    output[j] = output[j].max(0.0);
-                }
-            }
-            input = output;
-        }
-        input
+    pub fn forward(&self, input: Vec<f32>) -> Vec<f32> {
+        forward_layers(self.layers.iter().map(|(w, b)| (w, b)), input)
     }
 }
@@ -4,6 +4,15 @@ pub mod mlp;
 pub mod swimming;
 pub mod event;
 pub mod event_log_bridge;
+pub mod event_sink;
+pub mod energy;
+pub mod decision;
+pub mod reproduction;
+pub mod genome;
+pub mod aging;
+pub mod work;
+pub mod disease;
+pub mod inventory;
 
 // Public API: only export what is needed outside the agent module
 pub use components::{AgentType, Hunger, Energy, MovementProfile, MovementEffect, agent_state::AgentState, MovementHistory, DecisionEngineConfig, InteractionState, RecentInteraction};
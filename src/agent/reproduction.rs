@@ -0,0 +1,153 @@
+//! Reproduction. Two well-fed agents of the same type that meet in
+//! `entity_interaction_system` (see `AgentContacts`) may conceive: both pay a hunger
+//! cost and go on cooldown, and the first of the pair carries the child for
+//! `gestation_ticks`. The child is then spawned next to its mother through
+//! `spawn_agent_with_parents`, with the genome drawn at conception (crossover of the
+//! parents' genomes, then mutation; see `genome`). All parameters come from the type's `reproduction`
+//! block in `agent_types.yaml`; types without one never reproduce.
+
+use legion::{Entity, IntoQuery, SystemBuilder};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex};
+use crate::agent::{AgentType, Hunger};
+use crate::agent::event::AgentEventLog;
+use crate::agent::genome::Genome;
+use crate::agent::systems::spawn_agent_with_parents;
+use crate::ecs_components::{AgentContacts, Demographics, Position};
+use crate::map::Map;
+use crate::sim_rng::{RngStream, SimRng};
+
+/// Per-type reproduction parameters (the YAML `reproduction` block).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReproductionConfig {
+    /// Both parents need at least this much hunger (i.e. to be this well fed).
+    #[serde(default = "default_min_hunger")]
+    pub min_hunger: f32,
+    /// Hunger each parent spends on conception.
+    #[serde(default = "default_hunger_cost")]
+    pub hunger_cost: f32,
+    #[serde(default = "default_gestation_ticks")]
+    pub gestation_ticks: u32,
+    /// Ticks before a parent can conceive again, and before a newborn can at all.
+    #[serde(default = "default_cooldown_ticks")]
+    pub cooldown_ticks: u32,
+    /// Chance that a meeting of two eligible agents leads to conception.
+    #[serde(default = "default_chance")]
+    pub chance: f32,
+}
+
+fn default_min_hunger() -> f32 { 70.0 }
+fn default_hunger_cost() -> f32 { 20.0 }
+fn default_gestation_ticks() -> u32 { 30 }
+fn default_cooldown_ticks() -> u32 { 200 }
+fn default_chance() -> f32 { 0.5 }
+
+impl Default for ReproductionConfig {
+    fn default() -> Self {
+        Self {
+            min_hunger: default_min_hunger(),
+            hunger_cost: default_hunger_cost(),
+            gestation_ticks: default_gestation_ticks(),
+            cooldown_ticks: default_cooldown_ticks(),
+            chance: default_chance(),
+        }
+    }
+}
+
+/// ECS component: an agent's reproductive state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fertility {
+    /// Ticks until the agent can conceive again.
+    pub cooldown: u32,
+    pub pregnancy: Option<Pregnancy>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pregnancy {
+    /// `None` if the father could not be tracked (e.g. he died before a snapshot restore).
+    pub father: Option<Entity>,
+    pub ticks_remaining: u32,
+    /// The child's genome; `None` if a parent had none.
+    pub genome: Option<Genome>,
+}
+
+impl Fertility {
+    /// Whether an agent of `agent_type` with `hunger` can conceive now.
+    pub fn can_conceive(&self, agent_type: &AgentType, hunger: &Hunger) -> bool {
+        match &agent_type.reproduction {
+            Some(config) => self.cooldown == 0 && self.pregnancy.is_none() && hunger.value >= config.min_hunger,
+            None => false,
+        }
+    }
+}
+
+/// Counts down cooldowns and pregnancies, queues births, and starts pregnancies for
+/// this tick's eligible contacts. Births are applied when the command buffer flushes.
+pub fn reproduction_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("ReproductionSystem")
+        .read_resource::<AgentContacts>()
+        .write_resource::<SimRng>()
+        .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
+        .read_resource::<crate::log_config::LogConfig>()
+        .with_query(<(Entity, &Position, &AgentType, &mut Hunger, &mut Fertility, Option<&Genome>)>::query())
+        .build(|cmd, world, (contacts, sim_rng, event_log, log_config), query| {
+            for (entity, pos, agent_type, _, fertility, _) in query.iter_mut(world) {
+                fertility.cooldown = fertility.cooldown.saturating_sub(1);
+                let Some(pregnancy) = &mut fertility.pregnancy else { continue };
+                pregnancy.ticks_remaining = pregnancy.ticks_remaining.saturating_sub(1);
+                if pregnancy.ticks_remaining > 0 {
+                    continue;
+                }
+                let parents: Vec<Entity> = std::iter::once(*entity).chain(pregnancy.father).collect();
+                let genome = pregnancy.genome.take();
+                fertility.pregnancy = None;
+                if !log_config.quiet {
+                    event_log.lock().unwrap().push(format!("[BIRTH] Agent {:?} gives birth at ({:.2}, {:.2})", entity, pos.x, pos.y));
+                }
+                let (pos, agent_type) = (*pos, agent_type.clone());
+                cmd.exec_mut(move |world, resources| {
+                    let map = resources.get::<Map>().expect("Map missing");
+                    let mut agent_event_log = resources.get_mut::<AgentEventLog>().expect("AgentEventLog missing");
+                    let mut sim_rng = resources.get_mut::<SimRng>().expect("SimRng missing");
+                    let rng = sim_rng.stream(RngStream::Reproduction);
+                    let cooldown = agent_type.reproduction.as_ref().map_or(0, |c| c.cooldown_ticks);
+                    if let Some(mut demographics) = resources.get_mut::<Demographics>() {
+                        demographics.births += 1;
+                    }
+                    let child = spawn_agent_with_parents(world, pos, agent_type.clone(), &map, &mut agent_event_log, rng, parents.clone(), genome.clone());
+                    if let Some(mut entry) = world.entry(child) {
+                        entry.add_component(Fertility { cooldown, pregnancy: None });
+                    }
+                });
+            }
+            let rng = sim_rng.stream(RngStream::Reproduction);
+            for &(mother, father) in &contacts.0 {
+                let mut eligible = |e: Entity| {
+                    let (_, _, agent_type, hunger, fertility, genome) = query.get_mut(world, e).ok()?;
+                    fertility.can_conceive(agent_type, hunger).then(|| (agent_type.name.clone(), agent_type.reproduction.clone(), genome.cloned(), agent_type.genetics.clone()))
+                };
+                let (Some((mother_type, Some(config), mother_genome, genetics)), Some((father_type, _, father_genome, _))) = (eligible(mother), eligible(father)) else { continue };
+                if mother_type != father_type || rng.gen::<f32>() >= config.chance {
+                    continue;
+                }
+                let child_genome = mother_genome.zip(father_genome).map(|(m, f)| {
+                    let mut child = m.crossover(&f, rng);
+                    child.mutate(&genetics, rng);
+                    child
+                });
+                for parent in [mother, father] {
+                    if let Ok((_, _, _, hunger, fertility, _)) = query.get_mut(world, parent) {
+                        hunger.value -= config.hunger_cost;
+                        fertility.cooldown = config.cooldown_ticks;
+                    }
+                }
+                if let Ok((_, pos, _, _, fertility, _)) = query.get_mut(world, mother) {
+                    fertility.pregnancy = Some(Pregnancy { father: Some(father), ticks_remaining: config.gestation_ticks, genome: child_genome });
+                    if !log_config.quiet {
+                        event_log.lock().unwrap().push(format!("[CONCEIVE] Agent {:?} and {:?} conceive at ({:.2}, {:.2})", mother, father, pos.x, pos.y));
+                    }
+                }
+            }
+        })
+}
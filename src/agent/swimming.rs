@@ -1,19 +1,63 @@
+//! Swimming. An idle agent sometimes (`SwimmingProfile::swim_chance_percent`) walks to
+//! the shore of the nearest water, steps in and swims about for a few ticks, then heads
+//! back to land. `action_selection_system` makes the decision and enters the water;
+//! `swimming_system` does the swimming and the return.
+
 use legion::*;
 use rand::seq::SliceRandom;
+use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
+use crate::agent::AgentType;
+use crate::map::{Map, Terrain};
+use crate::navigation::pathfinding::{a_star_path, tile_cost};
+
+/// How many ticks a swim lasts, drawn when the agent enters the water.
+pub const SWIM_TICKS: RangeInclusive<u32> = 5..=15;
+/// Furthest (in tile steps) an agent looks for water to swim in, or land to swim back to.
+pub const WATER_SEARCH_RADIUS: i32 = 30;
+
+/// Whether the agent can walk onto `terrain`.
+fn walkable(agent_type: &AgentType, terrain: Terrain) -> bool {
+    tile_cost(terrain, agent_type, None).is_some()
+}
+
+/// The nearest water within `WATER_SEARCH_RADIUS` of (`x`, `y`), and the walkable tile
+/// next to it that is closest to the agent. Returns `(water, shore)` tile coordinates.
+pub fn find_shore(map: &Map, agent_type: &AgentType, x: f32, y: f32) -> Option<((i32, i32), (i32, i32))> {
+    let (wx, wy, _) = map.find_nearest_water_within(x as i32, y as i32, WATER_SEARCH_RADIUS)?;
+    let dist_sq = |(sx, sy): (i32, i32)| (sx as f32 + 0.5 - x).powi(2) + (sy as f32 + 0.5 - y).powi(2);
+    [(wx - 1, wy), (wx + 1, wy), (wx, wy - 1), (wx, wy + 1)]
+        .into_iter()
+        .filter(|&(sx, sy)| sx >= 0 && sy >= 0 && sx < map.width && sy < map.height)
+        .filter(|&(sx, sy)| walkable(agent_type, map.tiles[sy as usize][sx as usize]))
+        .min_by(|a, b| dist_sq(*a).total_cmp(&dist_sq(*b)))
+        .map(|shore| ((wx, wy), shore))
+}
+
+/// Whether tile (`x`, `y`) is next to (or is) `water`, so the agent can step in.
+pub fn at_shore(x: f32, y: f32, water: (i32, i32)) -> bool {
+    (x as i32 - water.0).abs() + (y as i32 - water.1).abs() <= 1
+}
 
-/// System for random swimming movement and swim duration countdown.
+/// Moves swimming agents: a random step to neighbouring water each tick until
+/// `swim_ticks_remaining` runs out, then along an A* route (water allowed) to the
+/// nearest land, where they become Idle.
 pub fn swimming_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("SwimmingSystem")
-        .with_query(<(Entity, &mut crate::ecs_components::Position, &mut crate::agent::Hunger, &mut crate::agent::AgentState, &mut crate::agent::components::SwimmingProfile, &crate::agent::AgentType)>::query())
+        .with_query(<(Entity, &mut crate::ecs_components::Position, &mut crate::agent::Hunger, &mut crate::agent::Energy, &mut crate::agent::AgentState, &mut crate::agent::components::SwimmingProfile, &crate::agent::AgentType, Option<&mut crate::navigation::Path>)>::query())
         .read_resource::<crate::map::Map>()
         .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
         .read_resource::<crate::log_config::LogConfig>()
+        .write_resource::<crate::sim_rng::SimRng>()
         .build(move |_command_buffer, _world, resources, query| {
             let log_config = &resources.2;
-            for (entity, pos, hunger, agent_state, swimming_profile, agent_type) in query.iter_mut(_world) {
-                if *agent_state == crate::agent::AgentState::Swimming {
-                    if swimming_profile.swim_ticks_remaining == 0 {
+            let map = &resources.0;
+            for (entity, pos, hunger, energy, agent_state, swimming_profile, agent_type, maybe_path) in query.iter_mut(_world) {
+                if *agent_state != crate::agent::AgentState::Swimming {
+                    continue;
+                }
+                if swimming_profile.swim_ticks_remaining == 0 {
+                    if walkable(agent_type, map.terrain_at(pos.x, pos.y)) {
                         // Done swimming, become idle
                         *agent_state = crate::agent::AgentState::Idle;
                         if !log_config.quiet {
@@ -21,30 +65,55 @@ pub fn swimming_system() -> impl legion::systems::Runnable {
                         }
                         continue;
                     }
-                    // Pick a random adjacent water tile (including diagonals)
-                    let mut rng = rand::thread_rng();
-                    let directions = [(-1,0),(1,0),(0,-1),(0,1),(-1,-1),(-1,1),(1,-1),(1,1)];
-                    let mut water_neighbors = vec![];
-                    for (dx, dy) in &directions {
-                        let nx = pos.x as i32 + dx;
-                        let ny = pos.y as i32 + dy;
-                        if nx >= 0 && ny >= 0 && nx < resources.0.width && ny < resources.0.height {
-                            if let crate::map::Terrain::Water = resources.0.tiles[ny as usize][nx as usize] {
-                                water_neighbors.push((nx as f32, ny as f32));
+                    if let Some(path) = maybe_path {
+                        if path.waypoints.is_empty() {
+                            let start = (pos.x as i32, pos.y as i32);
+                            if let Some((lx, ly, _)) = map.find_nearest_tile(start.0, start.1, WATER_SEARCH_RADIUS, |t| walkable(agent_type, t)) {
+                                if let Some(route) = a_star_path(map, agent_type, agent_state, start, (lx, ly), 120) {
+                                    path.waypoints = route.into();
+                                }
+                            }
+                        }
+                        if !path.waypoints.is_empty() {
+                            let (travelled, spent) = crate::agent::systems::follow_waypoints(pos, path, agent_type, agent_state, map);
+                            hunger.value -= agent_type.hunger_rate * travelled;
+                            energy.value -= spent;
+                            if walkable(agent_type, map.terrain_at(pos.x, pos.y)) {
+                                path.waypoints.clear();
+                                *agent_state = crate::agent::AgentState::Idle;
+                                if !log_config.quiet {
+                                    resources.1.lock().unwrap().push(format!("[SWIM] Agent {:?} swam back to land at ({:.2}, {:.2})", entity, pos.x, pos.y));
+                                }
                             }
+                            continue;
                         }
                     }
-                    if !water_neighbors.is_empty() {
-                        let &(wx, wy) = water_neighbors.choose(&mut rng).unwrap();
-                        pos.x = wx;
-                        pos.y = wy;
-                        hunger.value -= agent_type.hunger_rate;
-                        if !log_config.quiet {
-                            resources.1.lock().unwrap().push(format!("[SWIM] Agent {:?} swims to ({:.2}, {:.2})", entity, pos.x, pos.y));
+                    // No way back to land found: keep paddling about.
+                }
+                // Pick a random adjacent water tile (including diagonals)
+                let rng = resources.3.stream(crate::sim_rng::RngStream::Swimming);
+                let directions = [(-1,0),(1,0),(0,-1),(0,1),(-1,-1),(-1,1),(1,-1),(1,1)];
+                let mut water_neighbors = vec![];
+                for (dx, dy) in &directions {
+                    let nx = pos.x as i32 + dx;
+                    let ny = pos.y as i32 + dy;
+                    if nx >= 0 && ny >= 0 && nx < map.width && ny < map.height {
+                        if let Terrain::Water = map.tiles[ny as usize][nx as usize] {
+                            water_neighbors.push((nx as f32 + 0.5, ny as f32 + 0.5));
                         }
                     }
-                    swimming_profile.swim_ticks_remaining = swimming_profile.swim_ticks_remaining.saturating_sub(1);
                 }
+                if !water_neighbors.is_empty() {
+                    let &(wx, wy) = water_neighbors.choose(rng).unwrap();
+                    pos.x = wx;
+                    pos.y = wy;
+                    hunger.value -= agent_type.hunger_rate;
+                    energy.value -= crate::agent::energy::movement_energy(map, pos.x, pos.y, 1.0);
+                    if !log_config.quiet {
+                        resources.1.lock().unwrap().push(format!("[SWIM] Agent {:?} swims to ({:.2}, {:.2})", entity, pos.x, pos.y));
+                    }
+                }
+                swimming_profile.swim_ticks_remaining = swimming_profile.swim_ticks_remaining.saturating_sub(1);
             }
         })
 }
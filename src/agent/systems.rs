@@ -2,33 +2,53 @@
 
 use crate::navigation::*;
 use legion::*;
-use rand::seq::SliceRandom;
 use rand::Rng;
 use std::collections::VecDeque;
 use std::io::Write;
-use crate::agent::event::{AgentEvent, AgentEventLog};
+use crate::agent::event::{AgentEvent, AgentEventLog, DeathCause};
+use crate::sim_rng::{RngStream, SimRng};
+use crate::agent::energy::{movement_energy, needs_rest};
+use crate::agent::decision::{decide, Action, Observation};
+use crate::food::search::nearest_reachable_food;
+use crate::agent::swimming::{at_shore, find_shore, SWIM_TICKS};
 use std::sync::{Arc, Mutex};
 
-pub fn spawn_agent(world: &mut legion::World, pos: crate::ecs_components::Position, agent_type: crate::agent::AgentType, map: &crate::map::Map, agent_event_log: &mut AgentEventLog) -> legion::Entity {
+pub fn spawn_agent<R: Rng>(world: &mut legion::World, pos: crate::ecs_components::Position, agent_type: crate::agent::AgentType, map: &crate::map::Map, agent_event_log: &mut AgentEventLog, rng: &mut R) -> legion::Entity {
+    spawn_agent_with_parents(world, pos, agent_type, map, agent_event_log, rng, Vec::new(), None)
+}
+
+/// `spawn_agent` for a newborn; the `Spawned` event records its `parents`. A `genome`
+/// drawn at conception is used as is; without one, the genome is sampled from the type.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_agent_with_parents<R: Rng>(world: &mut legion::World, pos: crate::ecs_components::Position, mut agent_type: crate::agent::AgentType, map: &crate::map::Map, agent_event_log: &mut AgentEventLog, rng: &mut R, parents: Vec<legion::Entity>, genome: Option<crate::agent::genome::Genome>) -> legion::Entity {
     log::debug!("[SPAWN_INFO] spawn_agent() called for agent type: {} at ({:.2},{:.2})", agent_type.name, pos.x, pos.y);
     log::debug!("[SPAWN] Agent spawn_agent() called");
     let _color = agent_type.color.clone();
-    let mut rng = rand::thread_rng();
-    let (tx, ty) = random_passable_target(map, &agent_type, &mut rng, None);
-    // Generate random swim_chance_percent (1-30) and add SwimmingProfile
-    let swim_chance_percent = rand::Rng::gen_range(&mut rng, 1..=30);
+    let (tx, ty) = random_passable_target(map, &agent_type, rng, None);
+    // Individual traits, expressed in the agent's own copy of its type
+    let genome = genome.unwrap_or_else(|| {
+        // Generate random swim_chance_percent (1-30) and add SwimmingProfile
+        let swim_chance_percent: u8 = rng.gen_range(1..=30);
+        crate::agent::genome::Genome::sample(&agent_type, swim_chance_percent as f32, rng)
+    });
+    genome.express(&mut agent_type);
+    let age = crate::agent::aging::Age::new(&agent_type, rng);
+    // Only the initial population (no parents) is seeded with infections
+    let health = agent_type.disease.as_ref().map(|c| crate::agent::disease::Health::new(c, parents.is_empty(), rng));
     let swimming_profile = crate::agent::components::SwimmingProfile {
-        swim_chance_percent,
+        swim_chance_percent: genome.swim_chance_percent(),
         swim_ticks_remaining: 0,
+        destination: None,
     };
     // Push normal components
     let hunger_threshold = agent_type.hunger_threshold;
+    let has_role = agent_type.role.is_some();
     let entity = world.push((
         pos,
         agent_type.clone(),
-        crate::agent::Hunger { value: 100.0, threshold: hunger_threshold },
-        crate::agent::Energy { value: 100.0 },
-        crate::agent::InteractionState { target: None, ticks: 0, last_partner: None, cooldown: 0, recent_partners: VecDeque::new() },
+        crate::agent::Hunger { value: agent_type.hunger_cap, threshold: hunger_threshold },
+        crate::agent::Energy { value: agent_type.stamina },
+        crate::agent::InteractionState { target: None, ticks: 0, last_partner: None, cooldown: 0, recent_partners: VecDeque::new(), food_tip: None },
         Target { x: tx, y: ty, stuck_ticks: 0, path_ticks: None, ticks_to_reach: None },
         Path { waypoints: VecDeque::new() },
         crate::agent::AgentState::Idle,
@@ -37,14 +57,69 @@ pub fn spawn_agent(world: &mut legion::World, pos: crate::ecs_components::Positi
         agent: entity,
         agent_type: agent_type.name.clone(),
         pos: (pos.x, pos.y),
+        parents,
     });
     log::debug!("[SPAWN_INFO] Agent {:?} spawned at ({:.2},{:.2}) with state {:?}", entity, pos.x, pos.y, crate::agent::AgentState::Idle);
     log::debug!("[SPAWN] Agent {:?} spawned at ({:.2},{:.2}) with state {:?}", entity, pos.x, pos.y, crate::agent::AgentState::Idle);
-    world.extend(vec![(entity, crate::agent::components::MovementHistory::new(12))]);
-    world.extend(vec![(entity, swimming_profile)]);
+    let mut entry = world.entry(entity).expect("agent just pushed");
+    entry.add_component(crate::agent::components::MovementHistory::new(12));
+    entry.add_component(swimming_profile);
+    entry.add_component(crate::agent::reproduction::Fertility::default());
+    entry.add_component(genome);
+    entry.add_component(age);
+    entry.add_component(crate::agent::inventory::Inventory::for_strength(agent_type.strength));
+    if has_role {
+        entry.add_component(crate::agent::work::Work::default());
+    }
+    if let Some(health) = health {
+        entry.add_component(health);
+    }
     entity
 }
 
+/// Movement points needed per tile on the tile at (`x`, `y`); tiles the agent couldn't
+/// normally enter (it's already on them, or the map changed) count as grass.
+fn step_cost(map: &crate::map::Map, agent_type: &crate::agent::AgentType, agent_state: &crate::agent::AgentState, x: f32, y: f32) -> f32 {
+    pathfinding::tile_cost(map.terrain_at(x, y), agent_type, Some(agent_state)).unwrap_or(1.0)
+}
+
+/// Spends one tick's movement budget (`movement_profile.speed` points scaled by
+/// `damping`) walking along `path`, popping every waypoint reached. Each tile costs its
+/// A* tile cost per unit of distance. Returns the distance travelled and the energy spent.
+pub fn follow_waypoints(
+    pos: &mut crate::ecs_components::Position,
+    path: &mut Path,
+    agent_type: &crate::agent::AgentType,
+    agent_state: &crate::agent::AgentState,
+    map: &crate::map::Map,
+) -> (f32, f32) {
+    let mut budget = agent_type.movement_profile.speed * agent_type.damping;
+    let mut travelled = 0.0;
+    let mut spent = 0.0;
+    while budget > f32::EPSILON {
+        let Some(&(tx, ty)) = path.waypoints.front() else { break };
+        let cost = step_cost(map, agent_type, agent_state, tx, ty);
+        let dist = ((tx - pos.x).powi(2) + (ty - pos.y).powi(2)).sqrt();
+        let reach = budget / cost;
+        let step = reach.min(dist);
+        if dist > 0.0 {
+            pos.x += (tx - pos.x) / dist * step;
+            pos.y += (ty - pos.y) / dist * step;
+        }
+        travelled += step;
+        spent += movement_energy(map, tx, ty, step);
+        if reach >= dist {
+            pos.x = tx;
+            pos.y = ty;
+            path.waypoints.pop_front();
+            budget -= dist * cost;
+        } else {
+            budget = 0.0;
+        }
+    }
+    (travelled, spent)
+}
+
 // --- ECS Agent Path Following System ---
 pub fn path_following_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("PathFollowingSystem")
@@ -62,7 +137,7 @@ pub fn path_following_system() -> impl legion::systems::Runnable {
             let move_time = 0.0f64;
             let mut waypoint_time = 0.0f64;
             let mut snap_time = 0.0f64;
-            for (entity, pos, agent_type, hunger, _energy, mut maybe_target, mut maybe_path, agent_state) in query.iter_mut(world) {
+            for (entity, pos, agent_type, hunger, energy, mut maybe_target, mut maybe_path, agent_state) in query.iter_mut(world) {
                 agent_count += 1;
                 log::debug!("[PATHFOLLOW] Agent {:?} state: {:?} pos: ({:.2},{:.2}) hunger: {:.2}/{:.2} state: {:?}", entity, agent_state, pos.x, pos.y, hunger.value, hunger.threshold, agent_state);
                 if *agent_state == crate::agent::AgentState::Idle
@@ -73,19 +148,13 @@ pub fn path_following_system() -> impl legion::systems::Runnable {
                             log::debug!("[PATHFOLLOW] Agent {:?} path waypoints: {:?}", entity, path.waypoints);
                             if !path.waypoints.is_empty() {
                                 let wp_start = Instant::now();
-                                let (tx, ty) = path.waypoints[0];
-                                let dx = tx as f32 - pos.x;
-                                let dy = ty as f32 - pos.y;
-                                let dist = (dx * dx + dy * dy).sqrt();
-                                let step = agent_type.movement_profile.speed.min(dist);
-                                log::debug!("[PATHFOLLOW] Agent {:?} moving from ({:.2},{:.2}) toward ({:.2},{:.2}) step: {:.2} dist: {:.2}", entity, pos.x, pos.y, tx, ty, step, dist);
-                                pos.x += dx / dist * step;
-                                pos.y += dy / dist * step;
-                                hunger.value -= agent_type.hunger_rate * step;
+                                let (travelled, spent) = follow_waypoints(pos, path, agent_type, agent_state, &resources.0);
+                                log::debug!("[PATHFOLLOW] Agent {:?} travelled {:.2} to ({:.2},{:.2}), {} waypoints left", entity, travelled, pos.x, pos.y, path.waypoints.len());
+                                hunger.value -= agent_type.hunger_rate * travelled;
+                                energy.value -= spent;
                                 if !log_config.quiet {
                                     resources.1.lock().unwrap().push(format!("[MOVE] Agent {:?} moved to ({:.2}, {:.2}) via path (speed {:.2})", entity, pos.x, pos.y, agent_type.movement_profile.speed));
                                 }
-                                path.waypoints.pop_front();
                                 waypoint_time += wp_start.elapsed().as_secs_f64();
                                 moved += 1;
                             } else {
@@ -103,14 +172,16 @@ pub fn path_following_system() -> impl legion::systems::Runnable {
                         } else {
                             log::debug!("[PATHFOLLOW] Agent {:?} has no path, moving directly toward target ({:.2},{:.2})", entity, target.x, target.y);
                             let dist = ((target.x - pos.x).powi(2) + (target.y - pos.y).powi(2)).sqrt();
-                            let step = agent_type.movement_profile.speed.min(dist);
+                            let speed = agent_type.movement_profile.speed * agent_type.damping / step_cost(&resources.0, agent_type, agent_state, pos.x, pos.y);
+                            let step = speed.min(dist);
                             log::debug!("[PATHFOLLOW] Agent {:?} direct move step: {:.2} dist: {:.2}", entity, step, dist);
                             if dist > 0.1 {
                                 pos.x += (target.x - pos.x) / dist * step;
                                 pos.y += (target.y - pos.y) / dist * step;
                                 hunger.value -= agent_type.hunger_rate * step;
+                                energy.value -= movement_energy(&resources.0, pos.x, pos.y, step);
                                 if !log_config.quiet {
-                                    resources.1.lock().unwrap().push(format!("[MOVE] Agent {:?} moved to ({:.2}, {:.2}) (speed {:.2})", entity, pos.x, pos.y, agent_type.movement_profile.speed));
+                                    resources.1.lock().unwrap().push(format!("[MOVE] Agent {:?} moved to ({:.2}, {:.2}) (speed {:.2})", entity, pos.x, pos.y, speed));
                                 }
                                 moved += 1;
                             } else {
@@ -138,61 +209,241 @@ pub fn path_following_system() -> impl legion::systems::Runnable {
         })
 }
 
+/// Fills `path` with an A* route from `pos` to `goal` and sets the agent Moving, or
+/// Idle if there is no route. Logs to `event_log` when given.
+pub(crate) fn assign_path(
+    map: &crate::map::Map,
+    agent_type: &crate::agent::AgentType,
+    agent_state: &mut crate::agent::AgentState,
+    pos: &crate::ecs_components::Position,
+    path: &mut Path,
+    goal: (f32, f32),
+    event_log: Option<&Mutex<crate::event_log::EventLog>>,
+) {
+    let astar_path = pathfinding::a_star_path(map, agent_type, agent_state, (pos.x as i32, pos.y as i32), (goal.0 as i32, goal.1 as i32), 120);
+    set_path(agent_state, path, astar_path, event_log);
+}
+
+/// Follows `route` if there is one (agent Moving), otherwise leaves the agent Idle.
+fn set_path(
+    agent_state: &mut crate::agent::AgentState,
+    path: &mut Path,
+    route: Option<Vec<(f32, f32)>>,
+    event_log: Option<&Mutex<crate::event_log::EventLog>>,
+) {
+    if let Some(route) = route {
+        path.waypoints = VecDeque::from(route);
+        if let Some(event_log) = event_log {
+            event_log.lock().unwrap().push(format!("[PATHFIND] Path assigned: {} waypoints", path.waypoints.len()));
+        }
+        *agent_state = crate::agent::AgentState::Moving;
+    } else {
+        if let Some(event_log) = event_log {
+            event_log.lock().unwrap().push("[PATHFIND] No path found".to_string());
+        }
+        *agent_state = crate::agent::AgentState::Idle;
+    }
+}
+
 // --- ECS Agent Action Selection System ---
 pub fn action_selection_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("ActionSelectionSystem")
-        .with_query(<(Entity, &mut crate::ecs_components::Position, &crate::agent::AgentType, &mut crate::agent::Hunger, &mut crate::agent::Energy, Option<&mut Target>, Option<&mut Path>, &mut crate::agent::AgentState)>::query())
+        .with_query(<(Entity, &mut crate::ecs_components::Position, &crate::agent::AgentType, &mut crate::agent::Hunger, &mut crate::agent::Energy, Option<&mut Target>, Option<&mut Path>, (&mut crate::agent::AgentState, Option<&mut crate::agent::components::SwimmingProfile>, Option<&mut crate::agent::InteractionState>, Option<&crate::agent::inventory::Inventory>))>::query())
+        .with_query(<(&crate::ecs_components::Position, &crate::agent::inventory::Stockpile)>::query())
         .read_resource::<crate::map::Map>()
-        .read_resource::<crate::ecs_components::FoodPositions>()
+        .write_resource::<crate::food::FoodClaims>()
         .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
         .read_resource::<crate::log_config::LogConfig>()
-        .build(move |_command_buffer, world, resources, query| {
+        .write_resource::<SimRng>()
+        .read_resource::<crate::spatial::SpatialIndex>()
+        .read_resource::<crate::social::SocialGraph>()
+        .build(move |_command_buffer, world, resources, (query, stockpile_query)| {
+            let stockpiles: Vec<_> = stockpile_query.iter(world).map(|(pos, stockpile)| (*pos, stockpile.clone())).collect();
             let log_config = &resources.3;
-            let mut rng = rand::thread_rng();
-            let food_positions = &resources.1.0;
+            let rng = resources.4.stream(RngStream::ActionSelection);
+            let claims = &mut *resources.1;
+            let index = &resources.5;
+            let social = &resources.6;
             let mut _matched = 0;
-            for (entity, pos, agent_type, hunger, _energy, mut maybe_target, mut maybe_path, agent_state) in query.iter_mut(world) {
+            for (entity, pos, agent_type, hunger, energy, mut maybe_target, mut maybe_path, (agent_state, mut maybe_swimming, mut maybe_interaction, maybe_inventory)) in query.iter_mut(world) {
                 _matched += 1;
                 log::debug!("[ACTION] Matching agent {:?} state: {:?} hunger: {:.2}/{:.2}", entity, agent_state, hunger.value, hunger.threshold);
+                // Tired agents on land head for a house within vision, or else stop where
+                // they are, and rest (see energy::rest_system)
+                if matches!(*agent_state, crate::agent::AgentState::Idle | crate::agent::AgentState::Arrived | crate::agent::AgentState::Moving)
+                    && needs_rest(energy, agent_type) {
+                    let map = &resources.0;
+                    if *agent_state == crate::agent::AgentState::Moving
+                        && maybe_target.as_deref().is_some_and(|t| map.structures.is_shelter(t.x as i32, t.y as i32)) {
+                        continue;
+                    }
+                    claims.release_agent(*entity);
+                    if let Some(path) = maybe_path.as_mut() {
+                        path.waypoints.clear();
+                    }
+                    let (tx, ty) = (pos.x as i32, pos.y as i32);
+                    let house = if map.structures.is_shelter(tx, ty) {
+                        None
+                    } else {
+                        map.find_nearest_tile_where(tx, ty, agent_type.vision as i32, |x, y| map.structures.is_shelter(x, y))
+                    };
+                    if let (Some((hx, hy, _)), Some(target), Some(path)) = (house, maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                        target.x = hx as f32 + 0.5;
+                        target.y = hy as f32 + 0.5;
+                        assign_path(map, agent_type, agent_state, pos, path, (target.x, target.y), (!log_config.quiet).then_some(&*resources.2));
+                        if *agent_state == crate::agent::AgentState::Moving {
+                            if !log_config.quiet {
+                                resources.2.lock().unwrap().push(format!("[REST] Agent {:?} heads for shelter at ({}, {}) energy {:.1}", entity, hx, hy, energy.value));
+                            }
+                            continue;
+                        }
+                    }
+                    *agent_state = crate::agent::AgentState::Resting;
+                    if !log_config.quiet {
+                        resources.2.lock().unwrap().push(format!("[REST] Agent {:?} rests at ({:.2}, {:.2}) energy {:.1}", entity, pos.x, pos.y, energy.value));
+                    }
+                    continue;
+                }
+                // Partners stand still while they interact (see crate::interaction).
+                if maybe_interaction.as_deref().is_some_and(|i| i.target.is_some())
+                    && matches!(*agent_state, crate::agent::AgentState::Idle | crate::agent::AgentState::Arrived | crate::agent::AgentState::Moving) {
+                    if let Some(path) = maybe_path.as_mut() {
+                        path.waypoints.clear();
+                    }
+                    *agent_state = crate::agent::AgentState::Idle;
+                    continue;
+                }
                 if *agent_state == crate::agent::AgentState::Idle || *agent_state == crate::agent::AgentState::Arrived {
                     log::debug!("[ACTION_BRANCH] Agent {:?} entered Idle/Arrived branch. State: {:?} Hunger: {:.2}/{:.2}", entity, agent_state, hunger.value, hunger.threshold);
-                    let mut possible_actions = Vec::new();
-                    if hunger.value < agent_type.hunger_threshold && !food_positions.is_empty() {
-                        let far_food: Vec<_> = food_positions.iter()
-                            .filter(|(fx, fy)| ((*fx - pos.x).powi(2) + (*fy - pos.y).powi(2)).sqrt() >= 5.0)
-                            .collect();
-                        if let Some((fx, fy)) = far_food.get(0) {
-                            possible_actions.push(("seek_food", *fx, *fy));
+                    // Whatever it was heading for, it's deciding afresh.
+                    claims.release_agent(*entity);
+                    // Reached the shore it was walking to: into the water.
+                    if let Some(swimming) = maybe_swimming.as_deref_mut() {
+                        if let Some(water) = swimming.destination.take() {
+                            if at_shore(pos.x, pos.y, water) {
+                                pos.x = water.0 as f32 + 0.5;
+                                pos.y = water.1 as f32 + 0.5;
+                                swimming.swim_ticks_remaining = rng.gen_range(SWIM_TICKS);
+                                *agent_state = crate::agent::AgentState::Swimming;
+                                if !log_config.quiet {
+                                    resources.2.lock().unwrap().push(format!("[SWIM] Agent {:?} enters the water at ({:.2}, {:.2}) for {} ticks", entity, pos.x, pos.y, swimming.swim_ticks_remaining));
+                                }
+                                continue;
+                            }
                         }
                     }
-                    if let Some((action, ax, ay)) = possible_actions.choose(&mut rng) {
-                        match *action {
-                            "seek_food" => {
-                                if let Some(ref mut target) = maybe_target.as_mut() {
-                                    target.x = *ax;
-                                    target.y = *ay;
-                                    if !log_config.quiet {
-                                        resources.2.lock().unwrap().push(format!("[TARGET][MLP] Agent {:?} seeks food at ({:.2}, {:.2})", entity, ax, ay));
-                                    }
-                                    if let Some(ref mut path) = maybe_path.as_mut() {
-                                        if let Some(astar_path) = pathfinding::a_star_path(&resources.0, agent_type, agent_state, (pos.x as i32, pos.y as i32), (*ax as i32, *ay as i32), 120) {
-                                            path.waypoints = std::collections::VecDeque::from_iter(astar_path.into_iter());
-                                            if !log_config.quiet {
-                                                resources.2.lock().unwrap().push(format!("[PATHFIND] Path assigned: {} waypoints", path.waypoints.len()));
-                                            }
-                                            *agent_state = crate::agent::AgentState::Moving;
-                                        } else {
-                                            if !log_config.quiet {
-                                                resources.2.lock().unwrap().push("[PATHFIND] No path found".to_string());
-                                            }
-                                            *agent_state = crate::agent::AgentState::Idle;
-                                        }
-                                    }
+                    if let crate::agent::DecisionEngineConfig::MLP(config) = &agent_type.decision_engine {
+                        let nearest_food = index.food.nearest(pos.x, pos.y, agent_type.vision, |food| {
+                            !claims.is_claimed_by_other(food.entity, *entity) && agent_type.diet.eats(food.data)
+                        });
+                        let nearby_agents = index.agents.count_within(pos.x, pos.y, agent_type.vision)
+                            .saturating_sub(1); // the agent itself
+                        let observation = Observation {
+                            hunger: hunger.value,
+                            energy: energy.value,
+                            nearest_food: nearest_food.map(|food| (food.x - pos.x, food.y - pos.y)),
+                            nearby_agents,
+                            terrain: resources.0.terrain_at(pos.x, pos.y),
+                        };
+                        if let Some(action) = decide(config, agent_type, &observation) {
+                            log::debug!("[ACTION][MLP] Agent {:?} chose {:?}", entity, action);
+                            let goal = match action {
+                                Action::SeekFood => nearest_food.map(|food| (food.x, food.y)),
+                                Action::Wander => Some((pos.x + rng.gen_range(-5.0..=5.0), pos.y + rng.gen_range(-5.0..=5.0))),
+                                Action::Rest => {
+                                    *agent_state = crate::agent::AgentState::Resting;
+                                    None
+                                }
+                                Action::Stay => None,
+                            };
+                            if let (Some((gx, gy)), Some(target), Some(path)) = (goal, maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                                target.x = gx;
+                                target.y = gy;
+                                if !log_config.quiet {
+                                    resources.2.lock().unwrap().push(format!("[TARGET][MLP] Agent {:?} {:?} to ({:.2}, {:.2})", entity, action, gx, gy));
+                                }
+                                assign_path(&resources.0, agent_type, agent_state, pos, path, (gx, gy), (!log_config.quiet).then_some(&*resources.2));
+                                if let (Action::SeekFood, Some(food), crate::agent::AgentState::Moving) = (action, nearest_food, *agent_state) {
+                                    claims.claim(food.entity, *entity);
                                 }
                             }
-                            _ => {}
+                            continue;
                         }
+                    }
+                    let food_target = if hunger.value < agent_type.hunger_threshold {
+                        nearest_reachable_food(index, claims, &resources.0, *entity, agent_type, agent_state, (pos.x, pos.y))
+                    } else {
+                        None
+                    };
+                    // Nothing in sight: try food a partner told it about.
+                    let food_tip = if food_target.is_none() && hunger.value < agent_type.hunger_threshold {
+                        maybe_interaction.as_mut().and_then(|i| i.food_tip.take())
                     } else {
+                        None
+                    };
+                    if let (Some(food_target), Some(target), Some(path)) = (food_target, maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                        target.x = food_target.x;
+                        target.y = food_target.y;
+                        if !log_config.quiet {
+                            let goal = if food_target.fishing { "goes fishing from" } else { "seeks food at" };
+                            resources.2.lock().unwrap().push(format!("[TARGET] Agent {:?} {} ({:.2}, {:.2})", entity, goal, food_target.x, food_target.y));
+                        }
+                        claims.claim(food_target.food, *entity);
+                        set_path(agent_state, path, Some(food_target.path), (!log_config.quiet).then_some(&*resources.2));
+                    } else if let (Some((tx, ty)), Some(target), Some(path)) = (food_tip, maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                        target.x = tx;
+                        target.y = ty;
+                        if !log_config.quiet {
+                            resources.2.lock().unwrap().push(format!("[TARGET] Agent {:?} follows a tip to food at ({:.2}, {:.2})", entity, tx, ty));
+                        }
+                        assign_path(&resources.0, agent_type, agent_state, pos, path, (tx, ty), (!log_config.quiet).then_some(&*resources.2));
+                    } else if let (Some((goal, why)), Some(target), Some(path)) = (carry_goal(index, claims, &resources.0, *entity, agent_type, agent_state, pos, hunger, maybe_inventory, &stockpiles), maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                        // Gather food to carry, take it to a stockpile, or go and eat from one
+                        // (see crate::agent::inventory).
+                        match goal {
+                            CarryGoal::Food(food_target) => {
+                                target.x = food_target.x;
+                                target.y = food_target.y;
+                                claims.claim(food_target.food, *entity);
+                                set_path(agent_state, path, Some(food_target.path), (!log_config.quiet).then_some(&*resources.2));
+                            }
+                            CarryGoal::Stockpile(x, y) => {
+                                target.x = x;
+                                target.y = y;
+                                assign_path(&resources.0, agent_type, agent_state, pos, path, (x, y), (!log_config.quiet).then_some(&*resources.2));
+                            }
+                        }
+                        if !log_config.quiet {
+                            resources.2.lock().unwrap().push(format!("[{}] Agent {:?} heads for ({:.2}, {:.2})", why, entity, target.x, target.y));
+                        }
+                    } else if let Some(swimming) = maybe_swimming.filter(|s| rng.gen_range(0..100) < s.swim_chance_percent) {
+                        if let (Some((water, shore)), Some(target), Some(path)) = (find_shore(&resources.0, agent_type, pos.x, pos.y), maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                            target.x = shore.0 as f32 + 0.5;
+                            target.y = shore.1 as f32 + 0.5;
+                            if !log_config.quiet {
+                                resources.2.lock().unwrap().push(format!("[SWIM] Agent {:?} heads for the water at ({}, {})", entity, water.0, water.1));
+                            }
+                            assign_path(&resources.0, agent_type, agent_state, pos, path, (target.x, target.y), (!log_config.quiet).then_some(&*resources.2));
+                            if *agent_state == crate::agent::AgentState::Moving {
+                                swimming.destination = Some(water);
+                            }
+                        }
+                    } else if rng.gen::<f32>() < agent_type.move_probability {
+                        // Rather than wander, go and find a friend (see crate::social).
+                        let out_of_reach: Vec<_> = index.agents.within(pos.x, pos.y, agent_type.vision).into_iter()
+                            .filter(|other| (other.x - pos.x).abs() >= crate::ecs_components::INTERACTION_RANGE || (other.y - pos.y).abs() >= crate::ecs_components::INTERACTION_RANGE)
+                            .collect();
+                        let friend = social.best_friend(*entity, out_of_reach.iter().map(|other| other.entity))
+                            .and_then(|friend| out_of_reach.iter().find(|other| other.entity == friend));
+                        if let (Some(friend), Some(target), Some(path)) = (friend, maybe_target.as_deref_mut(), maybe_path.as_deref_mut()) {
+                            target.x = friend.x;
+                            target.y = friend.y;
+                            if !log_config.quiet {
+                                resources.2.lock().unwrap().push(format!("[TARGET] Agent {:?} goes to see friend {:?} at ({:.2}, {:.2})", entity, friend.entity, friend.x, friend.y));
+                            }
+                            assign_path(&resources.0, agent_type, agent_state, pos, path, (friend.x, friend.y), (!log_config.quiet).then_some(&*resources.2));
+                            continue;
+                        }
                         log::debug!("[WANDER_BRANCH] Agent {:?} entering wander branch. State: {:?} Hunger: {:.2}/{:.2}", entity, agent_state, hunger.value, hunger.threshold);
                         // Example wander: pick a random nearby tile
                         let rx = pos.x + rng.gen_range(-5.0..=5.0);
@@ -203,19 +454,8 @@ pub fn action_selection_system() -> impl legion::systems::Runnable {
                             if !log_config.quiet {
                                 resources.2.lock().unwrap().push(format!("[TARGET] Agent {:?} wanders to ({:.2}, {:.2}) [local 120 units]", entity, rx, ry));
                             }
-                            if let Some(ref mut path) = maybe_path.as_mut() {
-                                if let Some(astar_path) = pathfinding::a_star_path(&resources.0, agent_type, agent_state, (pos.x as i32, pos.y as i32), (rx as i32, ry as i32), 120) {
-                                    path.waypoints = std::collections::VecDeque::from_iter(astar_path.into_iter());
-                                    if !log_config.quiet {
-                                        resources.2.lock().unwrap().push(format!("[PATHFIND] Path assigned: {} waypoints", path.waypoints.len()));
-                                    }
-                                    *agent_state = crate::agent::AgentState::Moving;
-                                } else {
-                                    if !log_config.quiet {
-                                        resources.2.lock().unwrap().push("[PATHFIND] No path found".to_string());
-                                    }
-                                    *agent_state = crate::agent::AgentState::Idle;
-                                }
+                            if let Some(path) = maybe_path {
+                                assign_path(&resources.0, agent_type, agent_state, pos, path, (rx, ry), (!log_config.quiet).then_some(&*resources.2));
                             }
                         }
                     }
@@ -224,13 +464,63 @@ pub fn action_selection_system() -> impl legion::systems::Runnable {
         })
 }
 
+/// Where `carry_goal` sends an agent.
+enum CarryGoal {
+    /// Food to pick up.
+    Food(crate::food::search::FoodTarget),
+    /// A stockpile to deposit at or eat from.
+    Stockpile(f32, f32),
+}
+
+/// What an agent with nothing better to do does about carrying food, and the log tag for
+/// it: hungry with nothing in its pack, it goes to eat from the nearest stockpile holding
+/// something it eats ("STOCKPILE"); otherwise, with room in its pack, it gathers food it
+/// sees ("GATHER"), and once full or out of food to gather it brings what it carries to
+/// the nearest stockpile ("RETURN").
+#[allow(clippy::too_many_arguments)]
+fn carry_goal(
+    index: &crate::spatial::SpatialIndex,
+    claims: &crate::food::FoodClaims,
+    map: &crate::map::Map,
+    entity: Entity,
+    agent_type: &crate::agent::AgentType,
+    agent_state: &crate::agent::AgentState,
+    pos: &crate::ecs_components::Position,
+    hunger: &crate::agent::Hunger,
+    inventory: Option<&crate::agent::inventory::Inventory>,
+    stockpiles: &[(crate::ecs_components::Position, crate::agent::inventory::Stockpile)],
+) -> Option<(CarryGoal, &'static str)> {
+    use crate::agent::inventory::{at_stockpile, nearest_stockpile};
+    let inventory = inventory?;
+    let stockpile_goal = |(s_pos, _): &(crate::ecs_components::Position, _)| {
+        (!at_stockpile(s_pos, pos.x, pos.y)).then_some(CarryGoal::Stockpile(s_pos.x, s_pos.y))
+    };
+    if hunger.value < agent_type.hunger_threshold {
+        if inventory.has_meal(&agent_type.diet) {
+            return None;
+        }
+        let stockpile = nearest_stockpile(stockpiles, pos.x, pos.y, |s| s.has_meal(&agent_type.diet))?;
+        return Some((stockpile_goal(stockpile)?, "STOCKPILE"));
+    }
+    if inventory.has_room() {
+        if let Some(food) = nearest_reachable_food(index, claims, map, entity, agent_type, agent_state, (pos.x, pos.y)) {
+            return Some((CarryGoal::Food(food), "GATHER"));
+        }
+    }
+    if inventory.is_empty() {
+        return None;
+    }
+    let stockpile = nearest_stockpile(stockpiles, pos.x, pos.y, |_| true)?;
+    Some((stockpile_goal(stockpile)?, "RETURN"))
+}
+
 // --- ECS Agent Passive Hunger System ---
 pub fn passive_hunger_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("PassiveHungerSystem")
         .with_query(<(&crate::agent::AgentType, &mut crate::agent::Hunger, &crate::agent::AgentState)>::query())
         .build(|_cmd, world, _res, query| {
             for (agent_type, hunger, agent_state) in query.iter_mut(world) {
-                if *agent_state == crate::agent::AgentState::Idle || *agent_state == crate::agent::AgentState::Arrived {
+                if matches!(*agent_state, crate::agent::AgentState::Idle | crate::agent::AgentState::Arrived | crate::agent::AgentState::Resting) {
                     // Reduce hunger by 10% of normal rate when not moving
                     hunger.value -= agent_type.hunger_rate * 0.1;
                 }
@@ -250,17 +540,38 @@ pub fn agent_movement_history_system() -> impl legion::systems::Runnable {
 }
 
 // --- ECS Agent Death System ---
+/// Why an agent in this condition dies, if it does. Starvation takes precedence, then
+/// running out of energy (drowning if in the water), then old age.
+pub fn death_cause(hunger: &crate::agent::Hunger, energy: &crate::agent::Energy, terrain: crate::map::Terrain, age: Option<&crate::agent::aging::Age>) -> Option<DeathCause> {
+    if hunger.value <= 0.0 {
+        Some(DeathCause::Starvation)
+    } else if energy.value <= 0.0 && terrain == crate::map::Terrain::Water {
+        Some(DeathCause::Drowning)
+    } else if energy.value <= 0.0 {
+        Some(DeathCause::Exhaustion)
+    } else if age.is_some_and(|a| a.is_expired()) {
+        Some(DeathCause::OldAge)
+    } else {
+        None
+    }
+}
+
 pub fn agent_death_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("AgentDeathSystem")
-        .with_query(<(legion::Entity, &crate::agent::Hunger, &crate::agent::Energy)>::query())
-        .build(|cmd, _world, _, _query| {
-            let mut to_remove = Vec::new();
-            for (entity, hunger, energy) in _query.iter(_world) {
-                if hunger.value <= 0.0 || energy.value <= 0.0 {
-                    to_remove.push(entity);
-                }
-            }
-            for entity in to_remove {
+        .with_query(<(legion::Entity, &crate::ecs_components::Position, &crate::agent::Hunger, &crate::agent::Energy, Option<&crate::agent::aging::Age>)>::query())
+        .write_resource::<crate::food::FoodClaims>()
+        .read_resource::<crate::map::Map>()
+        .write_resource::<AgentEventLog>()
+        .write_resource::<crate::ecs_components::Demographics>()
+        .write_resource::<crate::social::SocialGraph>()
+        .build(|cmd, _world, (claims, map, agent_event_log, demographics, social), _query| {
+            for (entity, pos, hunger, energy, age) in _query.iter(_world) {
+                let Some(cause) = death_cause(hunger, energy, map.terrain_at(pos.x, pos.y), age) else { continue };
+                let age = age.map_or(0, |a| a.ticks);
+                demographics.record_death(cause, age);
+                agent_event_log.push(AgentEvent::Died { agent: *entity, pos: (pos.x, pos.y), cause, age });
+                claims.release_agent(*entity);
+                social.remove_agent(*entity);
                 cmd.remove(*entity);
             }
         })
@@ -0,0 +1,358 @@
+//! Work. Agent types with a `role` work whenever they are idle, fed and rested:
+//! `work_system` picks a site for the role within the agent's vision, sends the agent
+//! there and has it work (`AgentState::Working`) for up to `WORK_SESSION_TICKS` before it
+//! decides afresh. Each working tick produces the role's rate times the type's `work_rate`:
+//! - Farmers tend `Fields`. A grass tile becomes a field when first worked, and a field
+//!   yields a food item whenever its growth reaches 1. Untended fields grow slowly too.
+//! - Miners stand next to mountains and mine stone into `WorkStats::stone`.
+//! - Builders spend that stone on the nearest unfinished structure (see `crate::structure`),
+//!   or start a new one when there is none in sight and enough stone to finish it.
+//! - Doctors go to the nearest agent low on energy or ill (see `agent::disease`), and
+//!   restore the energy of, and shorten the illness of, every such agent within reach.
+//!
+//! Working costs as much hunger and energy per tick as walking a tile; agents stop when
+//! they get hungry or tired.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use legion::{Entity, IntoQuery, SystemBuilder};
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentState, AgentType, Energy, Hunger, InteractionState};
+use crate::agent::disease::Health;
+use crate::agent::energy::{needs_rest, MOVE_ENERGY_PER_TILE};
+use crate::ecs_components::{spawn_food, Position, INTERACTION_RANGE};
+use crate::food::ecology::FoodEcology;
+use crate::map::{Map, Terrain};
+use crate::navigation::{Path, Target};
+use crate::sim_rng::{RngStream, SimRng};
+use crate::spatial::SpatialIndex;
+use crate::structure::{fits, place_structure, Structure, StructureKind, STOREHOUSE_CAPACITY, STOREHOUSE_RANGE};
+
+/// Most ticks an agent works before deciding what to do next.
+pub const WORK_SESSION_TICKS: u32 = 20;
+/// Field growth per tick of farming, per point of `work_rate`.
+pub const FARM_RATE: f32 = 0.01;
+/// Field growth per tick without a farmer.
+pub const FIELD_GROWTH: f32 = 0.005;
+/// Stone per tick of mining, per point of `work_rate`.
+pub const MINE_RATE: f32 = 0.1;
+/// Stone laid per tick of building, per point of `work_rate`.
+pub const BUILD_RATE: f32 = 0.25;
+/// Energy restored per tick of healing, per point of `work_rate`.
+pub const HEAL_RATE: f32 = 0.5;
+/// Agents below this fraction of their stamina need a doctor.
+pub const HEAL_BELOW: f32 = 0.5;
+/// Ticks of illness cured per tick of healing, per point of `work_rate`.
+pub const CURE_RATE: f32 = 1.0;
+
+/// What an agent type does for work (the YAML `role` key).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkRole {
+    Farmer,
+    Miner,
+    Builder,
+    Doctor,
+}
+
+impl WorkRole {
+    pub const ALL: [WorkRole; 4] = [WorkRole::Farmer, WorkRole::Miner, WorkRole::Builder, WorkRole::Doctor];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkRole::Farmer => "farmer",
+            WorkRole::Miner => "miner",
+            WorkRole::Builder => "builder",
+            WorkRole::Doctor => "doctor",
+        }
+    }
+}
+
+/// ECS component carried by agents whose type has a `role`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Work {
+    /// Tile being worked, or walked to.
+    pub site: Option<(i32, i32)>,
+    /// Working ticks left in the current session.
+    pub ticks_left: u32,
+}
+
+/// ECS resource: cultivated tiles and their growth toward the next harvest (0..1).
+#[derive(Clone, Debug, Default)]
+pub struct Fields(pub BTreeMap<(i32, i32), f32>);
+
+/// ECS resource: what all the work has produced.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkStats {
+    pub food_grown: usize,
+    pub stone_mined: f32,
+    /// Mined stone not yet built with.
+    pub stone: f32,
+    pub structures_built: usize,
+    pub energy_healed: f32,
+    /// Illnesses doctors brought to an end.
+    #[serde(default)]
+    pub cured: usize,
+}
+
+impl WorkStats {
+    /// "2 fields, 5 food grown, 40.0 stone mined (15.0 in store), 1 structures built, 30.0 energy healed, 2 cured"
+    pub fn summary(&self, fields: usize) -> String {
+        format!(
+            "{} fields, {} food grown, {:.1} stone mined ({:.1} in store), {} structures built, {:.1} energy healed, {} cured",
+            fields, self.food_grown, self.stone_mined, self.stone, self.structures_built, self.energy_healed, self.cured
+        )
+    }
+}
+
+/// A tile next to a mountain that is passable by terrain, where miners work.
+pub fn is_mine_face(map: &Map, x: i32, y: i32) -> bool {
+    map.is_passable(x, y, None)
+        && [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            nx >= 0 && ny >= 0 && nx < map.width && ny < map.height && map.tiles[ny as usize][nx as usize] == Terrain::Mountain
+        })
+}
+
+fn tile(pos: &Position) -> (i32, i32) {
+    (pos.x as i32, pos.y as i32)
+}
+
+/// Agents within reach of (`x`, `y`) (the interaction range on each axis).
+fn in_reach(index: &SpatialIndex, x: f32, y: f32) -> impl Iterator<Item = Entity> {
+    index.agents.within(x, y, INTERACTION_RANGE * std::f32::consts::SQRT_2).into_iter()
+        .filter(move |other| (other.x - x).abs() < INTERACTION_RANGE && (other.y - y).abs() < INTERACTION_RANGE)
+        .map(|other| other.entity)
+}
+
+/// Where `agent` would work next, if there is anything for it to do within its vision.
+/// Farmers go to the nearest field, or failing that the nearest grass tile; miners to the
+/// nearest mine face; builders, if there is stone, to the nearest unfinished structure;
+/// doctors to the nearest agent in `patients`.
+#[allow(clippy::too_many_arguments)]
+pub fn find_site(
+    role: WorkRole,
+    agent: Entity,
+    agent_type: &AgentType,
+    pos: &Position,
+    map: &Map,
+    index: &SpatialIndex,
+    fields: &Fields,
+    stats: &WorkStats,
+    patients: &HashSet<Entity>,
+) -> Option<(i32, i32)> {
+    let (x, y) = tile(pos);
+    let range = agent_type.vision as i32;
+    let found = match role {
+        WorkRole::Farmer => map.find_nearest_tile_where(x, y, range, |tx, ty| fields.0.contains_key(&(tx, ty)))
+            .or_else(|| map.find_nearest_tile(x, y, range, |t| t == Terrain::Grass)),
+        WorkRole::Miner => map.find_nearest_tile_where(x, y, range, |tx, ty| is_mine_face(map, tx, ty)),
+        WorkRole::Builder if stats.stone > 0.0 => map.find_nearest_tile_where(x, y, range, |tx, ty| map.structures.get(tx, ty).is_some_and(|t| !t.built)),
+        WorkRole::Builder => None,
+        WorkRole::Doctor => index.agents.nearest(pos.x, pos.y, agent_type.vision, |other| other.entity != agent && patients.contains(&other.entity))
+            .map(|other| (other.x as i32, other.y as i32, 0)),
+    };
+    found.map(|(tx, ty, _)| (tx, ty))
+}
+
+/// Where a builder at `pos` would put a new `kind` structure: the nearest free spot within
+/// its vision, as the structure's top-left tile.
+pub fn find_building_site(kind: StructureKind, agent_type: &AgentType, pos: &Position, map: &Map) -> Option<(i32, i32)> {
+    let (x, y) = tile(pos);
+    map.find_nearest_tile_where(x, y, agent_type.vision as i32, |tx, ty| fits(map, kind, tx, ty))
+        .map(|(tx, ty, _)| (tx, ty))
+}
+
+/// Runs every agent with a role: starts and ends work sessions, applies the output of
+/// each working tick, and harvests ripe fields into storehouses or onto the field.
+pub fn work_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("WorkSystem")
+        .write_resource::<Map>()
+        .read_resource::<SpatialIndex>()
+        .write_resource::<Fields>()
+        .write_resource::<WorkStats>()
+        .read_resource::<FoodEcology>()
+        .write_resource::<SimRng>()
+        .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
+        .read_resource::<crate::log_config::LogConfig>()
+        .with_query(<(Entity, &Position, &AgentType, &mut Hunger, &mut Energy, &mut AgentState, &mut Work, (Option<&mut Target>, Option<&mut Path>, Option<&InteractionState>))>::query())
+        .with_query(<(Entity, &AgentType, &mut Energy, Option<&mut Health>)>::query())
+        .with_query(<(Entity, &mut Structure)>::query())
+        .build(|cmd, world, (map, index, fields, stats, ecology, sim_rng, event_log, log_config), (workers, patient_query, structure_query)| {
+            let patients: HashSet<Entity> = patient_query.iter_mut(world)
+                .filter(|(_, agent_type, energy, health)| {
+                    energy.value < agent_type.stamina * HEAL_BELOW
+                        || health.as_ref().zip(agent_type.disease.as_ref()).is_some_and(|(h, c)| h.is_ill(c))
+                })
+                .map(|(entity, ..)| *entity)
+                .collect();
+            // (kind, progress) of every structure, updated by this tick's builders
+            let mut structures: HashMap<Entity, (StructureKind, f32)> = HashMap::new();
+            let mut counts: BTreeMap<StructureKind, usize> = BTreeMap::new();
+            // (entity, corner tile, food held) of finished storehouses
+            let mut storehouses: Vec<(Entity, (i32, i32), usize)> = Vec::new();
+            for (entity, structure) in structure_query.iter_mut(world) {
+                structures.insert(*entity, (structure.kind, structure.progress));
+                *counts.entry(structure.kind).or_insert(0) += 1;
+                if structure.kind == StructureKind::Storehouse && structure.is_built() {
+                    storehouses.push((*entity, (structure.x, structure.y), structure.food));
+                }
+            }
+            // (patient, doctor's output) from this tick's doctors
+            let mut heals: Vec<(Entity, f32)> = Vec::new();
+            for (entity, pos, agent_type, hunger, energy, agent_state, work, (target, path, interaction)) in workers.iter_mut(world) {
+                let Some(role) = agent_type.role else { continue };
+                let fit = hunger.value >= agent_type.hunger_threshold && !needs_rest(energy, agent_type);
+                match *agent_state {
+                    AgentState::Working => {
+                        let mut done = !fit || work.ticks_left == 0;
+                        if let (false, Some(site)) = (done, work.site) {
+                            let output = agent_type.work_rate;
+                            match role {
+                                WorkRole::Farmer => *fields.0.entry(site).or_insert(0.0) += output * FARM_RATE,
+                                WorkRole::Miner => {
+                                    stats.stone += output * MINE_RATE;
+                                    stats.stone_mined += output * MINE_RATE;
+                                }
+                                WorkRole::Builder => {
+                                    let under_way = map.structures.get(site.0, site.1)
+                                        .and_then(|t| Some((t.entity, structures.get_mut(&t.entity)?)))
+                                        .filter(|(_, (_, progress))| *progress < 1.0);
+                                    match under_way {
+                                        Some((structure, (kind, progress))) => {
+                                            let stone = (output * BUILD_RATE).min(stats.stone).min((1.0 - *progress) * kind.cost());
+                                            stats.stone -= stone;
+                                            *progress += stone / kind.cost();
+                                            // Allow for rounding in the many small increments.
+                                            if *progress >= 1.0 - 1e-4 {
+                                                *progress = 1.0;
+                                                stats.structures_built += 1;
+                                                if !log_config.quiet {
+                                                    event_log.lock().unwrap().push(format!("[WORK] Agent {:?} finished a {} ({:?}) at ({}, {})", entity, kind.as_str(), structure, site.0, site.1));
+                                                }
+                                            }
+                                            done = *progress >= 1.0 || stats.stone <= 0.0;
+                                        }
+                                        None => done = true,
+                                    }
+                                }
+                                WorkRole::Doctor => {
+                                    let before = heals.len();
+                                    heals.extend(in_reach(index, pos.x, pos.y).filter(|other| other != entity && patients.contains(other)).map(|other| (other, output)));
+                                    done = heals.len() == before;
+                                }
+                            }
+                            hunger.value -= agent_type.hunger_rate;
+                            energy.value -= MOVE_ENERGY_PER_TILE;
+                            work.ticks_left = work.ticks_left.saturating_sub(1);
+                        }
+                        if done || work.site.is_none() {
+                            work.site = None;
+                            work.ticks_left = 0;
+                            *agent_state = AgentState::Idle;
+                        }
+                    }
+                    AgentState::Idle => {
+                        if !fit || interaction.is_some_and(|i| i.target.is_some()) {
+                            work.site = None;
+                            continue;
+                        }
+                        // A site it isn't standing on was left behind (e.g. to rest); pick again.
+                        let found = work.site.filter(|site| *site == tile(pos))
+                            .or_else(|| find_site(role, *entity, agent_type, pos, map, index, fields, stats, &patients));
+                        // Builders with nothing under way nearby start something new: the
+                        // kind most needed that there is room for, once there is stone enough.
+                        let found = found.or_else(|| {
+                            if role != WorkRole::Builder {
+                                return None;
+                            }
+                            let (kind, (x, y)) = StructureKind::build_order(&counts).into_iter()
+                                .find_map(|kind| Some((kind, find_building_site(kind, agent_type, pos, map)?)))
+                                .filter(|(kind, _)| stats.stone >= kind.cost())?;
+                            let structure = place_structure(cmd, map, kind, x, y, Some(*entity));
+                            structures.insert(structure, (kind, 0.0));
+                            *counts.entry(kind).or_insert(0) += 1;
+                            if !log_config.quiet {
+                                event_log.lock().unwrap().push(format!("[WORK] Agent {:?} starts a {} at ({}, {})", entity, kind.as_str(), x, y));
+                            }
+                            Some((x, y))
+                        });
+                        let Some(site) = found else {
+                            work.site = None;
+                            continue;
+                        };
+                        work.site = Some(site);
+                        if site == tile(pos) {
+                            work.ticks_left = WORK_SESSION_TICKS;
+                            *agent_state = AgentState::Working;
+                            if !log_config.quiet {
+                                event_log.lock().unwrap().push(format!("[WORK] Agent {:?} ({}) starts work at ({}, {})", entity, role.as_str(), site.0, site.1));
+                            }
+                        } else if let (Some(target), Some(path)) = (target, path) {
+                            target.x = site.0 as f32 + 0.5;
+                            target.y = site.1 as f32 + 0.5;
+                            crate::agent::systems::assign_path(map, agent_type, agent_state, pos, path, (target.x, target.y), (!log_config.quiet).then_some(&**event_log));
+                            if *agent_state != AgentState::Moving {
+                                work.site = None;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            for (entity, agent_type, energy, health) in patient_query.iter_mut(world) {
+                let care: f32 = heals.iter().filter(|(patient, _)| patient == entity).map(|(_, output)| output).sum();
+                if care > 0.0 {
+                    let before = energy.value;
+                    energy.value = (energy.value + care * HEAL_RATE).min(agent_type.stamina.max(before));
+                    stats.energy_healed += energy.value - before;
+                    if let (Some(health), Some(config)) = (health, &agent_type.disease) {
+                        if health.treat(((care * CURE_RATE).ceil() as u32).max(1), config) {
+                            stats.cured += 1;
+                            if !log_config.quiet {
+                                event_log.lock().unwrap().push(format!("[WORK] Agent {:?} is cured", entity));
+                            }
+                        }
+                    }
+                }
+            }
+            // Fields grow a little on their own. Ripe ones deliver to the nearest storehouse in
+            // range with room, or else yield food on the field unless some is still there.
+            let rng = sim_rng.stream(RngStream::Work);
+            for (&(x, y), growth) in fields.0.iter_mut() {
+                *growth += FIELD_GROWTH;
+                if *growth >= 1.0 {
+                    let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+                    let store = storehouses.iter_mut()
+                        .filter(|(_, (sx, sy), food)| *food < STOREHOUSE_CAPACITY && (sx - x).abs() <= STOREHOUSE_RANGE && (sy - y).abs() <= STOREHOUSE_RANGE)
+                        .min_by_key(|(_, (sx, sy), _)| (sx - x).abs() + (sy - y).abs());
+                    if let Some((_, _, food)) = store {
+                        *growth -= 1.0;
+                        *food += 1;
+                        stats.food_grown += 1;
+                    } else if index.food.within(fx, fy, 0.5).is_empty() {
+                        *growth -= 1.0;
+                        spawn_food(cmd, Position { x: fx, y: fy }, ecology, rng);
+                        stats.food_grown += 1;
+                    } else {
+                        *growth = 1.0;
+                    }
+                }
+            }
+            for (entity, structure) in structure_query.iter_mut(world) {
+                let Some(&(_, progress)) = structures.get(entity) else { continue };
+                if progress >= 1.0 && !structure.is_built() {
+                    map.structures.set_built(structure);
+                    if structure.kind == StructureKind::Farm {
+                        for tile in structure.tiles() {
+                            fields.0.entry(tile).or_insert(0.0);
+                        }
+                    }
+                }
+                structure.progress = progress;
+                if let Some((_, _, food)) = storehouses.iter().find(|(store, ..)| store == entity) {
+                    structure.food = *food;
+                }
+            }
+        })
+}
@@ -3,18 +3,42 @@ use std::collections::VecDeque;
 use legion::Resources;
 use crate::event_log::EventLog;
 use crate::agent::event::AgentEventLog;
+use crate::agent::event_sink::{EventSink, StableIds};
+use crate::agent::disease::{DiseaseSink, DiseaseStats};
+use crate::agent::genome::TraitSink;
+use crate::agent::work::{Fields, WorkStats};
 use crate::log_config::LogConfig;
-use crate::ecs_components::{FoodPositions, FoodStats, InteractionStats};
-use crate::food::PendingFoodSpawns;
+use crate::world_clock::WorldClock;
+use crate::ecs_components::{AgentContacts, Demographics, FoodPositions, FoodStats, InteractionStats};
+use crate::food::{FoodClaims, PendingFoodSpawns};
+use crate::food::ecology::FoodEcology;
 use crate::map::Map;
+use crate::sim_rng::SimRng;
+use crate::social::SocialGraph;
+use crate::spatial::SpatialIndex;
 
-pub fn insert_standard_resources(resources: &mut Resources, map: &Map) {
+pub fn insert_standard_resources(resources: &mut Resources, map: &Map, sim_rng: SimRng) {
     resources.insert(map.clone());
+    resources.insert(sim_rng);
     resources.insert(PendingFoodSpawns(VecDeque::new()));
     resources.insert(FoodPositions(Vec::new()));
+    resources.insert(SpatialIndex::new(map));
+    resources.insert(FoodClaims::default());
     resources.insert(FoodStats::default());
+    resources.insert(FoodEcology::default());
     resources.insert(InteractionStats::default());
+    resources.insert(Demographics::default());
+    resources.insert(SocialGraph::default());
+    resources.insert(Fields::default());
+    resources.insert(WorkStats::default());
+    resources.insert(DiseaseStats::default());
+    resources.insert(AgentContacts::default());
     resources.insert(Arc::new(Mutex::new(EventLog::new(200))));
     resources.insert(AgentEventLog::default());
+    resources.insert(EventSink::disabled());
+    resources.insert(TraitSink::disabled());
+    resources.insert(DiseaseSink::disabled());
+    resources.insert(WorldClock::disabled());
+    resources.insert(StableIds::default());
     resources.insert(LogConfig::default());
 }
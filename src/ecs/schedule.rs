@@ -7,7 +7,7 @@ use crate::event_log::EventLog;
 use crate::map::{Map, Terrain};
 use crate::ecs_simulation::{simulation_tick, build_simulation_schedule_profiled, SystemProfile};
 use crate::render_ascii;
-use crate::ecs_components::{Position, InteractionStats};
+use crate::ecs_components::{Demographics, FoodStats, Position, InteractionStats};
 use std::sync::{Arc, Mutex};
 use std::fs::File;
 use std::io::Write;
@@ -18,6 +18,16 @@ use serde::{Deserialize, /*Serialize*/};
 use serde_yaml;
 use crate::agent::event::AgentEventLog;
 use crate::ecs::resources::insert_standard_resources;
+use crate::food::Food;
+use crate::sim_rng::{RngStream, SimRng};
+use crate::snapshot::{CheckpointConfig, Snapshot};
+use crate::agent::event_sink::{per_profile_path, EventSink, StableIds};
+use crate::agent::disease::{DiseaseSink, DiseaseStats};
+use crate::agent::genome::TraitSink;
+use crate::world_clock::{WorldClock, CLOCK_CONFIG_PATH};
+use crate::food::ecology::{FoodConfig, FoodEcology, FOOD_CONFIG_PATH};
+use crate::agent::work::{Fields, WorkStats};
+use crate::social::{write_social_graph, SocialGraph};
 
 #[derive(Debug, Deserialize)]
 pub struct SimProfile {
@@ -29,6 +39,7 @@ pub struct SimProfile {
     pub ticks: usize,
     pub benchmark: Option<bool>,
     pub quiet: Option<bool>,
+    pub seed: Option<u64>,
 }
 
 pub fn load_profiles_from_yaml(path: &str) -> Vec<SimProfile> {
@@ -39,6 +50,8 @@ pub fn load_profiles_from_yaml(path: &str) -> Vec<SimProfile> {
 // TODO: Move or re-export run_simulation, load_profiles_from_yaml, etc., as needed for full migration.
 
 /// Runs a single simulation profile (non-GUI), with ECS setup and tick loop. Returns timing info.
+/// With `Some(seed)` the run is fully reproducible; with `None` a random seed is drawn and logged.
+#[allow(clippy::too_many_arguments)]
 pub fn run_simulation(
     map_width: i32,
     map_height: i32,
@@ -48,91 +61,63 @@ pub fn run_simulation(
     agent_types: &[AgentType],
     profile_systems: bool,
     profile_csv: &str,
+    seed: Option<u64>,
+    checkpoint: &CheckpointConfig,
+    events_out: Option<&str>,
+    traits_out: Option<&str>,
+    social_out: Option<&str>,
+    disease_out: Option<&str>,
 ) -> (f64, f64, f64) {
     log::info!("[TEST] Entered run_simulation");
     log::info!("\n=== Running {}: map {}x{}, {} agents, {} ticks ===", label, map_width, map_height, num_agents, ticks);
-    // --- ECS World Setup (MATCH graphics mode) ---
+    // --- ECS World Setup (MATCH graphics mode), or restore from a snapshot ---
     let mut world = World::default();
-    let map = Map::new(map_width, map_height);
-    let mut rng = rand::thread_rng();
-    // Convert agent_types from agent.rs::AgentType to ecs_components::AgentType directly
-    let ecs_agent_types: Vec<AgentType> = agent_types.iter().map(|a| AgentType {
-        name: a.name.clone(),
-        color: a.color,
-        movement_profile: a.movement_profile,
-        decision_engine: a.decision_engine.clone(),
-        hunger_rate: a.hunger_rate,
-        hunger_threshold: a.hunger_threshold,
-    }).collect();
-    let mut agent_count = 0;
-    let mut attempts = 0;
-    if num_agents > 0 {
-        for i in 0..num_agents {
-            // Find a random passable tile
-            let mut x;
-            let mut y;
-            let mut tries = 0;
-            loop {
-                x = rng.gen_range(0..map_width) as f32;
-                y = rng.gen_range(0..map_height) as f32;
-                if map.tiles[y as usize][x as usize] == Terrain::Grass || map.tiles[y as usize][x as usize] == Terrain::Forest {
-                    break;
-                }
-                tries += 1;
-                if tries > 1000 {
-                    panic!("Could not find passable tile for agent after 1000 tries");
-                }
-            }
-            let _agent_type = ecs_agent_types[i % ecs_agent_types.len()].clone();
-            // Remove direct calls to spawn_agent from this file, as they cannot provide &mut AgentEventLog.
-            // Agent spawning with event logging should be handled in simulation.rs, sim_render.rs, or other files where resources are available.
-            // If you need to spawn agents for a profile, delegate to a function in simulation.rs or similar, passing resources as needed.
-            // crate::agent::spawn_agent(&mut world, Position { x, y }, agent_type, &map);
-            agent_count += 1;
-            attempts += tries;
+    let mut resources = Resources::default();
+    let start_tick = match &checkpoint.resume {
+        Some(path) => {
+            let snapshot = Snapshot::load(path).unwrap_or_else(|e| panic!("Failed to load snapshot {}: {}", path, e));
+            let start_tick = snapshot.tick;
+            snapshot.restore(&mut world, &mut resources);
+            resources.get_mut::<FoodEcology>().expect("FoodEcology missing").config = FoodConfig::load(FOOD_CONFIG_PATH);
+            start_tick
         }
-    }
-    // --- Spawn initial food entities (1 per 10 agents, minimum 1 if agents exist) ---
-    let food_count = if agent_count > 0 { std::cmp::max(1, agent_count / 10) } else { 0 };
-    for _ in 0..food_count {
-        let mut tries = 0;
-        let (mut x, mut y);
-        loop {
-            x = rng.gen_range(0..map_width) as f32;
-            y = rng.gen_range(0..map_height) as f32;
-            if map.tiles[y as usize][x as usize] == Terrain::Grass || map.tiles[y as usize][x as usize] == Terrain::Forest {
-                break;
-            }
-            tries += 1;
-            if tries > 1000 {
-                panic!("Could not find passable tile for food after 1000 tries");
-            }
+        None => {
+            spawn_initial_world(&mut world, &mut resources, map_width, map_height, num_agents, agent_types, seed);
+            0
         }
-        world.push((Position { x, y }, /*Food { nutrition: rng.gen_range(5.0..=10.0) }*/));
+    };
+    let map = resources.get::<Map>().expect("Map missing").clone();
+    resources.insert(WorldClock { tick: start_tick as u64, ..WorldClock::load(CLOCK_CONFIG_PATH) });
+    if let Some(path) = events_out {
+        let sink = EventSink::create(path, start_tick as u64).unwrap_or_else(|e| panic!("Failed to create events file {}: {}", path, e));
+        log::info!("[EVENTS] Writing agent events to {}", path);
+        resources.insert(sink);
+    }
+    if let Some(path) = traits_out {
+        let sink = TraitSink::create(path, start_tick as u64).unwrap_or_else(|e| panic!("Failed to create traits file {}: {}", path, e));
+        log::info!("[TRAITS] Writing trait histograms to {}", path);
+        resources.insert(sink);
+    }
+    if let Some(path) = disease_out {
+        let sink = DiseaseSink::create(path, start_tick as u64).unwrap_or_else(|e| panic!("Failed to create disease file {}: {}", path, e));
+        log::info!("[DISEASE] Writing SIR counts to {}", path);
+        resources.insert(sink);
     }
-    log::debug!("[DEBUG] Total spawn attempts: {} (avg {:.2} per agent)", attempts, attempts as f32 / agent_count as f32);
-    let total_entities = world.len();
-    log::debug!("[DEBUG] Total entities in world after spawning: {}", total_entities);
-    std::io::stdout().flush().unwrap();
-    log::debug!("[DEBUG] Spawned {} agents", agent_count);
     // --- DEBUG: Print all entities with Position and their component type names before tick loop ---
     log::debug!("[DEBUG] Entities with Position and their component types before tick loop:");
     let mut query = <(
         legion::Entity,
         &Position,
         Option<&AgentType>,
-        //Option<&Food>
+        Option<&Food>
     )>::query();
-    for (entity, _pos, agent_type, /*food*/) in query.iter(&world) {
+    for (entity, _pos, agent_type, food) in query.iter(&world) {
         let mut comps = vec!["Position"];
         if agent_type.is_some() { comps.push("AgentType"); }
-        //if food.is_some() { comps.push("Food"); }
+        if food.is_some() { comps.push("Food"); }
         log::debug!("  Entity {:?}: [{}]", entity, comps.join(", "));
     }
     // --- Main simulation loop ---
-    let mut resources = Resources::default();
-    insert_standard_resources(&mut resources, &map);
-    // Insert other resources as needed for ECS systems
     if profile_systems {
         let mut csv_file = File::create(profile_csv).expect("Failed to create csv file");
         writeln!(csv_file, "tick,agent_movement,entity_interaction,agent_death,food_spawn_collect,food_spawn_apply").unwrap();
@@ -140,7 +125,7 @@ pub fn run_simulation(
         let mut min_profile: Option<SystemProfile> = None;
         let mut max_profile: Option<SystemProfile> = None;
         let mut schedule = build_simulation_schedule_profiled();
-        for tick in 0..ticks {
+        for tick in start_tick..ticks {
             log::debug!("Tick {}", tick);
             log::info!("[DEBUG] AgentEventLog present at tick {}? {}", tick, resources.get::<AgentEventLog>().is_some());
             assert!(resources.get::<AgentEventLog>().is_some(), "AgentEventLog missing from resources at tick {}!", tick);
@@ -150,6 +135,7 @@ pub fn run_simulation(
                 &mut schedule,
             );
             writeln!(csv_file, "{}{}{}", tick, if tick == 0 { "," } else { "," }, profile.to_csv_row()).unwrap();
+            checkpoint.maybe_save(&world, &resources, label, tick + 1);
             // Optionally render ASCII after ECS update
             if profile_systems {
                 let ascii = render_ascii::render_simulation_ascii(&world, &map);
@@ -181,7 +167,7 @@ pub fn run_simulation(
                 }
             });
         }
-        let ticks_f = ticks as f64;
+        let ticks_f = ticks.saturating_sub(start_tick) as f64;
         let mut avg_profile = sum_profile.clone();
         avg_profile.div_assign(ticks_f);
         log::debug!("\n=== System Profile Summary ===");
@@ -198,7 +184,7 @@ pub fn run_simulation(
     } else {
         let mut schedule = build_simulation_schedule_profiled();
         let mut last_ascii = String::new();
-        for tick in 0..ticks {
+        for tick in start_tick..ticks {
             log::debug!("Tick {}", tick);
             log::info!("[DEBUG] AgentEventLog present at tick {}? {}", tick, resources.get::<AgentEventLog>().is_some());
             assert!(resources.get::<AgentEventLog>().is_some(), "AgentEventLog missing from resources at tick {}!", tick);
@@ -207,15 +193,16 @@ pub fn run_simulation(
                 &mut resources,
                 &mut schedule,
             );
+            checkpoint.maybe_save(&world, &resources, label, tick + 1);
             // Generate ASCII snapshot at each tick (optional, but we'll save the last)
             last_ascii = render_ascii::render_simulation_ascii(&world, &map);
             // Optionally print: println!("{}", last_ascii);
         }
         // --- Write simulation summary to map file ---
         use legion::IntoQuery;
-        use std::collections::HashMap;
-        // Count agent types at end
-        let mut agent_type_counts: HashMap<String, usize> = HashMap::new();
+        use std::collections::BTreeMap;
+        // Count agent types at end (sorted, so the summary is stable across runs)
+        let mut agent_type_counts: BTreeMap<String, usize> = BTreeMap::new();
         let mut agent_query = <(&AgentType,)>::query();
         for (agent_type,) in agent_query.iter(&world) {
             *agent_type_counts.entry(agent_type.name.clone()).or_insert(0) += 1;
@@ -227,8 +214,35 @@ pub fn run_simulation(
         // Prepare summary string
         let mut summary = String::new();
         summary.push_str(&format!("# Simulation Summary\n"));
+        summary.push_str(&format!("Seed: {}\n", resources.get::<SimRng>().expect("No SimRng resource").seed()));
         summary.push_str(&format!("Total interactions: {}\n", total_interactions));
         summary.push_str(&format!("Average interactions per tick: {:.2}\n", avg_interactions_per_tick));
+        summary.push_str(&format!("Interaction outcomes: {}\n", stats.outcomes_summary()));
+        let demographics = resources.get::<Demographics>().map(|d| d.clone()).unwrap_or_default();
+        summary.push_str(&format!("Births: {}\n", demographics.births));
+        summary.push_str(&format!("Deaths: {} ({})\n", demographics.total_deaths(), demographics.deaths_summary()));
+        if let Some(mean_age) = demographics.mean_age_at_death() {
+            summary.push_str(&format!("Average age at death: {:.1} ticks\n", mean_age));
+        }
+        let fields = resources.get::<Fields>().map_or(0, |f| f.0.len());
+        let work = resources.get::<WorkStats>().map(|w| w.clone()).unwrap_or_default();
+        summary.push_str(&format!("Work: {}\n", work.summary(fields)));
+        summary.push_str(&format!("Structures: {}\n", crate::structure::structure_summary(&world)));
+        let disease = resources.get::<DiseaseStats>().map(|d| d.clone()).unwrap_or_default();
+        summary.push_str(&format!("Disease: {}\n", disease.summary()));
+        let clock = resources.get::<WorldClock>().map(|c| c.label()).unwrap_or_default();
+        summary.push_str(&format!("Clock: {}\n", clock));
+        let food_stats = resources.get::<FoodStats>().map(|s| s.clone()).unwrap_or_default();
+        let ecology = resources.get::<FoodEcology>().map(|e| e.summary()).unwrap_or_default();
+        let on_map: Vec<String> = crate::food::FoodKind::ALL.iter()
+            .map(|kind| format!("{} {}", kind.as_str(), <&Food>::query().iter(&world).filter(|f| f.kind == *kind).count()))
+            .collect();
+        summary.push_str(&format!("Food: {} spawned, {} eaten, {} rotted; {}; on the map: {}\n", food_stats.spawned_per_tick, food_stats.collected_per_tick, food_stats.rotted, ecology, on_map.join(", ")));
+        let carried: usize = <&crate::agent::inventory::Inventory>::query().iter(&world).map(|i| i.items.len()).sum();
+        summary.push_str(&format!("Carried food: {} picked up, {} eaten from packs or stockpiles, {} deposited, {} withdrawn, {} carried now; {}\n", food_stats.picked_up, food_stats.eaten_carried, food_stats.deposited, food_stats.withdrawn, carried, crate::agent::inventory::stockpile_summary(&world)));
+        let agent_count: usize = agent_type_counts.values().sum();
+        let social = resources.get::<SocialGraph>().map(|g| g.metrics(agent_count)).unwrap_or_default();
+        summary.push_str(&format!("Social graph: {} relationships, mean degree {:.2}, clustering {:.3}\n", social.relationships, social.mean_degree, social.clustering));
         summary.push_str("Agent counts at end:\n");
         for (name, count) in agent_type_counts.iter() {
             summary.push_str(&format!("  {}: {}\n", name, count));
@@ -240,18 +254,127 @@ pub fn run_simulation(
         file.write_all(last_ascii.as_bytes()).expect("Unable to write ascii output");
         log::info!("[INFO] Simulation summary and final ASCII snapshot written to simulation_ascii.txt");
     }
+    if let Some(path) = social_out {
+        let graph = resources.get::<SocialGraph>().expect("SocialGraph missing");
+        let mut ids = resources.get_mut::<StableIds>().expect("StableIds missing");
+        match write_social_graph(path, &graph, &world, &mut ids) {
+            Ok(()) => log::info!("[SOCIAL] Social graph ({} relationships) written to {}", graph.len(), path),
+            Err(e) => log::error!("[SOCIAL] Failed to write social graph {}: {}", path, e),
+        }
+    }
     // Optionally: write last snapshot to file or keep for further processing
     (0.0, 0.0, 0.0)
 }
 
-pub fn run_profiles_from_yaml(path: &str, agent_types: &[AgentType], profile_systems: bool, profile_csv: &str) {
-    let profiles = load_profiles_from_yaml(path);
+/// Generates the map, inserts the standard resources and spawns the initial agents and food.
+pub(crate) fn spawn_initial_world(
+    world: &mut World,
+    resources: &mut Resources,
+    map_width: i32,
+    map_height: i32,
+    num_agents: usize,
+    agent_types: &[AgentType],
+    seed: Option<u64>,
+) {
+    let mut sim_rng = SimRng::from_seed_or_entropy(seed);
+    let map = Map::new_with_seed(map_width, map_height, sim_rng.stream(RngStream::Map).gen());
+    insert_standard_resources(resources, &map, sim_rng);
+    resources.insert(FoodEcology::new(FoodConfig::load(FOOD_CONFIG_PATH)));
+    let ecology = resources.get::<FoodEcology>().expect("FoodEcology missing");
+    let mut sim_rng = resources.get_mut::<SimRng>().expect("SimRng missing");
+    let mut agent_event_log = resources.get_mut::<AgentEventLog>().expect("AgentEventLog missing");
+    let rng = sim_rng.stream(RngStream::Spawn);
+    // Convert agent_types from agent.rs::AgentType to ecs_components::AgentType directly
+    let ecs_agent_types: Vec<AgentType> = agent_types.to_vec();
+    let mut agent_count = 0;
+    let mut attempts = 0;
+    if num_agents > 0 {
+        for i in 0..num_agents {
+            // Find a random passable tile
+            let mut x;
+            let mut y;
+            let mut tries = 0;
+            loop {
+                x = rng.gen_range(0..map_width) as f32;
+                y = rng.gen_range(0..map_height) as f32;
+                if map.tiles[y as usize][x as usize] == Terrain::Grass || map.tiles[y as usize][x as usize] == Terrain::Forest {
+                    break;
+                }
+                tries += 1;
+                if tries > 1000 {
+                    panic!("Could not find passable tile for agent after 1000 tries");
+                }
+            }
+            let agent_type = ecs_agent_types[i % ecs_agent_types.len()].clone();
+            crate::agent::spawn_agent(world, Position { x, y }, agent_type, &map, &mut agent_event_log, rng);
+            agent_count += 1;
+            attempts += tries;
+        }
+    }
+    // --- Spawn initial food entities (1 per 10 agents, minimum 1 if agents exist) ---
+    let food_count = if agent_count > 0 { std::cmp::max(1, agent_count / 10) } else { 0 };
+    for _ in 0..food_count {
+        let mut tries = 0;
+        let (mut x, mut y);
+        loop {
+            x = rng.gen_range(0..map_width) as f32;
+            y = rng.gen_range(0..map_height) as f32;
+            if ecology.grows(map.tiles[y as usize][x as usize]) {
+                break;
+            }
+            tries += 1;
+            if tries > 1000 {
+                panic!("Could not find a food-growing tile after 1000 tries");
+            }
+        }
+        if let Some(food) = ecology.grow(map.tiles[y as usize][x as usize], rng) {
+            world.push((Position { x, y }, food));
+        }
+    }
+    drop(ecology);
+    drop(agent_event_log);
+    drop(sim_rng);
+    log::debug!("[DEBUG] Total spawn attempts: {} (avg {:.2} per agent)", attempts, attempts as f32 / agent_count as f32);
+    let total_entities = world.len();
+    log::debug!("[DEBUG] Total entities in world after spawning: {}", total_entities);
+    std::io::stdout().flush().unwrap();
+    log::debug!("[DEBUG] Spawned {} agents", agent_count);
+}
+
+/// `seed` (from `--seed`) overrides any `seed:` key set on the profile.
+/// When resuming, only the profile the snapshot was taken from is run.
+/// With several profiles, `events_out`, `traits_out`, `social_out` and `disease_out` get the profile name inserted before their extension.
+#[allow(clippy::too_many_arguments)]
+pub fn run_profiles_from_yaml(
+    path: &str,
+    agent_types: &[AgentType],
+    profile_systems: bool,
+    profile_csv: &str,
+    seed: Option<u64>,
+    checkpoint: &CheckpointConfig,
+    events_out: Option<&str>,
+    traits_out: Option<&str>,
+    social_out: Option<&str>,
+    disease_out: Option<&str>,
+) {
+    let mut profiles = load_profiles_from_yaml(path);
+    if let Some(snapshot_path) = &checkpoint.resume {
+        let resume_label = Snapshot::load(snapshot_path)
+            .unwrap_or_else(|e| panic!("Failed to load snapshot {}: {}", snapshot_path, e))
+            .label;
+        profiles.retain(|p| p.name == resume_label);
+    }
+    let multiple = profiles.len() > 1;
     log::info!("\n===== Simulation Profiles (YAML) =====");
     for profile in profiles {
+        let profile_events_out = events_out.map(|p| if multiple { per_profile_path(p, &profile.name) } else { p.to_string() });
+        let profile_traits_out = traits_out.map(|p| if multiple { per_profile_path(p, &profile.name) } else { p.to_string() });
+        let profile_social_out = social_out.map(|p| if multiple { per_profile_path(p, &profile.name) } else { p.to_string() });
+        let profile_disease_out = disease_out.map(|p| if multiple { per_profile_path(p, &profile.name) } else { p.to_string() });
         let width = profile.map_width.unwrap_or(profile.map_size.unwrap_or(20));
         let height = profile.map_height.unwrap_or(profile.map_size.unwrap_or(20));
         log::info!("Running profile: {} (map {}x{}, {} agents, {} ticks)", profile.name, width, height, profile.num_agents, profile.ticks);
-        run_simulation(width, height, profile.num_agents, profile.ticks, &profile.name, agent_types, profile_systems, profile_csv);
+        run_simulation(width, height, profile.num_agents, profile.ticks, &profile.name, agent_types, profile_systems, profile_csv, seed.or(profile.seed), checkpoint, profile_events_out.as_deref(), profile_traits_out.as_deref(), profile_social_out.as_deref(), profile_disease_out.as_deref());
     }
 }
 
@@ -260,6 +383,7 @@ pub fn run_benchmark_profiles_from_yaml(
     agent_types: &[AgentType],
     profile_systems: bool,
     profile_csv: &str,
+    seed: Option<u64>,
 ) {
     let profiles = load_profiles_from_yaml(path);
     let mut found = false;
@@ -269,14 +393,15 @@ pub fn run_benchmark_profiles_from_yaml(
         let width = profile.map_width.or(profile.map_size).unwrap_or(20);
         let height = profile.map_height.or(profile.map_size).unwrap_or(20);
         log::info!("Benchmarking profile: {} (map {}x{}, {} agents, {} ticks)", profile.name, width, height, profile.num_agents, profile.ticks);
-        run_simulation(width, height, profile.num_agents, profile.ticks, &profile.name, agent_types, profile_systems, profile_csv);
+        run_simulation(width, height, profile.num_agents, profile.ticks, &profile.name, agent_types, profile_systems, profile_csv, seed.or(profile.seed), &CheckpointConfig::default(), None, None, None, None);
     }
     if !found {
         log::warn!("[WARNING] No profiles with benchmark: true found in YAML. Falling back to hardcoded scaling benchmarks.");
-        run_scaling_benchmarks(agent_types);
+        run_scaling_benchmarks(agent_types, seed);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_profile_from_yaml(
     path: &str,
     profile_name: &str,
@@ -285,6 +410,8 @@ pub fn run_profile_from_yaml(
     profile_csv: &str,
     log_config: &LogConfig,
     event_log: Arc<Mutex<EventLog>>,
+    seed: Option<u64>,
+    events_out: Option<&str>,
 ) {
     // TODO: Remove crate::simulation dependency after full migration
     crate::simulation::run_profile_from_yaml(
@@ -295,12 +422,14 @@ pub fn run_profile_from_yaml(
         profile_csv,
         log_config,
         event_log,
+        seed,
+        events_out,
     )
 }
 
-pub fn run_scaling_benchmarks(agent_types: &[AgentType]) {
+pub fn run_scaling_benchmarks(agent_types: &[AgentType], seed: Option<u64>) {
     // TODO: Remove crate::simulation dependency after full migration
-    crate::simulation::run_scaling_benchmarks(agent_types)
+    crate::simulation::run_scaling_benchmarks(agent_types, seed)
 }
 
 // Example (pseudo-code):
@@ -4,13 +4,20 @@ use legion::IntoQuery;
 use legion::SystemBuilder;
 use rand::Rng;
 use log;
-use crate::food::Food;
-use crate::agent::{InteractionState, event::{AgentEvent, AgentEventLog}};
+use crate::food::{Food, FoodClaims};
+use crate::food::search::FISHING_RANGE;
+use crate::agent::{AgentType, Hunger, InteractionState, event::{AgentEvent, AgentEventLog, DeathCause}};
+use crate::agent::inventory::Inventory;
+use crate::sim_rng::{RngStream, SimRng};
+use crate::spatial::SpatialIndex;
+use crate::interaction::InteractionOutcome;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
 // Example usage in entity_interaction_system:
 // agent_event_log.push(AgentEvent::AteFood { agent, food, nutrition });
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -22,10 +29,16 @@ pub struct Velocity {
     pub dy: f32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InteractionStats {
+    /// Interactions started over the whole run.
     pub agent_interactions: usize,
+    /// Interactions in progress.
     pub active_interactions: usize,
     pub active_interactions_history: std::collections::VecDeque<usize>,
+    /// Completed interactions by outcome.
+    #[serde(default)]
+    pub outcomes: std::collections::BTreeMap<InteractionOutcome, usize>,
 }
 
 impl Default for InteractionStats {
@@ -34,45 +47,213 @@ impl Default for InteractionStats {
             agent_interactions: 0,
             active_interactions: 0,
             active_interactions_history: std::collections::VecDeque::with_capacity(100),
+            outcomes: std::collections::BTreeMap::new(),
         }
     }
 }
 
+impl InteractionStats {
+    /// "chat 3, share_food 1, share_information 0, fight 2"
+    pub fn outcomes_summary(&self) -> String {
+        InteractionOutcome::ALL.iter()
+            .map(|outcome| format!("{} {}", outcome.as_str(), self.outcomes.get(outcome).copied().unwrap_or(0)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// ECS resource: births and deaths (by cause) over the whole run.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Demographics {
+    pub births: usize,
+    pub deaths: std::collections::BTreeMap<DeathCause, usize>,
+    /// Sum of the ages (in ticks) of every agent that died.
+    pub total_age_at_death: u64,
+}
+
+impl Demographics {
+    pub fn record_death(&mut self, cause: DeathCause, age: u32) {
+        *self.deaths.entry(cause).or_insert(0) += 1;
+        self.total_age_at_death += age as u64;
+    }
+
+    pub fn deaths_of(&self, cause: DeathCause) -> usize {
+        self.deaths.get(&cause).copied().unwrap_or(0)
+    }
+
+    pub fn total_deaths(&self) -> usize {
+        self.deaths.values().sum()
+    }
+
+    pub fn mean_age_at_death(&self) -> Option<f32> {
+        let deaths = self.total_deaths();
+        (deaths > 0).then(|| self.total_age_at_death as f32 / deaths as f32)
+    }
+
+    /// "starvation 3, exhaustion 0, drowning 1, old_age 2"
+    pub fn deaths_summary(&self) -> String {
+        DeathCause::ALL.iter()
+            .map(|cause| format!("{} {}", cause.as_str(), self.deaths_of(*cause)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// ECS resource: the agent pairs that met in `entity_interaction_system` this tick,
+/// in pairing order.
 #[derive(Default, Clone, Debug)]
+pub struct AgentContacts(pub Vec<(legion::Entity, legion::Entity)>);
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct FoodStats {
     pub spawned_per_tick: usize,
     pub collected_per_tick: usize,
+    /// Items that rotted uneaten (see `food::ecology`).
+    #[serde(default)]
+    pub rotted: usize,
+    /// Items picked up to carry, rather than eaten (see `agent::inventory`).
+    #[serde(default)]
+    pub picked_up: usize,
+    /// Carried or stockpiled items eaten; these count towards `collected_per_tick` too.
+    #[serde(default)]
+    pub eaten_carried: usize,
+    /// Items put into stockpiles.
+    #[serde(default)]
+    pub deposited: usize,
+    /// Items taken out of stockpiles to eat.
+    #[serde(default)]
+    pub withdrawn: usize,
+    #[serde(default)]
+    pub stockpiles_started: usize,
 }
 
 // --- Entity Spawning Functions ---
 use legion::systems::CommandBuffer;
-pub fn spawn_food(cmd: &mut CommandBuffer, pos: Position, food_stats: Option<&mut FoodStats>) -> legion::Entity {
-    let nutrition = rand::thread_rng().gen_range(5.0..=10.0);
+/// Spawns a grain item from the grain config, as fields and storehouses put out. It is
+/// counted in `FoodStats` when the command buffer runs.
+pub fn spawn_food<R: Rng>(cmd: &mut CommandBuffer, pos: Position, ecology: &crate::food::ecology::FoodEcology, rng: &mut R) -> legion::Entity {
+    cmd.exec_mut(|_, resources| {
+        if let Some(mut stats) = resources.get_mut::<FoodStats>() {
+            stats.spawned_per_tick += 1;
+        }
+    });
+    spawn_food_item(cmd, pos, ecology.food_of(crate::food::FoodKind::Grain, rng), None)
+}
+
+pub fn spawn_food_item(cmd: &mut CommandBuffer, pos: Position, food: Food, food_stats: Option<&mut FoodStats>) -> legion::Entity {
     if let Some(stats) = food_stats {
         stats.spawned_per_tick += 1;
     }
-    cmd.push((pos, Food { nutrition }))
+    cmd.push((pos, food))
 }
 
 // --- Resource for food positions (for agent movement system) ---
 pub struct FoodPositions(pub Vec<(f32, f32)>);
 
 // --- ECS Agent Interaction System ---
+/// Runs the interactions `entity_interaction_system` started: ages cooldowns and partner
+/// memories, counts interactions down, breaks off pairs that drifted apart or lost a
+/// partner, and applies the outcome of those that finish (see `crate::interaction`).
 pub fn agent_interaction_system() -> impl legion::systems::Runnable {
     legion::SystemBuilder::new("AgentInteractionSystem")
-        .with_query(<(legion::Entity, &Position)>::query())
-        .build(|_cmd, _world, _, query| {
-            // --- Pass 1: Collect all agent info for matching (read-only) ---
-            for (_entity, _pos) in query.iter(_world) {
-                // No-op
+        .write_resource::<SimRng>()
+        .write_resource::<AgentEventLog>()
+        .write_resource::<InteractionStats>()
+        .read_resource::<FoodClaims>()
+        .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
+        .read_resource::<crate::log_config::LogConfig>()
+        .with_query(<(legion::Entity, &Position, &AgentType, &mut InteractionState, &mut Hunger, &mut crate::agent::Energy)>::query())
+        .with_query(<(&Position, &Food)>::query())
+        .build(|_cmd, world, (sim_rng, agent_event_log, stats, claims, event_log, log_config), (query, food_query)| {
+            let rng = sim_rng.stream(RngStream::InteractionOutcome);
+            let mut ongoing = Vec::new();
+            for (entity, pos, _, state, _, _) in query.iter_mut(world) {
+                state.tick();
+                if let Some(partner) = state.target {
+                    state.ticks = state.ticks.saturating_sub(1);
+                    ongoing.push((*entity, partner, *pos, state.ticks));
+                }
             }
-            // --- Phase 2/3: Decrement ticks, clear when done ---
-            for (_entity, _pos) in query.iter(_world) {
-                // No-op
+            // Each pair once, led by whichever agent comes first.
+            let mut handled = std::collections::HashSet::new();
+            for (a, b, a_pos, ticks) in ongoing {
+                if !handled.insert(a) {
+                    continue;
+                }
+                let partner_pos = match query.get_mut(world, b) {
+                    Ok((_, pos, _, state, _, _)) if state.target == Some(a) => Some(*pos),
+                    _ => None,
+                };
+                let drifted = partner_pos.is_some_and(|p| (p.x - a_pos.x).abs() > crate::interaction::BREAK_RANGE || (p.y - a_pos.y).abs() > crate::interaction::BREAK_RANGE);
+                if partner_pos.is_none() || drifted {
+                    for agent in [a, b] {
+                        if let Ok((_, _, agent_type, state, _, _)) = query.get_mut(world, agent) {
+                            if state.target.is_some_and(|t| t == a || t == b) {
+                                state.end(agent_type.interaction.cooldown_ticks);
+                            }
+                        }
+                    }
+                    handled.insert(b);
+                    if !log_config.quiet {
+                        event_log.lock().unwrap().push(format!("[INTERACT] Agent {:?} and {:?} broke off their interaction", a, b));
+                    }
+                    continue;
+                }
+                handled.insert(b);
+                if ticks > 0 {
+                    continue;
+                }
+                let mut participant = |agent: legion::Entity, partner: legion::Entity| {
+                    let (_, _, agent_type, state, hunger, energy) = query.get_mut(world, agent).ok()?;
+                    Some((agent_type.clone(), state.has_met(partner), hunger.clone(), energy.clone()))
+                };
+                let (Some((a_type, a_knows_b, mut a_hunger, mut a_energy)), Some((b_type, _, mut b_hunger, mut b_energy))) = (participant(a, b), participant(b, a)) else { continue };
+                let mut outcome = a_type.interaction.choose_outcome(rng);
+                if outcome == InteractionOutcome::Fight && a_knows_b {
+                    outcome = InteractionOutcome::ShareInformation;
+                }
+                let (mut a_tip, mut b_tip) = (None, None);
+                match outcome {
+                    InteractionOutcome::Chat => {}
+                    InteractionOutcome::ShareFood => {
+                        crate::interaction::share_food((&mut a_hunger, &a_type), (&mut b_hunger, &b_type));
+                    }
+                    InteractionOutcome::Fight => {
+                        crate::interaction::fight((&mut a_energy, &a_type), (&mut b_energy, &b_type), rng);
+                    }
+                    InteractionOutcome::ShareInformation => {
+                        let mut claimed_food = |agent| claims.claim_of(agent).and_then(|food| food_query.get(world, food).ok()).map(|(pos, _)| (pos.x, pos.y));
+                        if claims.claim_of(b).is_none() {
+                            b_tip = claimed_food(a);
+                        }
+                        if claims.claim_of(a).is_none() {
+                            a_tip = claimed_food(b);
+                        }
+                    }
+                }
+                for (agent, partner, hunger, energy, tip) in [(a, b, a_hunger, a_energy, a_tip), (b, a, b_hunger, b_energy, b_tip)] {
+                    if let Ok((_, _, agent_type, state, h, e)) = query.get_mut(world, agent) {
+                        state.end(agent_type.interaction.cooldown_ticks);
+                        state.remember(partner, outcome);
+                        if tip.is_some() {
+                            state.food_tip = tip;
+                        }
+                        *h = hunger;
+                        *e = energy;
+                    }
+                }
+                *stats.outcomes.entry(outcome).or_insert(0) += 1;
+                agent_event_log.push(AgentEvent::Interacted { agent: a, with: b, outcome });
+                if !log_config.quiet {
+                    event_log.lock().unwrap().push(format!("[INTERACT] Agent {:?} and {:?}: {}", a, b, outcome.as_str()));
+                }
             }
         })
 }
 
+/// Agents closer than this on both axes interact.
+pub const INTERACTION_RANGE: f32 = 1.5;
+
 // --- ECS Interaction System (agent-agent, agent-food) ---
 pub fn entity_interaction_system() -> impl legion::systems::Runnable {
     SystemBuilder::new("EntityInteractionSystem")
@@ -80,60 +261,134 @@ pub fn entity_interaction_system() -> impl legion::systems::Runnable {
         .write_resource::<Arc<Mutex<crate::event_log::EventLog>>>()
         .write_resource::<FoodStats>()
         .write_resource::<AgentEventLog>()
-        .with_query(<(legion::Entity, &Position, &InteractionState)>::query()) // agents
+        .write_resource::<SimRng>()
+        .read_resource::<SpatialIndex>()
+        .write_resource::<FoodClaims>()
+        .write_resource::<AgentContacts>()
+        .with_query(<(legion::Entity, &Position, &mut InteractionState, Option<&AgentType>, Option<&Hunger>, Option<&Inventory>)>::query()) // agents
         .with_query(<(legion::Entity, &Position, &Food)>::query()) // food
-        .with_query(<(legion::Entity, &mut Position)>::query())
-        .build(|cmd, world, (stats, event_log, food_stats, agent_event_log), (agent_query, food_query, agent_stats_query)| {
+        .with_query(<(&AgentType, &mut Hunger, Option<&mut Inventory>)>::query()) // consumption
+        .build(|cmd, world, (stats, event_log, food_stats, agent_event_log, sim_rng, index, food_claims, contacts), (agent_query, food_query, hunger_query)| {
             let mut event_log = event_log.lock().unwrap();
-            let agent_count = agent_query.iter(world).count();
-            let food_count = food_query.iter(world).count();
+            let agent_count = agent_query.iter_mut(world).count();
+            let food_count = index.food.len();
             event_log.push(format!("[TICK] Agents: {}, Food: {}", agent_count, food_count));
             let mut interactions_this_tick = 0;
             let mut active_interactions = 0;
-            let agents: Vec<_> = agent_query.iter(world).map(|(entity, pos, _)| (*entity, pos.x, pos.y)).collect();
-            let foods: Vec<_> = food_query.iter(world).map(|(e, pos, food)| (*e, pos.x, pos.y, food.nutrition)).collect();
+            contacts.0.clear();
+            // Full agents leave food alone unless their type overeats. Agents out gathering
+            // (not hungry, with room in their pack and food claimed) pick it up instead.
+            let agents: Vec<_> = agent_query.iter_mut(world).map(|(entity, pos, state, agent_type, hunger, inventory)| {
+                let gathering = match (agent_type, hunger, inventory) {
+                    (Some(agent_type), Some(hunger), Some(inventory)) =>
+                        inventory.has_room() && hunger.value >= agent_type.hunger_threshold && food_claims.claim_of(*entity).is_some(),
+                    _ => false,
+                };
+                let wants_food = gathering || match (agent_type, hunger) {
+                    (Some(agent_type), Some(hunger)) => agent_type.overeating || !hunger.is_full(agent_type),
+                    _ => true,
+                };
+                if state.target.is_some() {
+                    active_interactions += 1;
+                }
+                (*entity, pos.x, pos.y, wants_food, state.is_available(), agent_type.map(|t| t.diet.clone()).unwrap_or_default(), gathering)
+            }).collect();
+            // Each pair was counted twice.
+            active_interactions /= 2;
+            let agent_index: HashMap<legion::Entity, usize> = agents.iter().enumerate().map(|(i, a)| (a.0, i)).collect();
             let mut interacted = vec![false; agents.len()];
-            let mut rng = rand::thread_rng();
+            let rng = sim_rng.stream(RngStream::Interaction);
             // Collect interaction events first
-            let mut food_eaten: Vec<(legion::Entity, legion::Entity, f32)> = Vec::new();
+            // (agent, food item, food, tile, picked up rather than eaten)
+            let mut food_eaten = Vec::new();
+            let mut food_claimed: std::collections::HashSet<legion::Entity> = std::collections::HashSet::new();
             for i in 0..agents.len() {
-                let (agent_entity, x, y) = agents[i];
+                let (agent_entity, x, y, wants_food, available, ref diet, gathering) = agents[i];
                 if !interacted[i] {
-                    // Agent-agent interaction
-                    for j in (i+1)..agents.len() {
-                        let (other_entity, ox, oy) = agents[j];
-                        if (x - ox).abs() < 1.5 && (y - oy).abs() < 1.5 {
-                            interactions_this_tick += 1;
-                            active_interactions += 1;
-                            interacted[i] = true;
-                            interacted[j] = true;
-                            event_log.push(format!("[INTERACT] Agent {:?} interacted with Agent {:?}", agent_entity, other_entity));
-                            break;
+                    // Agent-agent interaction: the first later free agent (in query order) within 1.5 tiles on each axis
+                    let mut partner: Option<usize> = None;
+                    if available {
+                        index.agents.for_each_within(x, y, INTERACTION_RANGE * std::f32::consts::SQRT_2, |other| {
+                            if (x - other.x).abs() < INTERACTION_RANGE && (y - other.y).abs() < INTERACTION_RANGE {
+                                if let Some(&j) = agent_index.get(&other.entity) {
+                                    if j > i && agents[j].4 && !interacted[j] && partner.is_none_or(|p| j < p) {
+                                        partner = Some(j);
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    if let Some(j) = partner {
+                        interactions_this_tick += 1;
+                        active_interactions += 1;
+                        interacted[i] = true;
+                        interacted[j] = true;
+                        contacts.0.push((agent_entity, agents[j].0));
+                        event_log.push(format!("[INTERACT] Agent {:?} interacted with Agent {:?}", agent_entity, agents[j].0));
+                    }
+                    // Agent-food interaction (randomize food selection if multiple in range, preferred kinds first).
+                    // Fish can also be caught from the next tile, from the shore.
+                    if wants_food {
+                        let mut foods_in_range: Vec<_> = index.food.within(x, y, FISHING_RANGE * std::f32::consts::SQRT_2).into_iter()
+                            .filter(|f| {
+                                let kind = f.data;
+                                let reach = if kind == crate::food::FoodKind::Fish { FISHING_RANGE } else { 1.0 };
+                                (x - f.x).abs() < reach && (y - f.y).abs() < reach && !food_claimed.contains(&f.entity) && diet.eats(kind)
+                            })
+                            .collect();
+                        if !foods_in_range.is_empty() {
+                            use rand::seq::SliceRandom;
+                            foods_in_range.shuffle(rng);
+                            foods_in_range.sort_by_key(|f| !diet.prefers(f.data));
+                            let food_e = foods_in_range[0].entity;
+                            if let Ok((_, food_pos, food)) = food_query.get(world, food_e) {
+                                food_claimed.insert(food_e);
+                                food_eaten.push((agent_entity, food_e, *food, (food_pos.x as i32, food_pos.y as i32), gathering));
+                            }
                         }
                     }
-                    // Agent-food interaction (randomize food selection if multiple in range)
-                    let mut foods_in_range: Vec<_> = foods.iter()
-                        .filter(|(_food_e, fx, fy, _nutrition)| (x - *fx).abs() < 1.0 && (y - *fy).abs() < 1.0)
-                        .collect();
-                    if !foods_in_range.is_empty() {
-                        use rand::seq::SliceRandom;
-                        foods_in_range.shuffle(&mut rng);
-                        let (food_e, _fx, _fy, nutrition) = *foods_in_range[0];
-                        food_eaten.push((agent_entity, food_e, nutrition));
+                }
+            }
+            // Start this tick's interactions; the first agent's type sets the duration.
+            for &(a, b) in &contacts.0 {
+                let duration = agent_query.get_mut(world, a).ok()
+                    .and_then(|(_, _, _, agent_type, _, _)| agent_type.map(|t| t.interaction.duration_ticks))
+                    .unwrap_or_else(|| crate::interaction::InteractionConfig::default().duration_ticks);
+                for (agent, partner) in [(a, b), (b, a)] {
+                    if let Ok((_, _, state, _, _, _)) = agent_query.get_mut(world, agent) {
+                        state.begin(partner, duration);
                     }
                 }
             }
-            // Second pass: apply food eaten mutations
-            for (agent_entity, food_e, nutrition) in food_eaten {
-                if let Some((_entity, _pos)) = agent_stats_query.iter_mut(world).find(|(e, _pos)| **e == agent_entity) {
+            // Second pass: consumption - apply nutrition to Hunger and record the meal, or
+            // put the item in the gatherer's pack
+            for (agent_entity, food_e, food, (tile_x, tile_y), pick_up) in food_eaten {
+                if pick_up {
+                    if let Ok((_, _, Some(inventory))) = hunger_query.get_mut(world, agent_entity) {
+                        inventory.items.push(food);
+                    }
+                    food_stats.picked_up += 1;
+                    agent_event_log.push(AgentEvent::PickedUpFood { agent: agent_entity, food: food_e });
+                    event_log.push(format!("[INVENTORY] Agent {:?} picked up {} {:?}", agent_entity, food.kind.as_str(), food_e));
+                } else {
+                    if let Ok((agent_type, hunger, _)) = hunger_query.get_mut(world, agent_entity) {
+                        hunger.eat(food.nutrition, agent_type);
+                    }
                     agent_event_log.push(AgentEvent::AteFood {
                         agent: agent_entity,
                         food: food_e,
-                        nutrition,
+                        nutrition: food.nutrition,
                     });
+                    food_stats.collected_per_tick += 1;
                 }
+                food_claims.release_food(food_e);
                 cmd.remove(food_e);
-                food_stats.collected_per_tick += 1;
+                // The harvest depletes the tile it grew on.
+                cmd.exec_mut(move |_, resources| {
+                    if let Some(mut ecology) = resources.get_mut::<crate::food::ecology::FoodEcology>() {
+                        ecology.deplete(tile_x, tile_y);
+                    }
+                });
             }
             stats.agent_interactions += interactions_this_tick;
             stats.active_interactions = active_interactions;
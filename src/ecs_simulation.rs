@@ -51,6 +51,7 @@ impl SystemProfile {
 
 mod agent {
     pub use crate::agent::event_log_bridge::agent_event_log_to_gui_system;
+    pub use crate::agent::event_sink::agent_event_sink_system;
 }
 
 /// Builds a Legion Schedule containing all ECS systems in the correct order, with per-system profiling.
@@ -59,13 +60,33 @@ pub fn build_simulation_schedule_profiled() -> Schedule {
         .add_system(collect_food_positions_system())
         .add_system(collect_food_spawn_positions_system())
         .add_system(food_spawn_apply_system())
+        .add_system(crate::spatial::spatial_index_system())
+        .add_system(crate::agent::work::work_system())
+        .add_system(crate::structure::storehouse_system())
         .add_system(action_selection_system())
         .add_system(path_following_system())
+        .add_system(crate::agent::swimming::swimming_system())
         .add_system(crate::agent::systems::passive_hunger_system())
+        .add_system(crate::agent::energy::rest_system())
+        .add_system(crate::agent::aging::aging_system())
+        .add_system(crate::world_clock::clock_factor_system())
         .add_system(agent_movement_history_system())
+        .add_system(crate::spatial::spatial_index_system())
         .add_system(entity_interaction_system())
+        .add_system(crate::agent::inventory::inventory_system())
+        .add_system(crate::agent::reproduction::reproduction_system())
+        .add_system(crate::agent::disease::disease_system())
+        .add_system(crate::ecs_components::agent_interaction_system())
+        .add_system(crate::social::social_graph_system())
+        // Births go through the command buffer; apply them so this tick's events include them.
+        .flush()
+        .add_system(crate::food::ecology::food_decay_system())
         .add_system(agent_death_system())
+        .add_system(crate::evolution::fitness_system())
+        .add_system(crate::agent::genome::trait_histogram_system())
+        .add_system(agent::agent_event_sink_system())
         .add_system(agent::agent_event_log_to_gui_system())
+        .add_system(crate::world_clock::world_clock_system())
         // Add any other new systems here as needed
         .build()
 }
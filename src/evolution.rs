@@ -0,0 +1,215 @@
+//! Neuroevolution of MLP brains (`--evolve N`).
+//!
+//! Each generation runs a fresh headless world in which every agent carries its own
+//! `MLPConfig`. When the generation ends, each agent's fitness is its lifetime food
+//! eaten plus `SURVIVAL_WEIGHT` per tick survived. The next generation keeps the best
+//! brain unchanged and fills the rest with mutated copies of tournament winners.
+//!
+//! Output, in `EvolutionConfig::out_dir`:
+//! - `fitness.csv`: best/mean/worst fitness per generation
+//! - `best_gen<N>.yaml`: the best agent type of generation N, loadable with `--agent-types`
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use legion::{Entity, IntoQuery, Resources, SystemBuilder, World};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::agent::{AgentType, DecisionEngineConfig, MLPConfig};
+use crate::agent::decision::{Action, OBSERVATION_SIZE};
+use crate::agent::event::{AgentEvent, AgentEventLog};
+use crate::ecs::schedule::spawn_initial_world;
+use crate::ecs_simulation::{build_simulation_schedule_profiled, simulation_tick};
+use crate::util::agent_types_to_yaml;
+
+/// Fitness per tick survived; a food item is worth its nutrition (in `config/food.yaml`:
+/// berries 6-12, grain 5-10, fish 8-14, meat 12-20).
+pub const SURVIVAL_WEIGHT: f32 = 0.1;
+/// Chance that any single weight or bias is mutated.
+pub const MUTATION_RATE: f64 = 0.1;
+/// Largest change a mutation makes to a weight or bias.
+pub const MUTATION_SCALE: f32 = 0.5;
+/// Candidates per tournament when picking a parent.
+pub const TOURNAMENT_SIZE: usize = 3;
+/// Hidden layers for brains created from scratch.
+pub const DEFAULT_HIDDEN: [usize; 1] = [8];
+
+/// ECS component: what an evolving agent has achieved so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fitness {
+    pub food_eaten: f32,
+    pub ticks_alive: u32,
+}
+
+impl Fitness {
+    pub fn score(&self) -> f32 {
+        self.food_eaten + SURVIVAL_WEIGHT * self.ticks_alive as f32
+    }
+}
+
+/// Credits `AteFood` and `AteCarriedFood` nutrition and survival ticks to agents that have a `Fitness`.
+/// Must run before `agent_event_log_to_gui_system`, which clears the log.
+pub fn fitness_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("FitnessSystem")
+        .read_resource::<AgentEventLog>()
+        .with_query(<&mut Fitness>::query())
+        .build(|_cmd, world, agent_event_log, query| {
+            for fitness in query.iter_mut(world) {
+                fitness.ticks_alive += 1;
+            }
+            for event in &agent_event_log.0 {
+                if let AgentEvent::AteFood { agent, nutrition, .. } | AgentEvent::AteCarriedFood { agent, nutrition } = event {
+                    if let Ok(fitness) = query.get_mut(world, *agent) {
+                        fitness.food_eaten += nutrition;
+                    }
+                }
+            }
+        })
+}
+
+#[derive(Clone, Debug)]
+pub struct EvolutionConfig {
+    pub generations: usize,
+    pub map_size: i32,
+    /// Agents (brains) per generation.
+    pub population: usize,
+    /// Ticks per generation.
+    pub ticks: usize,
+    pub out_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: f32,
+    pub mean: f32,
+    pub worst: f32,
+    pub best_fitness: Fitness,
+}
+
+/// The agent type brains are evolved for: the first type with an MLP that takes our
+/// observation vector, otherwise the first type given a random brain.
+fn template_type<R: Rng>(agent_types: &[AgentType], rng: &mut R) -> AgentType {
+    let usable = agent_types.iter().find(|a| matches!(&a.decision_engine, DecisionEngineConfig::MLP(c) if c.input_size == OBSERVATION_SIZE));
+    match usable {
+        Some(agent_type) => agent_type.clone(),
+        None => {
+            let mut agent_type = agent_types.first().cloned().unwrap_or_default();
+            log::info!("[EVOLVE] No agent type has a usable MLP; evolving a random brain for '{}'", agent_type.name);
+            agent_type.decision_engine = DecisionEngineConfig::MLP(MLPConfig::random(OBSERVATION_SIZE, &DEFAULT_HIDDEN, Action::ALL.len(), rng));
+            agent_type
+        }
+    }
+}
+
+fn brain_of(agent_type: &AgentType) -> MLPConfig {
+    match &agent_type.decision_engine {
+        DecisionEngineConfig::MLP(config) => config.clone(),
+        DecisionEngineConfig::Simple => unreachable!("template type always has an MLP"),
+    }
+}
+
+/// Index of the fittest of `TOURNAMENT_SIZE` randomly drawn brains.
+fn tournament<R: Rng>(scores: &[f32], rng: &mut R) -> usize {
+    (0..TOURNAMENT_SIZE)
+        .map(|_| rng.gen_range(0..scores.len()))
+        .max_by(|a, b| scores[*a].partial_cmp(&scores[*b]).unwrap_or(std::cmp::Ordering::Equal))
+        .expect("tournament size is non-zero")
+}
+
+/// Best brain unchanged, then mutated copies of tournament winners.
+pub fn next_generation<R: Rng>(brains: &[MLPConfig], scores: &[f32], rng: &mut R) -> Vec<MLPConfig> {
+    let best = best_index(scores);
+    let mut next = Vec::with_capacity(brains.len());
+    next.push(brains[best].clone());
+    while next.len() < brains.len() {
+        let mut child = brains[tournament(scores, rng)].clone();
+        child.mutate(rng, MUTATION_RATE, MUTATION_SCALE);
+        next.push(child);
+    }
+    next
+}
+
+fn best_index(scores: &[f32]) -> usize {
+    scores.iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Runs one generation and returns each brain's final fitness.
+fn run_generation(config: &EvolutionConfig, template: &AgentType, brains: &[MLPConfig], seed: u64) -> Vec<Fitness> {
+    let mut world = World::default();
+    let mut resources = Resources::default();
+    spawn_initial_world(&mut world, &mut resources, config.map_size, config.map_size, brains.len(), std::slice::from_ref(template), Some(seed));
+    // Hand out brains in query order, which is deterministic for a given seed.
+    let mut brain_of_entity: HashMap<Entity, usize> = HashMap::new();
+    let entities: Vec<Entity> = <(Entity, &AgentType)>::query().iter(&world).map(|(e, _)| *e).collect();
+    for (i, entity) in entities.into_iter().enumerate() {
+        let mut entry = world.entry(entity).expect("agent just spawned");
+        entry.get_component_mut::<AgentType>().unwrap().decision_engine = DecisionEngineConfig::MLP(brains[i].clone());
+        entry.add_component(Fitness::default());
+        brain_of_entity.insert(entity, i);
+    }
+    let mut fitness = vec![Fitness::default(); brains.len()];
+    let mut schedule = build_simulation_schedule_profiled();
+    for _ in 0..config.ticks {
+        simulation_tick(&mut world, &mut resources, &mut schedule);
+        // Dead agents are gone from the world; their last recorded fitness stands.
+        let mut alive = 0;
+        for (entity, f) in <(Entity, &Fitness)>::query().iter(&world) {
+            fitness[brain_of_entity[entity]] = f.clone();
+            alive += 1;
+        }
+        if alive == 0 {
+            break;
+        }
+    }
+    fitness
+}
+
+/// Runs `config.generations` generations, writing the fitness CSV and each generation's
+/// best agent type. Returns per-generation stats.
+pub fn run_evolution(config: &EvolutionConfig, agent_types: &[AgentType], seed: Option<u64>) -> io::Result<Vec<GenerationStats>> {
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    log::info!("[SEED] Evolution seed: {}", seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let template = template_type(agent_types, &mut rng);
+    let population = config.population.max(1);
+    let mut brains: Vec<MLPConfig> = (0..population)
+        .map(|i| {
+            let mut brain = brain_of(&template);
+            if i > 0 {
+                brain.mutate(&mut rng, MUTATION_RATE, MUTATION_SCALE);
+            }
+            brain
+        })
+        .collect();
+    fs::create_dir_all(&config.out_dir)?;
+    let mut csv = File::create(config.out_dir.join("fitness.csv"))?;
+    writeln!(csv, "generation,best,mean,worst,best_food_eaten,best_ticks_alive")?;
+    let mut history = Vec::with_capacity(config.generations);
+    for generation in 0..config.generations {
+        let fitness = run_generation(config, &template, &brains, rng.gen());
+        let scores: Vec<f32> = fitness.iter().map(Fitness::score).collect();
+        let best = best_index(&scores);
+        let stats = GenerationStats {
+            generation,
+            best: scores[best],
+            mean: scores.iter().sum::<f32>() / scores.len() as f32,
+            worst: scores.iter().copied().fold(f32::INFINITY, f32::min),
+            best_fitness: fitness[best].clone(),
+        };
+        log::info!("[EVOLVE] Generation {}: best {:.2} mean {:.2} worst {:.2}", generation, stats.best, stats.mean, stats.worst);
+        writeln!(csv, "{},{:.4},{:.4},{:.4},{:.4},{}", generation, stats.best, stats.mean, stats.worst, stats.best_fitness.food_eaten, stats.best_fitness.ticks_alive)?;
+        csv.flush()?;
+        let mut best_type = template.clone();
+        best_type.decision_engine = DecisionEngineConfig::MLP(brains[best].clone());
+        fs::write(config.out_dir.join(format!("best_gen{:03}.yaml", generation)), agent_types_to_yaml(&[best_type]))?;
+        history.push(stats);
+        brains = next_generation(&brains, &scores, &mut rng);
+    }
+    Ok(history)
+}
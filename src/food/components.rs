@@ -1,6 +1,144 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use std::collections::HashMap;
+use legion::Entity;
+use serde::{Serialize, Deserialize};
+
+/// What a food item is. Each kind has its own nutrition range, terrain and shelf life
+/// (see `food::ecology`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FoodKind {
+    #[default]
+    Berries,
+    Grain,
+    Fish,
+    Meat,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 4] = [FoodKind::Berries, FoodKind::Grain, FoodKind::Fish, FoodKind::Meat];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FoodKind::Berries => "berries",
+            FoodKind::Grain => "grain",
+            FoodKind::Fish => "fish",
+            FoodKind::Meat => "meat",
+        }
+    }
+
+    pub fn to_char(&self) -> char {
+        match self {
+            FoodKind::Berries => '*',
+            FoodKind::Grain => ',',
+            FoodKind::Fish => '%',
+            FoodKind::Meat => '&',
+        }
+    }
+
+    /// Fill colour in the GUI.
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            FoodKind::Berries => (200, 40, 140),
+            FoodKind::Grain => (230, 200, 60),
+            FoodKind::Fish => (150, 200, 240),
+            FoodKind::Meat => (170, 60, 40),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Food {
     pub nutrition: f32,
+    #[serde(default)]
+    pub kind: FoodKind,
+}
+
+impl Food {
+    /// A food item of the default kind.
+    pub fn new(nutrition: f32) -> Self {
+        Self { nutrition, kind: FoodKind::default() }
+    }
+}
+
+/// What an agent type eats (the YAML `diet` block). The default eats anything.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Diet {
+    /// Kinds sought out before any other food in view.
+    #[serde(default)]
+    pub prefers: Vec<FoodKind>,
+    /// Kinds never eaten.
+    #[serde(default)]
+    pub avoids: Vec<FoodKind>,
+}
+
+impl Diet {
+    pub fn eats(&self, kind: FoodKind) -> bool {
+        !self.avoids.contains(&kind)
+    }
+
+    pub fn prefers(&self, kind: FoodKind) -> bool {
+        self.prefers.contains(&kind)
+    }
+
+    pub fn is_omnivore(&self) -> bool {
+        self.prefers.is_empty() && self.avoids.is_empty()
+    }
 }
 
 pub struct PendingFoodSpawns(pub std::collections::VecDeque<(f32, f32)>);
+
+/// ECS resource: which agent is heading for which food item, so hungry agents spread
+/// out instead of all chasing the same one. An agent holds at most one claim.
+#[derive(Clone, Debug, Default)]
+pub struct FoodClaims {
+    by_food: HashMap<Entity, Entity>,
+    by_agent: HashMap<Entity, Entity>,
+}
+
+impl FoodClaims {
+    /// Reserves `food` for `agent`, dropping the agent's previous claim. Returns false,
+    /// changing nothing, if another agent already holds it.
+    pub fn claim(&mut self, food: Entity, agent: Entity) -> bool {
+        if self.is_claimed_by_other(food, agent) {
+            return false;
+        }
+        self.release_agent(agent);
+        self.by_food.insert(food, agent);
+        self.by_agent.insert(agent, food);
+        true
+    }
+
+    pub fn claimant(&self, food: Entity) -> Option<Entity> {
+        self.by_food.get(&food).copied()
+    }
+
+    pub fn claim_of(&self, agent: Entity) -> Option<Entity> {
+        self.by_agent.get(&agent).copied()
+    }
+
+    pub fn is_claimed_by_other(&self, food: Entity, agent: Entity) -> bool {
+        self.claimant(food).is_some_and(|holder| holder != agent)
+    }
+
+    /// Drops the agent's claim, if any (it re-decided, ate or died).
+    pub fn release_agent(&mut self, agent: Entity) {
+        if let Some(food) = self.by_agent.remove(&agent) {
+            self.by_food.remove(&food);
+        }
+    }
+
+    /// Drops any claim on `food` (it was eaten).
+    pub fn release_food(&mut self, food: Entity) {
+        if let Some(agent) = self.by_food.remove(&food) {
+            self.by_agent.remove(&agent);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_food.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_food.is_empty()
+    }
+}
@@ -0,0 +1,222 @@
+//! Food ecology. Food comes in kinds (berries, grain, fish, meat), each growing on its own
+//! terrains with its own nutrition range and shelf life: berries in forests, grain on
+//! grass, fish in water, and game (meat) more rarely on both kinds of land. Every tile of
+//! a terrain some kind grows on carries biomass (0..1) that regrows at that terrain's
+//! rate. Food spawns on a random growing tile with a chance equal to its biomass, and
+//! eating an item takes `harvest_depletion` off its tile. Uneaten food spoils after its
+//! kind's `lifetime`.
+//!
+//! Parameters live in `config/food.yaml`; without that file the defaults below apply.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use legion::{Entity, IntoQuery, SystemBuilder};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::ecs_components::FoodStats;
+use crate::food::{Food, FoodClaims, FoodKind};
+use crate::map::{Map, Terrain};
+
+/// Where headless and GUI runs read the food configuration from.
+pub const FOOD_CONFIG_PATH: &str = "config/food.yaml";
+
+/// What one kind of food is like and where it grows.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KindConfig {
+    /// Terrains it grows on.
+    pub terrain: Vec<Terrain>,
+    /// Nutrition of items of this kind, drawn uniformly from [min, max].
+    pub nutrition: [f32; 2],
+    /// Ticks an uneaten item lasts before it spoils; 0 means never.
+    #[serde(default)]
+    pub lifetime: u32,
+    /// Chance of this kind relative to the others growing on the same terrain.
+    #[serde(default = "default_abundance")]
+    pub abundance: f32,
+}
+
+/// Food spawning, regrowth and spoilage parameters (`config/food.yaml`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoodConfig {
+    /// Spawn attempts per tick, at least...
+    #[serde(default = "default_min_spawn_attempts")]
+    pub min_spawn_attempts: u32,
+    /// ...and one per this many map tiles.
+    #[serde(default = "default_tiles_per_attempt")]
+    pub tiles_per_attempt: u32,
+    /// Biomass an eaten item takes from its tile.
+    #[serde(default = "default_harvest_depletion")]
+    pub harvest_depletion: f32,
+    /// Biomass regained per tick, by terrain; terrains missing here never regrow.
+    #[serde(default = "default_regrowth")]
+    pub regrowth: BTreeMap<Terrain, f32>,
+    /// The kinds of food that grow; food never spawns on terrain none of them lists.
+    #[serde(default = "default_kinds")]
+    pub kinds: BTreeMap<FoodKind, KindConfig>,
+}
+
+fn default_abundance() -> f32 { 1.0 }
+fn default_min_spawn_attempts() -> u32 { 2 }
+fn default_tiles_per_attempt() -> u32 { 20000 }
+fn default_harvest_depletion() -> f32 { 0.5 }
+fn default_regrowth() -> BTreeMap<Terrain, f32> {
+    BTreeMap::from([(Terrain::Grass, 0.005), (Terrain::Forest, 0.002), (Terrain::Water, 0.003)])
+}
+fn default_kinds() -> BTreeMap<FoodKind, KindConfig> {
+    let kind = |terrain: &[Terrain], nutrition, lifetime, abundance| KindConfig { terrain: terrain.to_vec(), nutrition, lifetime, abundance };
+    BTreeMap::from([
+        (FoodKind::Berries, kind(&[Terrain::Forest], [6.0, 12.0], 400, 1.0)),
+        (FoodKind::Grain, kind(&[Terrain::Grass], [5.0, 10.0], 2000, 1.0)),
+        (FoodKind::Fish, kind(&[Terrain::Water], [8.0, 14.0], 150, 1.0)),
+        (FoodKind::Meat, kind(&[Terrain::Grass, Terrain::Forest], [12.0, 20.0], 200, 0.1)),
+    ])
+}
+
+/// A nutrition value drawn uniformly from `[min, max]`; no draw if the range is empty.
+fn draw_nutrition<R: Rng>([min, max]: [f32; 2], rng: &mut R) -> f32 {
+    if max > min { rng.gen_range(min..=max) } else { min }
+}
+
+impl Default for FoodConfig {
+    fn default() -> Self {
+        Self {
+            min_spawn_attempts: default_min_spawn_attempts(),
+            tiles_per_attempt: default_tiles_per_attempt(),
+            harvest_depletion: default_harvest_depletion(),
+            regrowth: default_regrowth(),
+            kinds: default_kinds(),
+        }
+    }
+}
+
+impl FoodConfig {
+    /// Reads the config from `path`. A missing file gives the defaults; an invalid one is
+    /// logged and does too.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(contents) = std::fs::read_to_string(&path) else { return Self::default() };
+        serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[CONFIG] {}: invalid food config ({}); using defaults", path.as_ref().display(), e);
+            Self::default()
+        })
+    }
+
+    /// Kinds growing on `terrain`, with their configs.
+    pub fn kinds_on(&self, terrain: Terrain) -> impl Iterator<Item = (FoodKind, &KindConfig)> {
+        self.kinds.iter().filter(move |(_, k)| k.terrain.contains(&terrain)).map(|(kind, k)| (*kind, k))
+    }
+}
+
+/// ECS resource: the food config and the biomass of every tile below full.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FoodEcology {
+    pub config: FoodConfig,
+    /// Growing tiles not at full biomass; all others are full.
+    pub depleted: BTreeMap<(i32, i32), f32>,
+}
+
+impl FoodEcology {
+    pub fn new(config: FoodConfig) -> Self {
+        Self { config, depleted: BTreeMap::new() }
+    }
+
+    /// Whether any kind of food grows on `terrain`.
+    pub fn grows(&self, terrain: Terrain) -> bool {
+        self.config.kinds_on(terrain).next().is_some()
+    }
+
+    /// Biomass of (`x`, `y`): 0 on terrain that grows nothing.
+    pub fn biomass(&self, map: &Map, x: i32, y: i32) -> f32 {
+        if !self.grows(map.terrain_at(x as f32, y as f32)) {
+            return 0.0;
+        }
+        self.depleted.get(&(x, y)).copied().unwrap_or(1.0)
+    }
+
+    /// Takes an eaten item's share off the biomass of (`x`, `y`).
+    pub fn deplete(&mut self, x: i32, y: i32) {
+        let biomass = self.depleted.entry((x, y)).or_insert(1.0);
+        *biomass = (*biomass - self.config.harvest_depletion).max(0.0);
+    }
+
+    /// One tick of regrowth on every depleted tile.
+    pub fn regrow(&mut self, map: &Map) {
+        let config = &self.config;
+        self.depleted.retain(|&(x, y), biomass| {
+            let terrain = map.terrain_at(x as f32, y as f32);
+            if config.kinds_on(terrain).next().is_none() {
+                return false;
+            }
+            *biomass += config.regrowth.get(&terrain).copied().unwrap_or(0.0);
+            *biomass < 1.0
+        });
+    }
+
+    /// Spawn attempts per tick on `map`, before seasonal scaling.
+    pub fn spawn_attempts(&self, map: &Map) -> u32 {
+        let tiles = (map.width * map.height) as u32;
+        (tiles / self.config.tiles_per_attempt.max(1)).max(self.config.min_spawn_attempts)
+    }
+
+    /// A new item of one of the kinds growing on `terrain`, picked by abundance. Draws from
+    /// `rng` for the kind only if several grow there. `None` if nothing grows there.
+    pub fn grow<R: Rng>(&self, terrain: Terrain, rng: &mut R) -> Option<Food> {
+        let kinds: Vec<_> = self.config.kinds_on(terrain).collect();
+        let (kind, config) = match kinds.as_slice() {
+            [] => return None,
+            [only] => *only,
+            _ => {
+                let total: f32 = kinds.iter().map(|(_, k)| k.abundance).sum();
+                let mut pick = rng.gen::<f32>() * total;
+                *kinds.iter().find(|(_, k)| { pick -= k.abundance; pick < 0.0 }).unwrap_or(&kinds[kinds.len() - 1])
+            }
+        };
+        Some(Food { nutrition: draw_nutrition(config.nutrition, rng), kind })
+    }
+
+    /// A new item of `kind`, as fields and storehouses put out grain. Its nutrition is
+    /// drawn from the kind's range, or from the default one if the config leaves it out.
+    pub fn food_of<R: Rng>(&self, kind: FoodKind, rng: &mut R) -> Food {
+        let range = self.config.kinds.get(&kind).map_or_else(|| default_kinds()[&kind].nutrition, |k| k.nutrition);
+        Food { nutrition: draw_nutrition(range, rng), kind }
+    }
+
+    /// Ticks an item of `kind` keeps; 0 means forever.
+    pub fn lifetime(&self, kind: FoodKind) -> u32 {
+        self.config.kinds.get(&kind).map_or(0, |k| k.lifetime)
+    }
+
+    /// "12 depleted tiles, mean biomass 0.43"
+    pub fn summary(&self) -> String {
+        let mean = if self.depleted.is_empty() { 1.0 } else { self.depleted.values().sum::<f32>() / self.depleted.len() as f32 };
+        format!("{} depleted tiles, mean biomass {:.2}", self.depleted.len(), mean)
+    }
+}
+
+/// ECS component: ticks since a food item appeared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FoodAge(pub u32);
+
+/// Ages every food item and removes those older than their kind's lifetime. Items get
+/// their `FoodAge` on their first tick.
+pub fn food_decay_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("FoodDecaySystem")
+        .read_resource::<FoodEcology>()
+        .write_resource::<FoodClaims>()
+        .write_resource::<FoodStats>()
+        .with_query(<(Entity, &Food, Option<&mut FoodAge>)>::query())
+        .build(|cmd, world, (ecology, claims, stats), query| {
+            for (entity, food, age) in query.iter_mut(world) {
+                let Some(age) = age else {
+                    cmd.add_component(*entity, FoodAge(0));
+                    continue;
+                };
+                age.0 += 1;
+                let lifetime = ecology.lifetime(food.kind);
+                if lifetime > 0 && age.0 >= lifetime {
+                    claims.release_food(*entity);
+                    cmd.remove(*entity);
+                    stats.rotted += 1;
+                }
+            }
+        })
+}
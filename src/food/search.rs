@@ -0,0 +1,69 @@
+//! Finding food an agent can actually get to, and will eat.
+
+use legion::Entity;
+use crate::agent::{AgentState, AgentType};
+use crate::food::{FoodClaims, FoodKind};
+use crate::map::Map;
+use crate::navigation::pathfinding::{a_star_path, tile_cost};
+use crate::spatial::SpatialIndex;
+
+/// A* searches tried per decision before giving up on the food in view.
+pub const MAX_PATH_ATTEMPTS: usize = 3;
+/// A* search limit used for food paths, as for every other agent path.
+pub const FOOD_PATH_LIMIT: i32 = 120;
+/// How far (in tiles, on each axis) an agent on the shore can reach a fish.
+pub const FISHING_RANGE: f32 = 1.5;
+
+/// Food found by `nearest_reachable_food`, with the route to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoodTarget {
+    pub food: Entity,
+    /// Where the route ends: the food's tile, or the shore next to it when fishing.
+    pub x: f32,
+    pub y: f32,
+    pub path: Vec<(f32, f32)>,
+    /// Whether the agent fishes from the shore rather than stepping onto the food's tile.
+    pub fishing: bool,
+}
+
+/// Closest food within the agent's `vision` that its diet allows, no other agent has
+/// claimed and it has an A* route to. The route leads onto the food's tile, or, for fish
+/// in water the agent can't enter, to the shore next to it. Kinds the diet prefers come
+/// first, then candidates are tried nearest first, at most `MAX_PATH_ATTEMPTS` of them.
+pub fn nearest_reachable_food(
+    index: &SpatialIndex,
+    claims: &FoodClaims,
+    map: &Map,
+    agent: Entity,
+    agent_type: &AgentType,
+    agent_state: &AgentState,
+    pos: (f32, f32),
+) -> Option<FoodTarget> {
+    let dist_sq = |x: f32, y: f32| (x - pos.0).powi(2) + (y - pos.1).powi(2);
+    let enterable = |x: i32, y: i32| x >= 0 && y >= 0 && x < map.width && y < map.height
+        && tile_cost(map.tiles[y as usize][x as usize], agent_type, Some(agent_state)).is_some();
+    let mut candidates: Vec<_> = index.food.within(pos.0, pos.1, agent_type.vision).into_iter()
+        .filter(|food| !claims.is_claimed_by_other(food.entity, agent) && agent_type.diet.eats(food.data))
+        .filter_map(|food| {
+            let (fx, fy) = (food.x as i32, food.y as i32);
+            if enterable(fx, fy) {
+                return Some((food, (food.x, food.y), false));
+            }
+            if food.data != FoodKind::Fish {
+                return None;
+            }
+            let shore = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (fx + dx, fy + dy)))
+                .filter(|&(sx, sy)| (sx, sy) != (fx, fy) && enterable(sx, sy))
+                .map(|(sx, sy)| (sx as f32, sy as f32))
+                .min_by(|a, b| dist_sq(a.0, a.1).total_cmp(&dist_sq(b.0, b.1)))?;
+            Some((food, shore, true))
+        })
+        .collect();
+    // Stable, so equally distant food keeps grid order.
+    candidates.sort_by(|(a, ..), (b, ..)| dist_sq(a.x, a.y).total_cmp(&dist_sq(b.x, b.y)));
+    candidates.sort_by_key(|(food, ..)| !agent_type.diet.prefers(food.data));
+    candidates.into_iter().take(MAX_PATH_ATTEMPTS).find_map(|(food, (x, y), fishing)| {
+        let path = a_star_path(map, agent_type, agent_state, (pos.0 as i32, pos.1 as i32), (x as i32, y as i32), FOOD_PATH_LIMIT)?;
+        Some(FoodTarget { food: food.entity, x, y, path, fishing })
+    })
+}
@@ -1,7 +1,9 @@
 use legion::*;
 use crate::food::Food;
 use crate::food::PendingFoodSpawns;
+use crate::food::ecology::FoodEcology;
 use crate::ecs_components::{Position, FoodPositions, FoodStats};
+use crate::sim_rng::{RngStream, SimRng};
 use rand::Rng;
 
 pub fn collect_food_positions_system() -> impl systems::Runnable {
@@ -21,26 +23,26 @@ pub fn collect_food_spawn_positions_system() -> impl systems::Runnable {
     SystemBuilder::new("CollectFoodSpawnPositionsSystem")
         .write_resource::<PendingFoodSpawns>()
         .read_resource::<crate::map::Map>()
-        .build(|_, _world, (pending_food, map), _| {
-            let num_to_spawn = (map.width * map.height / 20000).max(2);
-            let mut rng = rand::thread_rng();
+        .write_resource::<SimRng>()
+        .read_resource::<crate::world_clock::WorldClock>()
+        .write_resource::<FoodEcology>()
+        .build(|_, _world, (pending_food, map, sim_rng, clock, ecology), _| {
+            ecology.regrow(map);
+            // Scaled by the season: fewer items in winter, more in summer.
+            let num_to_spawn = (ecology.spawn_attempts(map) as f32 * clock.food_factor()).round() as i32;
+            let rng = sim_rng.stream(RngStream::FoodSpawn);
             let mut positions_to_spawn = Vec::new();
             for _ in 0..num_to_spawn {
-                let mut x;
-                let mut y;
-                let mut tries = 0;
-                loop {
-                    x = rng.gen_range(0..map.width) as f32;
-                    y = rng.gen_range(0..map.height) as f32;
-                    if map.tiles[y as usize][x as usize] == crate::map::Terrain::Grass || map.tiles[y as usize][x as usize] == crate::map::Terrain::Forest {
-                        break;
-                    }
-                    tries += 1;
-                    if tries > 1000 {
-                        break;
-                    }
+                // A random tile that grows food; it bears with a chance equal to its biomass.
+                let tile = (0..1000).find_map(|_| {
+                    let (x, y) = (rng.gen_range(0..map.width), rng.gen_range(0..map.height));
+                    ecology.grows(map.tiles[y as usize][x as usize]).then_some((x, y))
+                });
+                let Some((x, y)) = tile else { continue };
+                let biomass = ecology.biomass(map, x, y);
+                if biomass >= 1.0 || rng.gen::<f32>() < biomass {
+                    positions_to_spawn.push((x as f32, y as f32));
                 }
-                positions_to_spawn.push((x, y));
             }
             pending_food.0 = positions_to_spawn.into();
         })
@@ -50,11 +52,15 @@ pub fn food_spawn_apply_system() -> impl systems::Runnable {
     SystemBuilder::new("FoodSpawnApplySystem")
         .write_resource::<PendingFoodSpawns>()
         .write_resource::<FoodStats>()
-        .build(|cmd, _world, (pending, food_stats), _| {
+        .write_resource::<SimRng>()
+        .read_resource::<crate::map::Map>()
+        .read_resource::<FoodEcology>()
+        .build(|cmd, _world, (pending, food_stats, sim_rng, map, ecology), _| {
+            let rng = sim_rng.stream(RngStream::FoodSpawn);
             for (x, y) in pending.0.drain(..) {
-                let pos = Position { x, y };
-                let stats_opt = Some(&mut **food_stats);
-                crate::ecs_components::spawn_food(cmd, pos, stats_opt);
+                if let Some(food) = ecology.grow(map.terrain_at(x, y), rng) {
+                    crate::ecs_components::spawn_food_item(cmd, Position { x, y }, food, Some(&mut **food_stats));
+                }
             }
         })
 }
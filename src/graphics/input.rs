@@ -46,6 +46,9 @@ pub fn collect_input_events(
             Event::KeyDown { keycode: Some(Keycode::S), .. } => {
                 sim_ui_state.input_queue.push(InputIntent::SpawnAgentsRandom { count: 100 });
             }
+            Event::KeyDown { keycode: Some(Keycode::F5), .. } => {
+                sim_ui_state.input_queue.push(InputIntent::SaveSnapshot);
+            }
             Event::KeyDown { keycode: Some(Keycode::Period), .. } => {
                 if _paused {
                     sim_ui_state.input_queue.push(InputIntent::AdvanceOneTick);
@@ -8,6 +8,7 @@ pub enum InputIntent {
     SpawnAgentRandom,
     SpawnAgentsRandom { count: usize },
     SelectAgentAt { x: i32, y: i32 },
+    SaveSnapshot,
     // Add more as needed
 }
 
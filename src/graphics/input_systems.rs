@@ -3,7 +3,8 @@ use crate::graphics::sim_state::SimUIState;
 use crate::agent::{spawn_agent, AgentType, event::AgentEventLog};
 use crate::ecs_components::Position;
 use crate::map::Terrain;
-use legion::IntoQuery;
+use crate::sim_rng::{RngStream, SimRng};
+use crate::spatial::SpatialIndex;
 
 /// Processes all input intents from the InputQueue, mutating the ECS world and UI state as needed.
 pub fn process_input_intents(
@@ -32,7 +33,8 @@ pub fn process_input_intents(
             }
             InputIntent::SpawnAgentRandom => {
                 use rand::Rng;
-                let mut rng = rand::thread_rng();
+                let mut sim_rng = sim_ui_state.resources.get_mut::<SimRng>().expect("SimRng missing");
+                let rng = sim_rng.stream(RngStream::Input);
                 let mut x;
                 let mut y;
                 let mut tries = 0;
@@ -51,7 +53,7 @@ pub fn process_input_intents(
                 let mut agent_event_log = sim_ui_state.resources.get_mut::<AgentEventLog>().expect("AgentEventLog missing");
                 if let Some(agent_type) = agent_types.get(0) {
                     let agent_type = agent_type.clone();
-                    spawn_agent(sim_ui_state.world, Position { x, y }, agent_type, render_map, &mut *agent_event_log);
+                    spawn_agent(sim_ui_state.world, Position { x, y }, agent_type, render_map, &mut *agent_event_log, rng);
                     log::debug!("[DEBUG] Added agent at ({}, {})", x, y);
                 } else {
                     log::debug!("[ERROR] No agent types defined!");
@@ -59,7 +61,8 @@ pub fn process_input_intents(
             }
             InputIntent::SpawnAgentsRandom { count } => {
                 use rand::Rng;
-                let mut rng = rand::thread_rng();
+                let mut sim_rng = sim_ui_state.resources.get_mut::<SimRng>().expect("SimRng missing");
+                let rng = sim_rng.stream(RngStream::Input);
                 let mut spawned = 0;
                 let mut attempts = 0;
                 let max_tries_per_agent = 1000;
@@ -71,43 +74,51 @@ pub fn process_input_intents(
                     if render_map.tiles[y as usize][x as usize] == Terrain::Grass || render_map.tiles[y as usize][x as usize] == Terrain::Forest {
                         let type_idx = rng.gen_range(0..num_types);
                         let agent_type = agent_types[type_idx].clone();
-                        spawn_agent(sim_ui_state.world, Position { x, y }, agent_type, render_map, &mut *agent_event_log);
+                        spawn_agent(sim_ui_state.world, Position { x, y }, agent_type, render_map, &mut *agent_event_log, rng);
                         spawned += 1;
                     }
                     attempts += 1;
                 }
                 log::debug!("[DEBUG] Spawned {} agents ({} attempts)", spawned, attempts);
             }
+            InputIntent::SaveSnapshot => {
+                crate::snapshot::save_checkpoint(sim_ui_state.world, sim_ui_state.resources, "gui", sim_ui_state.tick.max(0) as usize);
+            }
             InputIntent::SelectAgentAt { x, y } => {
                 use sdl2::rect::Rect;
                 let mouse_x = x;
                 let mouse_y = y;
                 let mut found_agent = None;
                 let mut topmost_y = -1.0_f32;
-                for (entity, (pos,)) in <(legion::Entity, (&Position,) )>::query().iter(sim_ui_state.world) {
-                    let rect = Rect::new(
-                        ((pos.x - sim_ui_state.camera.x) * cell_size as f32) as i32,
-                        ((pos.y - sim_ui_state.camera.y) * cell_size as f32) as i32,
-                        cell_size as u32,
-                        cell_size as u32,
-                    );
-                    if mouse_x >= rect.x && mouse_x < rect.x + rect.width() as i32 &&
-                        mouse_y >= rect.y && mouse_y < rect.y + rect.height() as i32 {
-                        if pos.y > topmost_y {
-                            found_agent = Some(*entity);
-                            topmost_y = pos.y;
+                // Anything drawn under the cursor lies within 1.5 tiles of it.
+                let click_x = x as f32 / cell_size + sim_ui_state.camera.x;
+                let click_y = y as f32 / cell_size + sim_ui_state.camera.y;
+                if let Some(index) = sim_ui_state.resources.get::<SpatialIndex>() {
+                    for entry in index.agents.within(click_x, click_y, 1.5) {
+                        let rect = Rect::new(
+                            ((entry.x - sim_ui_state.camera.x) * cell_size as f32) as i32,
+                            ((entry.y - sim_ui_state.camera.y) * cell_size as f32) as i32,
+                            cell_size as u32,
+                            cell_size as u32,
+                        );
+                        if mouse_x >= rect.x && mouse_x < rect.x + rect.width() as i32 &&
+                            mouse_y >= rect.y && mouse_y < rect.y + rect.height() as i32 {
+                            if entry.y > topmost_y {
+                                found_agent = Some(entry.entity);
+                                topmost_y = entry.y;
+                            }
                         }
                     }
-                }
-                if found_agent.is_none() {
-                    let map_x = (x as f32 / cell_size as f32 + sim_ui_state.camera.x).floor();
-                    let map_y = (y as f32 / cell_size as f32 + sim_ui_state.camera.y).floor();
-                    for (entity, (pos,)) in <(legion::Entity, (&Position,) )>::query().iter(sim_ui_state.world) {
-                        let food_cell_x = pos.x.floor();
-                        let food_cell_y = pos.y.floor();
-                        if (food_cell_x - map_x).abs() < 0.5 && (food_cell_y - map_y).abs() < 0.5 {
-                            found_agent = Some(*entity);
-                            break;
+                    if found_agent.is_none() {
+                        let map_x = click_x.floor();
+                        let map_y = click_y.floor();
+                        for entry in index.food.within(click_x, click_y, 1.5) {
+                            let food_cell_x = entry.x.floor();
+                            let food_cell_y = entry.y.floor();
+                            if (food_cell_x - map_x).abs() < 0.5 && (food_cell_y - map_y).abs() < 0.5 {
+                                found_agent = Some(entry.entity);
+                                break;
+                            }
                         }
                     }
                 }
@@ -20,6 +20,7 @@ use legion::*;
 // pub use sim_render::run_sim_render;
 // pub use crate::graphics::sim_loop::main_sim_loop;
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_with_graphics_profile(
     _map_width: i32,
     _map_height: i32,
@@ -29,6 +30,8 @@ pub fn run_with_graphics_profile(
     profile_csv: &str,
     log_config: &LogConfig,
     event_log: std::sync::Arc<std::sync::Mutex<crate::event_log::EventLog>>,
+    seed: Option<u64>,
+    events_out: Option<&str>,
 ) {
     let mut world = World::default();
     let mut resources = Resources::default();
@@ -44,5 +47,7 @@ pub fn run_with_graphics_profile(
         profile_csv,
         &mut world,
         &mut resources,
+        seed,
+        events_out,
     );
 }
@@ -7,9 +7,10 @@ use sdl2::video::Window;
 use sdl2::rect::Rect;
 use sdl2::pixels::Color;
 
-/// Agent rendering function
+/// Agent rendering function; food and stockpiles have their own.
 pub fn agent_render_system(world: &World, canvas: &mut Canvas<Window>, camera_x: f32, camera_y: f32, cell_size: f32) {
-    let mut query = <(&Position, Option<&AgentType>)>::query();
+    let mut query = <(&Position, Option<&AgentType>)>::query()
+        .filter(!legion::component::<crate::food::Food>() & !legion::component::<crate::agent::inventory::Stockpile>());
     for (pos, agent_type_opt) in query.iter(world) {
         let rect = Rect::new(
             ((pos.x - camera_x) * cell_size) as i32,
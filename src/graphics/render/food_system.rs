@@ -9,18 +9,18 @@ use sdl2::video::Window;
 /// Food rendering function
 pub fn food_render_system(world: &World, canvas: &mut Canvas<Window>, camera_x: f32, camera_y: f32, cell_size: f32, selected_entity: Option<legion::Entity>) {
     let mut query = <(legion::Entity, &Position, &Food)>::query();
-    for (entity, pos, _food) in query.iter(world) {
+    for (entity, pos, food) in query.iter(world) {
         let rect = Rect::new(
             ((pos.x - camera_x) * cell_size) as i32,
             ((pos.y - camera_y) * cell_size) as i32,
             cell_size as u32,
             cell_size as u32,
         );
-        // Fill with green
-        canvas.set_draw_color(Color::RGB(0, 220, 0));
+        // Fill with the kind's colour, with a darker border of the same hue
+        let (r, g, b) = food.kind.color();
+        canvas.set_draw_color(Color::RGB(r, g, b));
         let _ = canvas.fill_rect(rect);
-        // Draw yellow border
-        canvas.set_draw_color(Color::RGB(255, 220, 40));
+        canvas.set_draw_color(Color::RGB(r / 2, g / 2, b / 2));
         let _ = canvas.draw_rect(rect);
         // If selected, draw a highlight (e.g., red border)
         if Some(*entity) == selected_entity {
@@ -1,9 +1,10 @@
-// Graphics render module root. Re-exports submodules for agent, food, terrain, overlays, and utils.
+// Graphics render module root. Re-exports submodules for agent, food, structure, terrain, overlays, and utils.
 pub mod terrain;
 pub mod overlays;
 pub mod utils;
 pub mod agent_system;
 pub mod food_system;
+pub mod structure_system;
 pub mod selected_agent_path_system;
 pub mod stats_system;
 pub mod event_log_system;
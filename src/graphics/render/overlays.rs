@@ -173,6 +173,15 @@ pub fn draw_stats_window(
                         &mut y,
                         line_height,
                     );
+                    render_stat_row(
+                        canvas,
+                        font,
+                        &texture_creator,
+                        &format!("Outcomes: {}", cached_stats.interaction_outcomes),
+                        Color::RGB(120, 180, 255),
+                        &mut y,
+                        line_height,
+                    );
                     // Draw line graph for interaction history
                     draw_line_graph(
                         canvas,
@@ -185,6 +194,70 @@ pub fn draw_stats_window(
                     );
                     y += 70;
                 }
+                "demographics" => {
+                    let demographics = &cached_stats.demographics;
+                    let mut rows = vec![
+                        format!("Births: {}", demographics.births),
+                        format!("Deaths: {}", demographics.total_deaths()),
+                    ];
+                    rows.extend(crate::agent::event::DeathCause::ALL.iter().map(|cause| format!("  {}: {}", cause.as_str(), demographics.deaths_of(*cause))));
+                    if let Some(mean_age) = demographics.mean_age_at_death() {
+                        rows.push(format!("Avg age at death: {:.0}", mean_age));
+                    }
+                    for row in rows {
+                        render_stat_row(
+                            canvas,
+                            font,
+                            &texture_creator,
+                            &row,
+                            Color::RGB(220, 180, 255),
+                            &mut y,
+                            line_height,
+                        );
+                    }
+                }
+                "clock" => {
+                    render_stat_row(
+                        canvas,
+                        font,
+                        &texture_creator,
+                        &format!("Time: {}", cached_stats.clock),
+                        Color::RGB(255, 230, 150),
+                        &mut y,
+                        line_height,
+                    );
+                }
+                "disease" => {
+                    let disease = &cached_stats.disease;
+                    let rows = [
+                        format!("Susceptible: {}", disease.susceptible),
+                        format!("Infected: {}", disease.infected),
+                        format!("Recovered: {}", disease.recovered),
+                        format!("Infections: {}, recoveries: {}", disease.infections, disease.recoveries),
+                    ];
+                    for row in rows {
+                        render_stat_row(
+                            canvas,
+                            font,
+                            &texture_creator,
+                            &row,
+                            Color::RGB(180, 255, 160),
+                            &mut y,
+                            line_height,
+                        );
+                    }
+                    // Draw line graph for infected history
+                    draw_line_graph(
+                        canvas,
+                        10,
+                        y,
+                        300,
+                        60,
+                        &disease.infected_history,
+                        Color::RGB(180, 255, 160),
+                    );
+                    y += 70;
+                }
                 "selected_agent" => {
                     if let Some(agent) = selected_agent {
                         if let Ok(entry) = _world.entry_ref(agent) {
@@ -195,6 +268,8 @@ pub fn draw_stats_window(
                             let energy = entry.get_component::<crate::agent::Energy>().ok();
                             let state = entry.get_component::<crate::agent::AgentState>().ok();
                             let interaction = entry.get_component::<crate::agent::InteractionState>().ok();
+                            let age = entry.get_component::<crate::agent::aging::Age>().ok();
+                            let inventory = entry.get_component::<crate::agent::inventory::Inventory>().ok();
 
                             render_stat_row(
                                 canvas,
@@ -221,7 +296,11 @@ pub fn draw_stats_window(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Type: {}", agent_type.name),
+                                    &if agent_type.icon.is_empty() {
+                                        format!("Type: {}", agent_type.name)
+                                    } else {
+                                        format!("Type: {} [{}]", agent_type.name, agent_type.icon)
+                                    },
                                     Color::RGB(200, 200, 255),
                                     &mut y,
                                     line_height,
@@ -239,7 +318,7 @@ pub fn draw_stats_window(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Strength: N/A"),
+                                    &format!("Strength: {:.0}", agent_type.strength),
                                     Color::RGB(180, 220, 255),
                                     &mut y,
                                     line_height,
@@ -248,7 +327,7 @@ pub fn draw_stats_window(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Stamina: N/A"),
+                                    &format!("Stamina: {:.0}", agent_type.stamina),
                                     Color::RGB(180, 220, 255),
                                     &mut y,
                                     line_height,
@@ -257,7 +336,7 @@ pub fn draw_stats_window(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Vision: N/A"),
+                                    &format!("Vision: {:.0}", agent_type.vision),
                                     Color::RGB(180, 220, 255),
                                     &mut y,
                                     line_height,
@@ -266,11 +345,25 @@ pub fn draw_stats_window(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Work Rate: N/A"),
+                                    &format!("Work Rate: {:.0}", agent_type.work_rate),
                                     Color::RGB(180, 220, 255),
                                     &mut y,
                                     line_height,
                                 );
+                                if !agent_type.terrain_effects.is_empty() {
+                                    let effects: Vec<String> = agent_type.terrain_effects.iter()
+                                        .map(|(terrain, effect)| format!("{:?} {}", terrain, effect))
+                                        .collect();
+                                    render_stat_row(
+                                        canvas,
+                                        font,
+                                        &texture_creator,
+                                        &format!("Terrain: {}", effects.join(", ")),
+                                        Color::RGB(180, 220, 255),
+                                        &mut y,
+                                        line_height,
+                                    );
+                                }
                             }
                             if let Some(hunger) = hunger {
                                 render_stat_row(
@@ -283,17 +376,45 @@ pub fn draw_stats_window(
                                     line_height,
                                 );
                             }
+                            if let Some(inventory) = inventory {
+                                render_stat_row(
+                                    canvas,
+                                    font,
+                                    &texture_creator,
+                                    &format!("Carrying: {}", inventory.summary()),
+                                    Color::RGB(255, 220, 100),
+                                    &mut y,
+                                    line_height,
+                                );
+                            }
                             if let Some(energy) = energy {
                                 render_stat_row(
                                     canvas,
                                     font,
                                     &texture_creator,
-                                    &format!("Energy: {:.1}", energy.value),
+                                    &match agent_type {
+                                        Some(agent_type) => format!("Energy: {:.1} / {:.0}", energy.value, agent_type.stamina),
+                                        None => format!("Energy: {:.1}", energy.value),
+                                    },
                                     Color::RGB(255, 255, 200),
                                     &mut y,
                                     line_height,
                                 );
                             }
+                            if let Some(age) = age {
+                                render_stat_row(
+                                    canvas,
+                                    font,
+                                    &texture_creator,
+                                    &match age.lifespan {
+                                        Some(lifespan) => format!("Age: {} / {}", age.ticks, lifespan),
+                                        None => format!("Age: {}", age.ticks),
+                                    },
+                                    Color::RGB(220, 180, 255),
+                                    &mut y,
+                                    line_height,
+                                );
+                            }
                             if let Some(state) = state {
                                 render_stat_row(
                                     canvas,
@@ -0,0 +1,58 @@
+use legion::World;
+use legion::IntoQuery;
+use crate::agent::inventory::Stockpile;
+use crate::ecs_components::Position;
+use crate::structure::{Structure, StructureKind};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::rect::Rect;
+use sdl2::pixels::Color;
+
+fn structure_color(kind: StructureKind) -> Color {
+    match kind {
+        StructureKind::House => Color::RGB(170, 90, 40),
+        StructureKind::Storehouse => Color::RGB(200, 170, 60),
+        StructureKind::Farm => Color::RGB(140, 110, 50),
+        StructureKind::Road => Color::RGB(150, 150, 150),
+    }
+}
+
+/// Structure rendering function: finished structures are filled, unfinished ones outlined.
+pub fn structure_render_system(world: &World, canvas: &mut Canvas<Window>, camera_x: f32, camera_y: f32, cell_size: f32) {
+    let mut query = <&Structure>::query();
+    for structure in query.iter(world) {
+        canvas.set_draw_color(structure_color(structure.kind));
+        for (x, y) in structure.tiles() {
+            let rect = Rect::new(
+                ((x as f32 - camera_x) * cell_size) as i32,
+                ((y as f32 - camera_y) * cell_size) as i32,
+                cell_size as u32,
+                cell_size as u32,
+            );
+            if structure.is_built() {
+                let _ = canvas.fill_rect(rect);
+            } else {
+                let _ = canvas.draw_rect(rect);
+            }
+        }
+    }
+}
+
+/// Stockpile rendering function: a brown square, filled once it holds food.
+pub fn stockpile_render_system(world: &World, canvas: &mut Canvas<Window>, camera_x: f32, camera_y: f32, cell_size: f32) {
+    let mut query = <(&Position, &Stockpile)>::query();
+    canvas.set_draw_color(Color::RGB(120, 80, 30));
+    for (pos, stockpile) in query.iter(world) {
+        let rect = Rect::new(
+            ((pos.x - camera_x) * cell_size) as i32,
+            ((pos.y - camera_y) * cell_size) as i32,
+            cell_size as u32,
+            cell_size as u32,
+        );
+        if stockpile.items.is_empty() {
+            let _ = canvas.draw_rect(rect);
+        } else {
+            let _ = canvas.fill_rect(rect);
+        }
+    }
+}
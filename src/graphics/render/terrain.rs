@@ -1,11 +1,13 @@
 // Terrain rendering logic will be moved here from sim_render.rs
 
+use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use crate::graphics::terrain::terrain_color;
 
-pub fn draw_terrain(canvas: &mut Canvas<Window>, render_map: &crate::map::Map, camera_x: f32, camera_y: f32, cell_size: f32) {
+/// Draws the map, darkened and tinted blue by night (`daylight` from the `WorldClock`, 1 by day).
+pub fn draw_terrain(canvas: &mut Canvas<Window>, render_map: &crate::map::Map, camera_x: f32, camera_y: f32, cell_size: f32, daylight: f32) {
     // Fill the entire window with black before drawing terrain
     // let (win_w, win_h) = canvas.window().size();
     canvas.set_draw_color(sdl2::pixels::Color::RGB(0, 0, 0));
@@ -18,8 +20,15 @@ pub fn draw_terrain(canvas: &mut Canvas<Window>, render_map: &crate::map::Map, c
                 cell_size as u32,
                 cell_size as u32,
             );
-            canvas.set_draw_color(terrain_color(&render_map.tiles[y][x]));
+            canvas.set_draw_color(tint(terrain_color(&render_map.tiles[y][x]), daylight));
             canvas.fill_rect(rect).unwrap();
         }
     }
 }
+
+/// Blends `color` toward a dark night blue as `daylight` falls from 1 to 0.
+fn tint(color: Color, daylight: f32) -> Color {
+    const NIGHT: (f32, f32, f32) = (0.3, 0.35, 0.6);
+    let blend = |c: u8, night: f32| (c as f32 * (night + (1.0 - night) * daylight)) as u8;
+    Color::RGB(blend(color.r, NIGHT.0), blend(color.g, NIGHT.1), blend(color.b, NIGHT.2))
+}
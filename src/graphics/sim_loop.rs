@@ -181,6 +181,7 @@ pub fn main_sim_loop(
             );
             last_stats_update = std::time::Instant::now();
         }
+        let daylight = sim_ui_state.resources.get::<crate::world_clock::WorldClock>().map_or(1.0, |c| c.daylight());
         // Now destructure sim_ui_state for rendering
         let SimUIState {
             world,
@@ -196,7 +197,21 @@ pub fn main_sim_loop(
         log::debug!("[DEBUG] About to render terrain");
         canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.clear();
-        draw_terrain(canvas, render_map, camera.x, camera.y, _cell_size);
+        draw_terrain(canvas, render_map, camera.x, camera.y, _cell_size, daylight);
+        crate::graphics::render::structure_system::structure_render_system(
+            world,
+            canvas,
+            camera.x,
+            camera.y,
+            _cell_size,
+        );
+        crate::graphics::render::structure_system::stockpile_render_system(
+            world,
+            canvas,
+            camera.x,
+            camera.y,
+            _cell_size,
+        );
         // --- ECS food rendering system integration ---
         crate::graphics::render::food_system::food_render_system(
             world,
@@ -13,6 +13,8 @@ use crate::graphics::sim_state::SimUIState;
 use crate::ecs_simulation::{build_simulation_schedule_profiled, build_simulation_schedule_unprofiled};
 use crate::agent::event::AgentEventLog;
 use crate::ecs::resources::insert_standard_resources;
+use crate::sim_rng::{RngStream, SimRng};
+use crate::agent::event_sink::EventSink;
 
 const CELL_SIZE: f32 = 6.0;
 
@@ -29,13 +31,16 @@ pub fn run_sim_render(
     profile_csv: &str,
     world: &mut World,
     resources: &mut Resources,
+    seed: Option<u64>,
+    events_out: Option<&str>,
 ) {
     // --- ECS World Setup ---
-    let map = crate::map::Map::new(_map_width, _map_height);
+    let mut sim_rng = SimRng::from_seed_or_entropy(seed);
+    let map = crate::map::Map::new_with_seed(_map_width, _map_height, sim_rng.stream(RngStream::Map).gen());
     let render_map = map.clone();
-    let mut rng = rand::thread_rng();
     let mut _agent_count = 0;
     let mut _attempts = 0;
+    let rng = sim_rng.stream(RngStream::Spawn);
     if _num_agents > 0 {
         for i in 0.._num_agents {
             let mut x;
@@ -54,14 +59,26 @@ pub fn run_sim_render(
             }
             let agent_type = agent_types[i % agent_types.len()].clone();
             let mut agent_event_log = resources.get_mut::<AgentEventLog>().expect("AgentEventLog missing");
-            spawn_agent(world, crate::ecs_components::Position { x, y }, agent_type, &map, &mut *agent_event_log);
+            spawn_agent(world, crate::ecs_components::Position { x, y }, agent_type, &map, &mut *agent_event_log, rng);
             _agent_count += 1;
             _attempts += tries;
         }
     }
     let agent_count_check = <(Read<crate::ecs_components::Position>,)>::query().iter(world).count();
     log::debug!("[DEBUG] Number of agents spawned: {}", agent_count_check);
-    insert_standard_resources(resources, &map);
+    // Keep the initial Spawned events; insert_standard_resources starts a fresh log.
+    let spawn_events = resources.remove::<AgentEventLog>();
+    insert_standard_resources(resources, &map, sim_rng);
+    if let Some(spawn_events) = spawn_events {
+        resources.insert(spawn_events);
+    }
+    resources.insert(crate::world_clock::WorldClock::load(crate::world_clock::CLOCK_CONFIG_PATH));
+    resources.insert(crate::food::ecology::FoodEcology::new(crate::food::ecology::FoodConfig::load(crate::food::ecology::FOOD_CONFIG_PATH)));
+    if let Some(path) = events_out {
+        let sink = EventSink::create(path, 0).unwrap_or_else(|e| panic!("Failed to create events file {}: {}", path, e));
+        log::info!("[EVENTS] Writing agent events to {}", path);
+        resources.insert(sink);
+    }
 
     // Instead of borrowing LogConfig from resources while resources is mutably borrowed,
     // get LogConfig at the start and pass as a plain reference to downstream functions.
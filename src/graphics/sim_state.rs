@@ -32,6 +32,10 @@ pub struct CachedStats {
     pub agent_interactions: usize,
     pub active_interactions: usize,
     pub active_interactions_history: std::collections::VecDeque<usize>,
+    pub interaction_outcomes: String,
+    pub demographics: crate::ecs_components::Demographics,
+    pub disease: crate::agent::disease::DiseaseStats,
+    pub clock: String,
 }
 
 /// Updates the cached stats in SimUIState by querying the ECS world and resources.
@@ -63,9 +67,14 @@ pub fn update_cached_stats(world: &World, resources: &Resources, cached: &mut Ca
         cached.agent_interactions = stats.agent_interactions;
         cached.active_interactions = stats.active_interactions;
         cached.active_interactions_history = stats.active_interactions_history.clone();
+        cached.interaction_outcomes = stats.outcomes_summary();
     } else {
         cached.agent_interactions = 0;
         cached.active_interactions = 0;
         cached.active_interactions_history.clear();
+        cached.interaction_outcomes.clear();
     }
+    cached.demographics = resources.get::<crate::ecs_components::Demographics>().map(|d| d.clone()).unwrap_or_default();
+    cached.disease = resources.get::<crate::agent::disease::DiseaseStats>().map(|d| d.clone()).unwrap_or_default();
+    cached.clock = resources.get::<crate::world_clock::WorldClock>().map(|c| c.label()).unwrap_or_default();
 }
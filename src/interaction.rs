@@ -1,3 +1,172 @@
-//! Agent/world interaction logic
+//! Agent-agent interactions.
+//!
+//! `entity_interaction_system` pairs up nearby agents that are free (no partner, off
+//! cooldown). The pair stops where it is (see `action_selection_system`) and
+//! `agent_interaction_system` runs the interaction for the first agent's
+//! `duration_ticks`. It then applies an outcome drawn from that agent's `outcomes`
+//! weights, emits `AgentEvent::Interacted`, puts both on cooldown and has each remember
+//! the other in `recent_partners`. A pair that drifts apart, or whose partner dies,
+//! breaks off with no outcome.
 
-// All legacy Agent logic removed. Implement ECS-based interaction as Legion systems in ecs_components.rs or similar.
+use std::collections::BTreeMap;
+use legion::Entity;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentType, Energy, Hunger, InteractionState, RecentInteraction};
+
+/// Ticks an agent remembers a partner for.
+pub const PARTNER_MEMORY_TICKS: u32 = 500;
+/// Most partners an agent remembers at once.
+pub const MAX_RECENT_PARTNERS: usize = 5;
+/// Partners further apart than this (on either axis) break off their interaction.
+pub const BREAK_RANGE: f32 = 3.0;
+/// Energy the loser of a fight loses.
+pub const FIGHT_LOSER_ENERGY: f32 = 15.0;
+/// Energy the winner of a fight loses.
+pub const FIGHT_WINNER_ENERGY: f32 = 5.0;
+
+/// What comes of an interaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionOutcome {
+    /// Nothing beyond the meeting itself.
+    Chat,
+    /// The better-fed partner gives the other a quarter of the hunger gap.
+    ShareFood,
+    /// A partner that has claimed food tells the other where it is (`food_tip`).
+    ShareInformation,
+    /// Both lose energy, the loser more; the stronger agent is likelier to win.
+    /// Agents never fight a partner they remember.
+    Fight,
+}
+
+impl InteractionOutcome {
+    pub const ALL: [InteractionOutcome; 4] = [
+        InteractionOutcome::Chat,
+        InteractionOutcome::ShareFood,
+        InteractionOutcome::ShareInformation,
+        InteractionOutcome::Fight,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InteractionOutcome::Chat => "chat",
+            InteractionOutcome::ShareFood => "share_food",
+            InteractionOutcome::ShareInformation => "share_information",
+            InteractionOutcome::Fight => "fight",
+        }
+    }
+}
+
+/// Per-type interaction parameters (the YAML `interaction` block).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct InteractionConfig {
+    #[serde(default = "default_duration_ticks")]
+    pub duration_ticks: u32,
+    /// Ticks after an interaction before the agent can start another.
+    #[serde(default = "default_cooldown_ticks")]
+    pub cooldown_ticks: u32,
+    /// Relative weights of each outcome; missing outcomes never happen.
+    #[serde(default = "default_outcomes")]
+    pub outcomes: BTreeMap<InteractionOutcome, f32>,
+}
+
+fn default_duration_ticks() -> u32 { 3 }
+fn default_cooldown_ticks() -> u32 { 20 }
+fn default_outcomes() -> BTreeMap<InteractionOutcome, f32> {
+    [(InteractionOutcome::Chat, 1.0), (InteractionOutcome::ShareFood, 1.0), (InteractionOutcome::ShareInformation, 1.0)].into_iter().collect()
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            duration_ticks: default_duration_ticks(),
+            cooldown_ticks: default_cooldown_ticks(),
+            outcomes: default_outcomes(),
+        }
+    }
+}
+
+impl InteractionConfig {
+    /// Draws an outcome by weight; `Chat` if no weight is positive.
+    pub fn choose_outcome<R: Rng>(&self, rng: &mut R) -> InteractionOutcome {
+        let total: f32 = self.outcomes.values().filter(|w| **w > 0.0).sum();
+        if total <= 0.0 {
+            return InteractionOutcome::Chat;
+        }
+        let mut roll = rng.gen::<f32>() * total;
+        let mut chosen = InteractionOutcome::Chat;
+        for (outcome, weight) in self.outcomes.iter().filter(|(_, w)| **w > 0.0) {
+            chosen = *outcome;
+            if roll < *weight {
+                break;
+            }
+            roll -= weight;
+        }
+        chosen
+    }
+}
+
+impl InteractionState {
+    /// Free to start a new interaction.
+    pub fn is_available(&self) -> bool {
+        self.target.is_none() && self.cooldown == 0
+    }
+
+    /// Starts an interaction with `partner` lasting `ticks`.
+    pub fn begin(&mut self, partner: Entity, ticks: u32) {
+        self.target = Some(partner);
+        self.ticks = ticks;
+    }
+
+    /// Ends the current interaction (if any) and starts the cooldown.
+    pub fn end(&mut self, cooldown: u32) {
+        self.target = None;
+        self.ticks = 0;
+        self.cooldown = cooldown;
+    }
+
+    pub fn has_met(&self, partner: Entity) -> bool {
+        self.recent_partners.iter().any(|r| r.partner == Some(partner))
+    }
+
+    /// Records `partner` as the most recent one, forgetting the oldest past `MAX_RECENT_PARTNERS`.
+    pub fn remember(&mut self, partner: Entity, outcome: InteractionOutcome) {
+        self.last_partner = Some(partner);
+        self.recent_partners.retain(|r| r.partner != Some(partner));
+        self.recent_partners.push_back(RecentInteraction { partner: Some(partner), ticks_since: 0, outcome: Some(outcome) });
+        while self.recent_partners.len() > MAX_RECENT_PARTNERS {
+            self.recent_partners.pop_front();
+        }
+    }
+
+    /// One tick passes: cooldown and partner memory age.
+    pub fn tick(&mut self) {
+        self.cooldown = self.cooldown.saturating_sub(1);
+        for recent in self.recent_partners.iter_mut() {
+            recent.ticks_since += 1;
+        }
+        self.recent_partners.retain(|r| r.ticks_since <= PARTNER_MEMORY_TICKS);
+    }
+}
+
+/// `ShareFood`: moves a quarter of the hunger gap from the better-fed partner to the
+/// other, never filling the receiver past its type's `hunger_cap`. Returns the amount given.
+pub fn share_food(a: (&mut Hunger, &AgentType), b: (&mut Hunger, &AgentType)) -> f32 {
+    let ((donor, _), (receiver, receiver_type)) = if a.0.value >= b.0.value { (a, b) } else { (b, a) };
+    let amount = ((donor.value - receiver.value) / 4.0).min(receiver_type.hunger_cap - receiver.value).max(0.0);
+    donor.value -= amount;
+    receiver.value += amount;
+    amount
+}
+
+/// `Fight`: `a` wins with probability `a.strength / (a.strength + b.strength)`, using
+/// each type's `strength`. Returns whether `a` won.
+pub fn fight<R: Rng>(a: (&mut Energy, &AgentType), b: (&mut Energy, &AgentType), rng: &mut R) -> bool {
+    let total = a.1.strength + b.1.strength;
+    let a_wins = if total > 0.0 { rng.gen::<f32>() * total < a.1.strength } else { rng.gen_bool(0.5) };
+    let (winner, loser) = if a_wins { (a.0, b.0) } else { (b.0, a.0) };
+    winner.value -= FIGHT_WINNER_ENERGY;
+    loser.value -= FIGHT_LOSER_ENERGY;
+    a_wins
+}
@@ -17,6 +17,14 @@ pub mod ecs_simulation;
 pub mod ecs;
 pub mod config;
 pub mod sim_summary;
+pub mod sim_rng;
+pub mod snapshot;
+pub mod util;
+pub mod evolution;
+pub mod spatial;
+pub mod social;
+pub mod structure;
+pub mod world_clock;
 
 // If you want to restrict the public API, you can `pub use` only what you want to expose here.
 // For now, this setup allows both main.rs and integration tests to access all needed modules.
@@ -15,7 +15,15 @@ pub mod render_ascii;
 
 pub mod terrain;
 pub mod sim_summary;
+pub mod sim_rng;
+pub mod snapshot;
 pub mod event_log;
+pub mod evolution;
+pub mod spatial;
+pub mod interaction;
+pub mod social;
+pub mod structure;
+pub mod world_clock;
 
 use clap::Parser;
 use chrono;
@@ -65,6 +73,33 @@ pub struct Args {
     /// Enable quiet logs
     #[arg(long)]
     log_quiet: bool,
+    /// Master RNG seed for reproducible runs (overrides the profile's `seed:`)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Headless: write a snapshot to checkpoints/ every N ticks
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+    /// Headless: resume the run from a snapshot file
+    #[arg(long)]
+    resume: Option<String>,
+    /// Write every agent event to this file (.csv for CSV, otherwise JSON Lines)
+    #[arg(long)]
+    events_out: Option<String>,
+    /// Headless: write per-tick trait histograms (per agent type) to this CSV file
+    #[arg(long)]
+    traits_out: Option<String>,
+    /// Headless: write the final social graph to this file (.graphml for GraphML, otherwise an edge-list CSV)
+    #[arg(long)]
+    social_out: Option<String>,
+    /// Headless: write per-tick susceptible/infected/recovered counts to this CSV file
+    #[arg(long)]
+    disease_out: Option<String>,
+    /// Evolve MLP brains for N generations (headless; uses --map-size, --agents, --ticks per generation)
+    #[arg(long)]
+    evolve: Option<usize>,
+    /// Output directory for --evolve (fitness.csv and best_genNNN.yaml)
+    #[arg(long, default_value = "evolution")]
+    evolve_out: String,
 }
 
 fn parse_log_level(level: &str) -> log::LevelFilter {
@@ -105,7 +140,8 @@ fn setup_logging(log_level: log::LevelFilter, event_log: Option<Arc<Mutex<event_
 fn main() {
     let args = Args::parse();
     let log_level = parse_log_level(&args.log_level);
-    let event_log = if args.headless {
+    let headless = args.headless || args.evolve.is_some();
+    let event_log = if headless {
         None
     } else {
         let event_log = Arc::new(Mutex::new(event_log::EventLog::new(200)));
@@ -117,15 +153,31 @@ fn main() {
         quiet: args.log_quiet,
         stats: args.log_stats,
     };
-    if args.headless {
+    if headless {
         log::info!("Running in headless mode");
-        if args.scale {
+        if let Some(generations) = args.evolve {
+            let config = evolution::EvolutionConfig {
+                generations,
+                map_size: args.map_size,
+                population: args.agents,
+                ticks: args.ticks,
+                out_dir: args.evolve_out.clone().into(),
+            };
+            if let Err(e) = evolution::run_evolution(&config, &agent_types, args.seed) {
+                log::error!("[EVOLVE] Failed to write results to {}: {}", args.evolve_out, e);
+                std::process::exit(1);
+            }
+        } else if args.scale {
             // TODO: Move run_scaling_benchmarks to ecs module
-            ecs::schedule::run_scaling_benchmarks(&agent_types);
+            ecs::schedule::run_scaling_benchmarks(&agent_types, args.seed);
         } else if args.benchmark_profiles {
-            ecs::schedule::run_benchmark_profiles_from_yaml("config/sim_profiles.yaml", &agent_types, args.profile_systems, &args.profile_csv);
+            ecs::schedule::run_benchmark_profiles_from_yaml("config/sim_profiles.yaml", &agent_types, args.profile_systems, &args.profile_csv, args.seed);
         } else {
-            ecs::schedule::run_profiles_from_yaml("config/sim_profiles.yaml", &agent_types, args.profile_systems, &args.profile_csv);
+            let checkpoint = snapshot::CheckpointConfig {
+                every: args.checkpoint_every,
+                resume: args.resume.clone(),
+            };
+            ecs::schedule::run_profiles_from_yaml("config/sim_profiles.yaml", &agent_types, args.profile_systems, &args.profile_csv, args.seed, &checkpoint, args.events_out.as_deref(), args.traits_out.as_deref(), args.social_out.as_deref(), args.disease_out.as_deref());
         }
     } else {
         log::info!("Running with graphics");
@@ -138,6 +190,8 @@ fn main() {
             &args.profile_csv,
             &log_config,
             event_log.expect("Event log should exist in GUI mode"),
+            args.seed,
+            args.events_out.as_deref(),
         );
     }
 }
@@ -1,9 +1,11 @@
 //! Map/grid logic
 
+use crate::structure::StructureLayer;
 use crate::terrain::{generator, types::TerrainType};
 use rand::Rng;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Terrain {
     Grass, // normal
     Forest,
@@ -12,6 +14,8 @@ pub enum Terrain {
 }
 
 impl Terrain {
+    pub const ALL: [Terrain; 4] = [Terrain::Grass, Terrain::Forest, Terrain::Water, Terrain::Mountain];
+
     pub fn to_char(&self) -> char {
         match self {
             Terrain::Grass => '.',
@@ -21,12 +25,14 @@ impl Terrain {
         }
     }
 
+    /// Base cost of walking onto a tile. Mountains are only crossable by agent types whose
+    /// `movement_profile` lists them (see `pathfinding::tile_cost`).
     pub fn movement_cost(&self) -> Option<f32> {
         match self {
             Terrain::Grass => Some(1.0),
             Terrain::Forest => Some(2.0),
-            Terrain::Mountain => None, // impassable
-            Terrain::Water => None,    // impassable (could be Some(3.0) for special agents)
+            Terrain::Mountain => Some(3.0),
+            Terrain::Water => None,    // impassable unless swimming
         }
     }
 }
@@ -50,17 +56,24 @@ pub enum Food {
     Present,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Map {
     pub width: i32,
     pub height: i32,
     pub tiles: Vec<Vec<Terrain>>,
+    /// Structures by tile (see `crate::structure`); snapshots rebuild it from the entities.
+    #[serde(skip)]
+    pub structures: StructureLayer,
 }
 
 impl Map {
     pub fn new(width: i32, height: i32) -> Self {
         let mut rng = rand::thread_rng();
-        let seed = rng.gen();
+        Self::new_with_seed(width, height, rng.gen())
+    }
+
+    /// Generates a map from a fixed terrain noise seed (same seed, same map).
+    pub fn new_with_seed(width: i32, height: i32, seed: u32) -> Self {
         let terrain_grid = generator::generate_terrain(
             width as usize,
             height as usize,
@@ -74,22 +87,32 @@ impl Map {
             .into_iter()
             .map(|row| row.into_iter().map(Terrain::from).collect())
             .collect();
-        Self { width, height, tiles }
+        Self::from_tiles(width, height, tiles)
     }
 
-    /// Render the map as ASCII (no agents)
+    /// A map of the given terrain (`tiles[y][x]`) with no structures.
+    pub fn from_tiles(width: i32, height: i32, tiles: Vec<Vec<Terrain>>) -> Self {
+        Self { width, height, tiles, structures: StructureLayer::default() }
+    }
+
+    /// Render the map as ASCII (no agents), with structures over the terrain
     pub fn render_ascii(&self) -> String {
         let mut ascii = String::new();
         for y in 0..self.height as usize {
             for x in 0..self.width as usize {
-                ascii.push(self.tiles[y][x].to_char());
+                match self.structures.get(x as i32, y as i32) {
+                    Some(tile) => ascii.push(tile.kind.to_char(tile.built)),
+                    None => ascii.push(self.tiles[y][x].to_char()),
+                }
             }
             ascii.push('\n');
         }
         ascii
     }
 
-    /// Checks if a tile at (x, y) is passable for an agent, taking into account the agent's state.
+    /// Checks if a tile at (x, y) is passable by terrain alone, taking into account the agent's state.
+    /// Agent movement profiles are ignored; for whether a given agent type can step onto a tile,
+    /// use `navigation::pathfinding::tile_cost`, which lets climbers cross mountains.
     ///
     /// - Water tiles are only passable if the agent is in the `Swimming` state.
     /// - Mountain tiles are never passable.
@@ -107,7 +130,7 @@ impl Map {
     /// ```
     /// use community_sim::map::{Map, Terrain};
     /// use community_sim::agent::components::AgentState;
-    /// let map = Map { width: 1, height: 1, tiles: vec![vec![Terrain::Water]] };
+    /// let map = Map::from_tiles(1, 1, vec![vec![Terrain::Water]]);
     /// assert_eq!(map.is_passable(0, 0, Some(&AgentState::Swimming)), true);
     /// assert_eq!(map.is_passable(0, 0, Some(&AgentState::Idle)), false);
     /// ```
@@ -128,9 +151,33 @@ impl Map {
         }
     }
 
+    /// Terrain of the tile containing world position (`x`, `y`), clamped to the map edges.
+    pub fn terrain_at(&self, x: f32, y: f32) -> Terrain {
+        let tx = (x as i32).clamp(0, self.width - 1) as usize;
+        let ty = (y as i32).clamp(0, self.height - 1) as usize;
+        self.tiles[ty][tx]
+    }
+
     /// Find the nearest water tile to the given (x, y) position using BFS.
     /// Returns Some((wx, wy, distance)) if a water tile is found, else None.
     pub fn find_nearest_water(&self, x: i32, y: i32) -> Option<(i32, i32, i32)> {
+        self.find_nearest_water_within(x, y, i32::MAX)
+    }
+
+    /// `find_nearest_water`, searching at most `max_dist` steps away.
+    pub fn find_nearest_water_within(&self, x: i32, y: i32, max_dist: i32) -> Option<(i32, i32, i32)> {
+        self.find_nearest_tile(x, y, max_dist, |terrain| terrain == Terrain::Water)
+    }
+
+    /// BFS (4-neighbour steps, over any terrain) for the nearest tile whose terrain
+    /// passes `wanted`, searching at most `max_dist` steps from (x, y).
+    /// Returns Some((tx, ty, distance)) if one is found, else None.
+    pub fn find_nearest_tile(&self, x: i32, y: i32, max_dist: i32, wanted: impl Fn(Terrain) -> bool) -> Option<(i32, i32, i32)> {
+        self.find_nearest_tile_where(x, y, max_dist, |tx, ty| wanted(self.tiles[ty as usize][tx as usize]))
+    }
+
+    /// `find_nearest_tile`, with `wanted` given the tile's coordinates instead of its terrain.
+    pub fn find_nearest_tile_where(&self, x: i32, y: i32, max_dist: i32, wanted: impl Fn(i32, i32) -> bool) -> Option<(i32, i32, i32)> {
         use std::collections::{VecDeque, HashSet};
         let mut queue = VecDeque::new();
         let mut visited = HashSet::new();
@@ -139,9 +186,12 @@ impl Map {
         let directions = [(-1,0),(1,0),(0,-1),(0,1)];
         while let Some((cx, cy, dist)) = queue.pop_front() {
             if cx >= 0 && cy >= 0 && cx < self.width && cy < self.height {
-                if let Terrain::Water = self.tiles[cy as usize][cx as usize] {
+                if wanted(cx, cy) {
                     return Some((cx, cy, dist));
                 }
+                if dist >= max_dist {
+                    continue;
+                }
                 for (dx, dy) in &directions {
                     let nx = cx + dx;
                     let ny = cy + dy;
@@ -165,15 +215,15 @@ mod tests {
 
     #[test]
     fn test_water_passable_only_when_swimming() {
-        let map = Map {
-            width: 3,
-            height: 3,
-            tiles: vec![
+        let map = Map::from_tiles(
+            3,
+            3,
+            vec![
                 vec![Terrain::Grass, Terrain::Water, Terrain::Grass],
                 vec![Terrain::Grass, Terrain::Water, Terrain::Grass],
                 vec![Terrain::Grass, Terrain::Water, Terrain::Grass],
             ],
-        };
+        );
         // Water tile at (1, 1)
         // Passable for Swimming
         assert_eq!(map.is_passable(1, 1, Some(&AgentState::Swimming)), true);
@@ -189,11 +239,7 @@ mod tests {
         assert_eq!(map.is_passable(0, 0, Some(&AgentState::Idle)), true);
         assert_eq!(map.is_passable(0, 0, Some(&AgentState::Swimming)), true);
         // Mountain never passable
-        let map2 = Map {
-            width: 1,
-            height: 1,
-            tiles: vec![vec![Terrain::Mountain]],
-        };
+        let map2 = Map::from_tiles(1, 1, vec![vec![Terrain::Mountain]]);
         assert_eq!(map2.is_passable(0, 0, Some(&AgentState::Swimming)), false);
     }
 }
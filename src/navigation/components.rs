@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
+use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Target {
     pub x: f32,
     pub y: f32,
@@ -9,7 +10,7 @@ pub struct Target {
     pub ticks_to_reach: Option<u32>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Path {
     pub waypoints: VecDeque<(f32, f32)>,
 }
@@ -1,15 +1,23 @@
 use crate::agent::{AgentType, MovementEffect, AgentState};
+use crate::agent::energy::SWIM_TERRAIN_COST;
 use crate::map::{Map, Terrain};
-use crate::terrain::types::TerrainType;
+use crate::structure::ROAD_COST_FACTOR;
 use std::collections::{BinaryHeap, HashMap};
 use std::cmp::Ordering;
 
-fn terrain_to_terrain_type(terrain: Terrain) -> TerrainType {
-    match terrain {
-        Terrain::Grass => TerrainType::Grass,
-        Terrain::Forest => TerrainType::Forest,
-        Terrain::Water => TerrainType::Water,
-        Terrain::Mountain => TerrainType::Mountain,
+/// Cost for `agent_type` to step onto `terrain`: the terrain's base cost times the type's
+/// per-terrain modifier, or `None` if it can't go there. Water needs `AgentState::Swimming`;
+/// mountains need an explicit `movement_profile` entry.
+pub fn tile_cost(terrain: Terrain, agent_type: &AgentType, agent_state: Option<&AgentState>) -> Option<f32> {
+    let base = match terrain {
+        Terrain::Water if agent_state == Some(&AgentState::Swimming) => SWIM_TERRAIN_COST,
+        Terrain::Mountain if !agent_type.terrain_effects.contains_key(&Terrain::Mountain) => return None,
+        other => other.movement_cost()?,
+    };
+    match agent_type.terrain_effect(terrain) {
+        MovementEffect::None => Some(base),
+        MovementEffect::Slowed(factor) => Some(base * factor),
+        MovementEffect::Blocked => None,
     }
 }
 
@@ -54,7 +62,11 @@ pub fn a_star_path(
     let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
     let mut g_score: HashMap<(i32, i32), f32> = HashMap::new();
     let mut f_score: HashMap<(i32, i32), f32> = HashMap::new();
-    let h = |x: i32, y: i32| ((x - goal.0).abs() + (y - goal.1).abs()) as f32;
+    // Each remaining tile costs at least the cheapest terrain on a road, so the heuristic
+    // never overestimates and the path found is the cheapest one.
+    let cheapest = Terrain::ALL.iter().filter_map(|t| tile_cost(*t, agent_type, Some(agent_state))).fold(f32::INFINITY, f32::min);
+    let min_step = if cheapest.is_finite() { cheapest * ROAD_COST_FACTOR } else { 0.0 };
+    let h = |x: i32, y: i32| ((x - goal.0).abs() + (y - goal.1).abs()) as f32 * min_step;
     g_score.insert(start, 0.0);
     f_score.insert(start, h(start.0, start.1));
     open.push(Node { x: start.0, y: start.1, est_total: h(start.0, start.1) });
@@ -80,21 +92,12 @@ pub fn a_star_path(
             if nx < 0 || ny < 0 || nx >= map.width || ny >= map.height {
                 continue;
             }
-            // Use is_passable with agent_state
-            if !map.is_passable(nx, ny, Some(agent_state)) {
-                continue;
-            }
             let terrain = map.tiles[ny as usize][nx as usize];
-            let _terrain_type = terrain_to_terrain_type(terrain);
-            let effect = agent_type.movement_profile.effect;
-            let (passable, move_cost) = match effect {
-                MovementEffect::Slowed(mult) => (true, mult),
-                MovementEffect::Blocked => (false, 0.0),
-                MovementEffect::None => (true, 1.0),
-            };
-            if !passable {
+            let Some(move_cost) = tile_cost(terrain, agent_type, Some(agent_state)) else {
                 continue;
-            }
+            };
+            // Roads make a tile cheaper than its terrain.
+            let move_cost = move_cost * map.structures.path_cost_factor(nx, ny);
             let tentative_g = g_score.get(&(x, y)).unwrap_or(&f32::INFINITY) + move_cost;
             if tentative_g < *g_score.get(&(nx, ny)).unwrap_or(&f32::INFINITY) {
                 came_from.insert((nx, ny), (x, y));
@@ -1,5 +1,6 @@
 use crate::map::Map;
 use crate::agent::AgentType;
+use crate::navigation::pathfinding::tile_cost;
 use rand::Rng;
 
 /// Returns a random passable target within a given radius of (cx, cy).
@@ -26,11 +27,7 @@ fn random_passable_target_within_radius<R: Rng>(
             continue;
         }
         let terrain = map.tiles[ny as usize][nx as usize];
-        let is_scout = agent_type.name == "scout";
-        let passable = match terrain {
-            crate::map::Terrain::Mountain => is_scout, // only scouts cross mountains
-            _ => terrain.movement_cost().is_some(),
-        };
+        let passable = tile_cost(terrain, agent_type, None).is_some();
         if passable {
             return (
                 nx as f32 + rng.gen_range(0.0..1.0),
@@ -57,11 +54,7 @@ pub fn random_passable_target<R: Rng>(map: &Map, agent_type: &AgentType, rng: &m
             let x = rng.gen_range(0..map.width) as i32;
             let y = rng.gen_range(0..map.height) as i32;
             let terrain = map.tiles[y as usize][x as usize];
-            let is_scout = agent_type.name == "scout";
-            let passable = match terrain {
-                crate::map::Terrain::Mountain => is_scout,
-                _ => terrain.movement_cost().is_some(),
-            };
+            let passable = tile_cost(terrain, agent_type, None).is_some();
             if passable { return (x as f32 + rng.gen_range(0.0..1.0), y as f32 + rng.gen_range(0.0..1.0)); }
             tries += 1;
             if tries > 1000 { return (x as f32, y as f32); }
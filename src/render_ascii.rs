@@ -3,7 +3,7 @@ use legion::World;
 use legion::IntoQuery;
 use crate::map::Map;
 
-/// Renders the simulation state (terrain, agents, food) as ASCII.
+/// Renders the simulation state (terrain, structures, food, stockpiles, agents) as ASCII.
 pub fn render_simulation_ascii(world: &World, map: &Map) -> String {
     // Build a 2D buffer of chars
     let mut buffer = vec![vec![' '; map.width as usize]; map.height as usize];
@@ -13,14 +13,32 @@ pub fn render_simulation_ascii(world: &World, map: &Map) -> String {
             buffer[y][x] = map.tiles[y][x].to_char();
         }
     }
-    // Overlay food and agents (entities with Position)
-    let mut query = <&crate::ecs_components::Position>::query();
-    for pos in query.iter(world) {
+    // Overlay structures, then food and agents on top of them
+    for structure in <&crate::structure::Structure>::query().iter(world) {
+        for (x, y) in structure.tiles() {
+            if x >= 0 && y >= 0 && x < map.width && y < map.height {
+                buffer[y as usize][x as usize] = structure.kind.to_char(structure.is_built());
+            }
+        }
+    }
+    // Overlay food (one character per kind) and stockpiles ('P'), then agents on top
+    let mut put = |pos: &crate::ecs_components::Position, ch: char| {
         let x = pos.x.round() as i32;
         let y = pos.y.round() as i32;
         if x >= 0 && y >= 0 && (x as usize) < map.width as usize && (y as usize) < map.height as usize {
-            buffer[y as usize][x as usize] = 'A';
+            buffer[y as usize][x as usize] = ch;
         }
+    };
+    for (pos, food) in <(&crate::ecs_components::Position, &crate::food::Food)>::query().iter(world) {
+        put(pos, food.kind.to_char());
+    }
+    for (pos, _) in <(&crate::ecs_components::Position, &crate::agent::inventory::Stockpile)>::query().iter(world) {
+        put(pos, 'P');
+    }
+    let mut query = <&crate::ecs_components::Position>::query()
+        .filter(!legion::component::<crate::food::Food>() & !legion::component::<crate::agent::inventory::Stockpile>());
+    for pos in query.iter(world) {
+        put(pos, 'A');
     }
     // Convert buffer to String
     let mut ascii = String::new();
@@ -0,0 +1,87 @@
+//! Seeded, deterministic randomness for the simulation.
+//!
+//! A single master seed drives every random decision in a run. Each consumer
+//! (map generation, spawning, food, action selection, ...) draws from its own
+//! ChaCha sub-stream, so adding draws in one system never shifts the numbers
+//! another system sees. Same seed + same schedule = same run.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+/// Independent random sub-streams, one per consumer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngStream {
+    Map,
+    Spawn,
+    FoodSpawn,
+    ActionSelection,
+    Interaction,
+    Swimming,
+    Input,
+    Reproduction,
+    InteractionOutcome,
+    Work,
+    Disease,
+}
+
+impl RngStream {
+    pub const ALL: [RngStream; 11] = [
+        RngStream::Map,
+        RngStream::Spawn,
+        RngStream::FoodSpawn,
+        RngStream::ActionSelection,
+        RngStream::Interaction,
+        RngStream::Swimming,
+        RngStream::Input,
+        RngStream::Reproduction,
+        RngStream::InteractionOutcome,
+        RngStream::Work,
+        RngStream::Disease,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// ECS resource holding the master seed and one generator per `RngStream`.
+/// Serializable, so snapshots resume every stream exactly where it stopped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimRng {
+    seed: u64,
+    streams: Vec<ChaCha8Rng>,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = RngStream::ALL.iter().map(|s| Self::new_stream(seed, s.index())).collect();
+        Self { seed, streams }
+    }
+
+    fn new_stream(seed: u64, index: usize) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(index as u64);
+        rng
+    }
+
+    /// Uses the given seed, or draws a fresh one from entropy when `None`.
+    /// The chosen seed is logged so any run can be reproduced.
+    pub fn from_seed_or_entropy(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+        log::info!("[SEED] Simulation seed: {}", seed);
+        Self::new(seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        // Snapshots taken before a stream existed don't have it; start it fresh.
+        while self.streams.len() <= stream.index() {
+            self.streams.push(Self::new_stream(self.seed, self.streams.len()));
+        }
+        &mut self.streams[stream.index()]
+    }
+}
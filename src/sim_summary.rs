@@ -1,11 +1,12 @@
 use crate::agent::AgentType;
 use crate::map::Map;
 use crate::ecs_components::InteractionStats;
+use crate::sim_rng::SimRng;
 use super::render_ascii;
 use legion::World;
 use legion::IntoQuery;
 use legion::Resources;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::io::Write;
 
 /// Writes a simulation summary and ASCII snapshot to the given file path.
@@ -16,7 +17,7 @@ pub fn write_simulation_summary_and_ascii(
     tick: usize,
     output_path: &str,
 ) {
-    let mut agent_type_counts: HashMap<String, usize> = HashMap::new();
+    let mut agent_type_counts: BTreeMap<String, usize> = BTreeMap::new();
     let mut agent_query = <(&AgentType,)>::query();
     for (agent_type,) in agent_query.iter(world) {
         *agent_type_counts.entry(agent_type.name.clone()).or_insert(0) += 1;
@@ -27,6 +28,9 @@ pub fn write_simulation_summary_and_ascii(
     let avg_interactions_per_tick = if tick > 0 { total_interactions as f64 / tick as f64 } else { 0.0 };
     let mut summary = String::new();
     summary.push_str(&format!("# Simulation Summary\n"));
+    if let Some(sim_rng) = resources.get::<SimRng>() {
+        summary.push_str(&format!("Seed: {}\n", sim_rng.seed()));
+    }
     summary.push_str(&format!("Total interactions: {}\n", total_interactions));
     summary.push_str(&format!("Average interactions per tick: {:.2}\n", avg_interactions_per_tick));
     summary.push_str("Agent counts at end:\n");
@@ -1,113 +1,33 @@
 //! Main simulation loop and logic
 
-use crate::agent::{AgentType, spawn_agent, event::AgentEventLog};
-use crate::map::{Map, Terrain};
+use crate::agent::AgentType;
+use crate::map::Map;
 use crate::graphics::run_with_graphics_profile;
-use crate::ecs_components::{Position, InteractionStats, FoodPositions, FoodStats};
-use crate::food::{PendingFoodSpawns, Food};
+use crate::ecs::schedule::spawn_initial_world;
+use crate::ecs_components::InteractionStats;
 use crate::ecs_simulation::{simulation_tick, build_simulation_schedule_profiled, SystemProfile};
 use crate::log_config::LogConfig;
 use crate::event_log::EventLog;
+use crate::world_clock::{WorldClock, CLOCK_CONFIG_PATH};
 // use serde::Deserialize;
 use std::io::Write;
 use std::fs::File;
-use legion::IntoQuery;
-use rand::Rng;
 use legion::{World, Resources};
 use log;
-use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-fn run_simulation(map_width: i32, map_height: i32, num_agents: usize, ticks: usize, label: &str, agent_types: &[AgentType], profile_systems: bool, profile_csv: &str) -> (f64, f64, f64) {
+/// Runs `ticks` ticks of a fresh world in the same setting as a headless run, for the
+/// scaling benchmarks. With `profile_systems`, per-system timings go to `profile_csv`.
+#[allow(clippy::too_many_arguments)]
+pub fn run_simulation(map_width: i32, map_height: i32, num_agents: usize, ticks: usize, label: &str, agent_types: &[AgentType], profile_systems: bool, profile_csv: &str, seed: Option<u64>) -> (f64, f64, f64) {
     log::info!("[TEST] Entered run_simulation");
     log::info!("\n=== Running {}: map {}x{}, {} agents, {} ticks ===", label, map_width, map_height, num_agents, ticks);
-    // --- ECS World Setup (MATCH graphics mode) ---
+    // --- ECS World Setup (MATCH headless mode) ---
     let mut world = World::default();
-    let map = Map::new(map_width, map_height);
-    let mut rng = rand::thread_rng();
-    // Convert agent_types from agent.rs::AgentType to ecs_components::AgentType directly
-    let ecs_agent_types: Vec<AgentType> = agent_types.iter().map(|a| AgentType {
-        name: a.name.clone(),
-        color: a.color,
-        movement_profile: a.movement_profile,
-        decision_engine: a.decision_engine.clone(),
-        hunger_rate: a.hunger_rate,
-        hunger_threshold: a.hunger_threshold,
-    }).collect();
-    let mut agent_count = 0;
-    let mut attempts = 0;
-    let mut agent_event_log = AgentEventLog::default();
-    if num_agents > 0 {
-        for i in 0..num_agents {
-            // Find a random passable tile
-            let mut x;
-            let mut y;
-            let mut tries = 0;
-            loop {
-                x = rng.gen_range(0..map_width) as f32;
-                y = rng.gen_range(0..map_height) as f32;
-                if map.tiles[y as usize][x as usize] == Terrain::Grass || map.tiles[y as usize][x as usize] == Terrain::Forest {
-                    break;
-                }
-                tries += 1;
-                if tries > 1000 {
-                    panic!("Could not find passable tile for agent after 1000 tries");
-                }
-            }
-            let agent_type = ecs_agent_types[i % ecs_agent_types.len()].clone();
-            spawn_agent(&mut world, Position { x, y }, agent_type, &map, &mut agent_event_log);
-            agent_count += 1;
-            attempts += tries;
-        }
-    }
-    // --- Spawn initial food entities (1 per 10 agents, minimum 1 if agents exist) ---
-    let food_count = if agent_count > 0 { std::cmp::max(1, agent_count / 10) } else { 0 };
-    for _ in 0..food_count {
-        let mut tries = 0;
-        let (mut x, mut y);
-        loop {
-            x = rng.gen_range(0..map_width) as f32;
-            y = rng.gen_range(0..map_height) as f32;
-            if map.tiles[y as usize][x as usize] == Terrain::Grass || map.tiles[y as usize][x as usize] == Terrain::Forest {
-                break;
-            }
-            tries += 1;
-            if tries > 1000 {
-                panic!("Could not find passable tile for food after 1000 tries");
-            }
-        }
-        world.push((Position { x, y }, Food { nutrition: rng.gen_range(5.0..=10.0) }));
-    }
-    log::debug!("[DEBUG] Total spawn attempts: {} (avg {:.2} per agent)", attempts, attempts as f32 / agent_count as f32);
-    let total_entities = world.len();
-    log::debug!("[DEBUG] Total entities in world after spawning: {}", total_entities);
-    std::io::stdout().flush().unwrap();
-    log::debug!("[DEBUG] Spawned {} agents", agent_count);
-    // --- DEBUG: Print all entities with Position and their component type names before tick loop ---
-    log::debug!("[DEBUG] Entities with Position and their component types before tick loop:");
-    let mut query = <(
-        legion::Entity,
-        &Position,
-        Option<&AgentType>,
-        Option<&Food>
-    )>::query();
-    for (entity, _pos, agent_type, food) in query.iter(&world) {
-        let mut comps = vec!["Position"];
-        if agent_type.is_some() { comps.push("AgentType"); }
-        if food.is_some() { comps.push("Food"); }
-        log::debug!("  Entity {:?}: [{}]", entity, comps.join(", "));
-    }
-    // --- Main simulation loop ---
     let mut resources = Resources::default();
-    resources.insert(map.clone());
-    resources.insert(PendingFoodSpawns(VecDeque::new()));
-    resources.insert(FoodPositions(Vec::new()));
-    resources.insert(FoodStats::default());
-    resources.insert(InteractionStats::default());
-    resources.insert(Arc::new(Mutex::new(EventLog::new(200))));
-    resources.insert(LogConfig::default()); // Insert LogConfig resource
-    resources.insert(AgentEventLog::default()); // Ensure AgentEventLog is always present
-    // Insert other resources as needed for ECS systems
+    spawn_initial_world(&mut world, &mut resources, map_width, map_height, num_agents, agent_types, seed);
+    resources.insert(WorldClock::load(CLOCK_CONFIG_PATH));
+    let map = resources.get::<Map>().expect("Map missing").clone();
     if profile_systems {
         let mut csv_file = File::create(profile_csv).expect("Failed to create csv file");
         writeln!(csv_file, "tick,agent_movement,entity_interaction,agent_death,food_spawn_collect,food_spawn_apply").unwrap();
@@ -249,6 +169,7 @@ pub fn run_profiles_from_yaml(path: &str, agent_types: &[AgentType], profile_sys
 */
 // --- END: Commented out after ECS refactor ---
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_profile_from_yaml(
     path: &str,
     profile_name: &str,
@@ -257,6 +178,8 @@ pub fn run_profile_from_yaml(
     profile_csv: &str,
     log_config: &LogConfig,
     event_log: Arc<Mutex<EventLog>>,
+    seed: Option<u64>,
+    events_out: Option<&str>,
 ) {
     log::info!("[TEST] Entered run_profile_from_yaml");
     let profiles = crate::ecs::schedule::load_profiles_from_yaml(path);
@@ -287,10 +210,12 @@ pub fn run_profile_from_yaml(
         profile_csv,
         &log_config,
         event_log,
+        seed.or(profile.seed),
+        events_out,
     );
 }
 
-pub fn run_scaling_benchmarks(agent_types: &[AgentType]) {
+pub fn run_scaling_benchmarks(agent_types: &[AgentType], seed: Option<u64>) {
     let configs = [
         (20, 20, 10, 10, "base"),        // Smallest, 10 ticks
         (200, 200, 100, 100, "10x"),     // Medium, 100 ticks
@@ -299,7 +224,7 @@ pub fn run_scaling_benchmarks(agent_types: &[AgentType]) {
     ];
     log::info!("\n===== Scaling Benchmarks =====");
     for &(map_width, map_height, num_agents, ticks, label) in &configs {
-        let (total, move_time, interact_time) = run_simulation(map_width, map_height, num_agents, ticks, label, agent_types, false, "scaling_benchmark.csv");
+        let (total, move_time, interact_time) = run_simulation(map_width, map_height, num_agents, ticks, label, agent_types, false, "scaling_benchmark.csv", seed);
         log::info!("{}: total {:.3}s, move {:.3}s, interact {:.3}s", label, total, move_time, interact_time);
     }
 }
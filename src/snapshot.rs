@@ -0,0 +1,371 @@
+//! World snapshots: checkpoint a running simulation to disk and resume it later.
+//!
+//! A snapshot holds every agent, food and structure entity plus the standard resources
+//! (`Map`, `FoodStats`, `InteractionStats`, `Demographics`, `SocialGraph`, `Fields`, `WorkStats`,
+//! `DiseaseStats`, pending food spawns, `SimRng`, `StableIds`), stockpiles and the tick counter. Entities are written in query order with snapshot-local
+//! ids; `InteractionState`, pregnancy, relationship, food claim and structure owner references are remapped to those ids, since legion
+//! `Entity` handles are only meaningful inside the process that created them.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path as FsPath;
+use legion::{Entity, EntityStore, IntoQuery, Resources, World};
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentState, AgentType, Energy, Hunger, InteractionState, MovementHistory, RecentInteraction};
+use crate::agent::components::SwimmingProfile;
+use crate::agent::reproduction::{Fertility, Pregnancy};
+use crate::agent::genome::Genome;
+use crate::agent::aging::Age;
+use crate::agent::disease::{DiseaseStats, Health};
+use crate::agent::inventory::{Inventory, Stockpile};
+use crate::agent::work::{Fields, Work, WorkStats};
+use crate::agent::event_sink::StableIds;
+use crate::ecs::resources::insert_standard_resources;
+use crate::ecs_components::{Demographics, FoodStats, InteractionStats, Position};
+use crate::food::{Food, FoodClaims, PendingFoodSpawns};
+use crate::food::ecology::{FoodAge, FoodEcology};
+use crate::interaction::InteractionOutcome;
+use crate::map::Map;
+use crate::navigation::{Path, Target};
+use crate::sim_rng::SimRng;
+use crate::social::{Relationship, SocialGraph};
+use crate::structure::{Structure, StructureSnapshot};
+use crate::world_clock::BaseTraits;
+
+/// Bumped whenever the on-disk layout changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Profile name the run was started with.
+    pub label: String,
+    /// Number of ticks already simulated.
+    pub tick: usize,
+    pub map: Map,
+    pub food_stats: FoodStats,
+    pub interaction_stats: InteractionStats,
+    #[serde(default)]
+    pub demographics: Demographics,
+    /// (agent id, agent id, relationship), in `SocialGraph` order.
+    #[serde(default)]
+    pub relationships: Vec<(usize, usize, Relationship)>,
+    /// (tile, growth) for every field.
+    #[serde(default)]
+    pub fields: Vec<((i32, i32), f32)>,
+    #[serde(default)]
+    pub work_stats: WorkStats,
+    #[serde(default)]
+    pub disease_stats: DiseaseStats,
+    /// (tile, biomass) for every depleted tile.
+    #[serde(default)]
+    pub food_biomass: Vec<((i32, i32), f32)>,
+    pub pending_food_spawns: Vec<(f32, f32)>,
+    pub rng: SimRng,
+    /// Next event-stream id to hand out (see `StableIds`).
+    #[serde(default)]
+    pub next_stable_id: u64,
+    pub agents: Vec<AgentSnapshot>,
+    pub food: Vec<FoodSnapshot>,
+    #[serde(default)]
+    pub structures: Vec<StructureSnapshot>,
+    #[serde(default)]
+    pub stockpiles: Vec<(Position, Stockpile)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgentSnapshot {
+    pub id: usize,
+    #[serde(default)]
+    pub stable_id: Option<u64>,
+    pub position: Position,
+    pub agent_type: AgentType,
+    pub hunger: Option<Hunger>,
+    pub energy: Option<Energy>,
+    pub interaction: Option<InteractionSnapshot>,
+    pub target: Option<Target>,
+    pub path: Option<Path>,
+    pub state: Option<AgentState>,
+    pub movement_history: Option<MovementHistory>,
+    pub swimming: Option<SwimmingProfile>,
+    #[serde(default)]
+    pub fertility: Option<FertilitySnapshot>,
+    #[serde(default)]
+    pub genome: Option<Genome>,
+    #[serde(default)]
+    pub age: Option<Age>,
+    #[serde(default)]
+    pub work: Option<Work>,
+    #[serde(default)]
+    pub health: Option<Health>,
+    #[serde(default)]
+    pub inventory: Option<Inventory>,
+    #[serde(default)]
+    pub base_traits: Option<BaseTraits>,
+}
+
+/// `Fertility` with the father replaced by his snapshot id.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FertilitySnapshot {
+    pub cooldown: u32,
+    /// (father id, ticks remaining)
+    pub pregnancy: Option<(Option<usize>, u32)>,
+    /// The unborn child's genome.
+    #[serde(default)]
+    pub child_genome: Option<Genome>,
+}
+
+/// `InteractionState` with entity references replaced by snapshot ids.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InteractionSnapshot {
+    pub target: Option<usize>,
+    pub ticks: u32,
+    pub last_partner: Option<usize>,
+    pub cooldown: u32,
+    /// (partner id, ticks since the interaction)
+    pub recent_partners: Vec<(Option<usize>, u32)>,
+    /// Outcome of each of `recent_partners`.
+    #[serde(default)]
+    pub recent_outcomes: Vec<Option<InteractionOutcome>>,
+    #[serde(default)]
+    pub food_tip: Option<(f32, f32)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoodSnapshot {
+    #[serde(default)]
+    pub stable_id: Option<u64>,
+    pub position: Position,
+    pub food: Food,
+    #[serde(default)]
+    pub age: Option<FoodAge>,
+    /// Id of the agent that claimed it (see `FoodClaims`).
+    #[serde(default)]
+    pub claimed_by: Option<usize>,
+}
+
+impl Snapshot {
+    /// Captures the world and standard resources after `tick` ticks have run.
+    pub fn capture(world: &World, resources: &Resources, label: &str, tick: usize) -> Self {
+        let mut agent_query = <(Entity, &Position, &AgentType)>::query();
+        let ids: HashMap<Entity, usize> = agent_query
+            .iter(world)
+            .enumerate()
+            .map(|(id, (entity, _, _))| (*entity, id))
+            .collect();
+        let map_ref = |e: Option<Entity>| e.and_then(|e| ids.get(&e).copied());
+        let stable_ids = resources.get::<StableIds>().map(|s| s.clone()).unwrap_or_default();
+        let mut agents = Vec::with_capacity(ids.len());
+        for (entity, position, agent_type) in agent_query.iter(world) {
+            let entry = world.entry_ref(*entity).expect("agent entity vanished during capture");
+            let interaction = entry.get_component::<InteractionState>().ok().map(|s| InteractionSnapshot {
+                target: map_ref(s.target),
+                ticks: s.ticks,
+                last_partner: map_ref(s.last_partner),
+                cooldown: s.cooldown,
+                recent_partners: s.recent_partners.iter().map(|r| (map_ref(r.partner), r.ticks_since)).collect(),
+                recent_outcomes: s.recent_partners.iter().map(|r| r.outcome).collect(),
+                food_tip: s.food_tip,
+            });
+            agents.push(AgentSnapshot {
+                id: ids[entity],
+                stable_id: stable_ids.get(*entity),
+                position: *position,
+                agent_type: agent_type.clone(),
+                hunger: entry.get_component::<Hunger>().ok().cloned(),
+                energy: entry.get_component::<Energy>().ok().cloned(),
+                interaction,
+                target: entry.get_component::<Target>().ok().cloned(),
+                path: entry.get_component::<Path>().ok().cloned(),
+                state: entry.get_component::<AgentState>().ok().copied(),
+                movement_history: entry.get_component::<MovementHistory>().ok().cloned(),
+                swimming: entry.get_component::<SwimmingProfile>().ok().cloned(),
+                fertility: entry.get_component::<Fertility>().ok().map(|f| FertilitySnapshot {
+                    cooldown: f.cooldown,
+                    pregnancy: f.pregnancy.as_ref().map(|p| (map_ref(p.father), p.ticks_remaining)),
+                    child_genome: f.pregnancy.as_ref().and_then(|p| p.genome.clone()),
+                }),
+                genome: entry.get_component::<Genome>().ok().cloned(),
+                age: entry.get_component::<Age>().ok().cloned(),
+                work: entry.get_component::<Work>().ok().cloned(),
+                health: entry.get_component::<Health>().ok().copied(),
+                inventory: entry.get_component::<Inventory>().ok().cloned(),
+                base_traits: entry.get_component::<BaseTraits>().ok().copied(),
+            });
+        }
+        let claims = resources.get::<FoodClaims>().map(|c| c.clone()).unwrap_or_default();
+        let food = <(Entity, &Position, &Food, Option<&FoodAge>)>::query()
+            .iter(world)
+            .map(|(entity, position, food, age)| FoodSnapshot {
+                stable_id: stable_ids.get(*entity),
+                position: *position,
+                food: *food,
+                age: age.copied(),
+                claimed_by: map_ref(claims.claimant(*entity)),
+            })
+            .collect();
+        Self {
+            version: SNAPSHOT_VERSION,
+            label: label.to_string(),
+            tick,
+            map: resources.get::<Map>().expect("Map missing").clone(),
+            food_stats: resources.get::<FoodStats>().map(|s| s.clone()).unwrap_or_default(),
+            interaction_stats: resources.get::<InteractionStats>().map(|s| s.clone()).unwrap_or_default(),
+            demographics: resources.get::<Demographics>().map(|d| d.clone()).unwrap_or_default(),
+            relationships: resources.get::<SocialGraph>()
+                .map(|g| g.edges().filter_map(|(a, b, r)| Some((ids.get(a).copied()?, ids.get(b).copied()?, r.clone()))).collect())
+                .unwrap_or_default(),
+            fields: resources.get::<Fields>().map(|f| f.0.iter().map(|(tile, growth)| (*tile, *growth)).collect()).unwrap_or_default(),
+            work_stats: resources.get::<WorkStats>().map(|s| s.clone()).unwrap_or_default(),
+            disease_stats: resources.get::<DiseaseStats>().map(|s| s.clone()).unwrap_or_default(),
+            food_biomass: resources.get::<FoodEcology>().map(|e| e.depleted.iter().map(|(tile, biomass)| (*tile, *biomass)).collect()).unwrap_or_default(),
+            pending_food_spawns: resources.get::<PendingFoodSpawns>().map(|p| p.0.iter().copied().collect()).unwrap_or_default(),
+            rng: resources.get::<SimRng>().expect("SimRng missing").clone(),
+            next_stable_id: stable_ids.next,
+            agents,
+            food,
+            structures: <&Structure>::query().iter(world).map(|s| StructureSnapshot::capture(s, &ids)).collect(),
+            stockpiles: <(&Position, &Stockpile)>::query().iter(world).map(|(pos, stockpile)| (*pos, stockpile.clone())).collect(),
+        }
+    }
+
+    /// Rebuilds `world` and the standard resources from this snapshot.
+    /// Any existing entities in `world` are removed first.
+    pub fn restore(self, world: &mut World, resources: &mut Resources) {
+        world.clear();
+        let mut stable_ids = StableIds { next: self.next_stable_id, ..Default::default() };
+        let mut entities = HashMap::with_capacity(self.agents.len());
+        for agent in &self.agents {
+            let entity = world.push((agent.position, agent.agent_type.clone()));
+            let mut entry = world.entry(entity).unwrap();
+            if let Some(hunger) = &agent.hunger { entry.add_component(hunger.clone()); }
+            if let Some(energy) = &agent.energy { entry.add_component(energy.clone()); }
+            if let Some(target) = &agent.target { entry.add_component(target.clone()); }
+            if let Some(path) = &agent.path { entry.add_component(path.clone()); }
+            if let Some(state) = agent.state { entry.add_component(state); }
+            if let Some(history) = &agent.movement_history { entry.add_component(history.clone()); }
+            if let Some(swimming) = &agent.swimming { entry.add_component(swimming.clone()); }
+            if let Some(genome) = &agent.genome { entry.add_component(genome.clone()); }
+            if let Some(age) = &agent.age { entry.add_component(age.clone()); }
+            if let Some(work) = &agent.work { entry.add_component(work.clone()); }
+            if let Some(health) = agent.health { entry.add_component(health); }
+            if let Some(inventory) = &agent.inventory { entry.add_component(inventory.clone()); }
+            if let Some(base) = agent.base_traits { entry.add_component(base); }
+            if let Some(id) = agent.stable_id { stable_ids.by_entity.insert(entity, id); }
+            entities.insert(agent.id, entity);
+        }
+        // Second pass: interaction state and pregnancies can point at any agent, so all must exist first.
+        let lookup = |id: Option<usize>| id.and_then(|id| entities.get(&id).copied());
+        for agent in &self.agents {
+            if let Some(f) = &agent.fertility {
+                let fertility = Fertility {
+                    cooldown: f.cooldown,
+                    pregnancy: f.pregnancy.map(|(father, ticks_remaining)| Pregnancy { father: lookup(father), ticks_remaining, genome: f.child_genome.clone() }),
+                };
+                world.entry(entities[&agent.id]).unwrap().add_component(fertility);
+            }
+            if let Some(s) = &agent.interaction {
+                let state = InteractionState {
+                    target: lookup(s.target),
+                    ticks: s.ticks,
+                    last_partner: lookup(s.last_partner),
+                    cooldown: s.cooldown,
+                    recent_partners: s.recent_partners.iter().enumerate()
+                        .map(|(i, (partner, ticks_since))| RecentInteraction {
+                            partner: lookup(*partner),
+                            ticks_since: *ticks_since,
+                            outcome: s.recent_outcomes.get(i).copied().flatten(),
+                        })
+                        .collect(),
+                    food_tip: s.food_tip,
+                };
+                world.entry(entities[&agent.id]).unwrap().add_component(state);
+            }
+        }
+        let mut social = SocialGraph::default();
+        for (a, b, relationship) in &self.relationships {
+            if let (Some(a), Some(b)) = (lookup(Some(*a)), lookup(Some(*b))) {
+                social.insert(a, b, relationship.clone());
+            }
+        }
+        let mut claims = FoodClaims::default();
+        for food in &self.food {
+            let entity = world.push((food.position, food.food));
+            if let Some(age) = food.age { world.entry(entity).unwrap().add_component(age); }
+            if let Some(agent) = lookup(food.claimed_by) { claims.claim(entity, agent); }
+            if let Some(id) = food.stable_id { stable_ids.by_entity.insert(entity, id); }
+        }
+        for (position, stockpile) in &self.stockpiles {
+            world.push((*position, stockpile.clone()));
+        }
+        let mut map = self.map;
+        for structure in &self.structures {
+            let structure = structure.restore(lookup);
+            let entity = world.push((structure.clone(),));
+            map.structures.insert(entity, &structure);
+        }
+        insert_standard_resources(resources, &map, self.rng);
+        resources.insert(self.food_stats);
+        resources.insert(self.interaction_stats);
+        resources.insert(self.demographics);
+        resources.insert(social);
+        resources.insert(Fields(self.fields.into_iter().collect()));
+        resources.insert(self.work_stats);
+        resources.insert(self.disease_stats);
+        resources.insert(FoodEcology { depleted: self.food_biomass.into_iter().collect(), ..Default::default() });
+        resources.insert(stable_ids);
+        resources.insert(claims);
+        resources.insert(PendingFoodSpawns(VecDeque::from(self.pending_food_spawns)));
+        log::info!("[SNAPSHOT] Restored '{}' at tick {} ({} agents, {} food)", self.label, self.tick, self.agents.len(), self.food.len());
+    }
+
+    pub fn save<P: AsRef<FsPath>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self).map_err(io::Error::from)
+    }
+
+    pub fn load<P: AsRef<FsPath>>(path: P) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let snapshot: Self = serde_json::from_reader(reader).map_err(io::Error::from)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("snapshot version {} is not supported (expected {})", snapshot.version, SNAPSHOT_VERSION),
+            ));
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Checkpoint/resume options for headless runs.
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointConfig {
+    /// Write a snapshot every N ticks (`--checkpoint-every`).
+    pub every: Option<usize>,
+    /// Snapshot file to resume from (`--resume`).
+    pub resume: Option<String>,
+}
+
+impl CheckpointConfig {
+    /// Saves a checkpoint when `tick` lands on the checkpoint interval.
+    pub fn maybe_save(&self, world: &World, resources: &Resources, label: &str, tick: usize) {
+        let Some(every) = self.every.filter(|n| *n > 0) else { return };
+        if tick.is_multiple_of(every) {
+            save_checkpoint(world, resources, label, tick);
+        }
+    }
+}
+
+/// Writes `checkpoints/<label>_tick<N>.json`, logging (not panicking) on failure.
+pub fn save_checkpoint(world: &World, resources: &Resources, label: &str, tick: usize) {
+    if let Err(e) = std::fs::create_dir_all("checkpoints") {
+        log::error!("[SNAPSHOT] Could not create checkpoints directory: {}", e);
+        return;
+    }
+    let path = format!("checkpoints/{}_tick{:06}.json", label, tick);
+    match Snapshot::capture(world, resources, label, tick).save(&path) {
+        Ok(()) => log::info!("[SNAPSHOT] Checkpoint written to {}", path),
+        Err(e) => log::error!("[SNAPSHOT] Failed to write checkpoint {}: {}", path, e),
+    }
+}
@@ -0,0 +1,295 @@
+//! The social graph: one `Relationship` per pair of agents that have interacted.
+//!
+//! `social_graph_system` reads the entries `agent_interaction_system` has just added to
+//! each agent's `recent_partners` and updates the pair's relationship: familiarity grows
+//! with every completed interaction and affinity moves by the outcome (see
+//! `affinity_change`). Every tick both decay toward zero, and relationships whose
+//! familiarity falls below `FORGET_BELOW` are dropped, as are those of agents that die.
+//!
+//! Relationships steer partner choice: instead of wandering at random,
+//! `action_selection_system` sends an agent toward the friend (`FRIEND_AFFINITY`) it likes
+//! most within vision, so friends meet again more often than strangers do.
+//!
+//! `--social-out` writes the final graph as GraphML (`.graphml`) or an edge-list CSV,
+//! with agents identified by their event-stream ids (see `StableIds`).
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use legion::{Entity, IntoQuery, SystemBuilder, World};
+use serde::{Serialize, Deserialize};
+use crate::agent::{AgentType, InteractionState};
+use crate::agent::event_sink::StableIds;
+use crate::interaction::InteractionOutcome;
+
+/// Share of the remaining gap to full familiarity each interaction closes.
+pub const FAMILIARITY_GAIN: f32 = 0.2;
+/// Share of familiarity and affinity lost every tick.
+pub const RELATIONSHIP_DECAY: f32 = 0.002;
+/// Relationships less familiar than this are forgotten.
+pub const FORGET_BELOW: f32 = 0.01;
+/// Agents at least this fond of each other seek each other out.
+pub const FRIEND_AFFINITY: f32 = 0.3;
+
+/// How much an interaction's outcome changes the pair's affinity.
+pub fn affinity_change(outcome: InteractionOutcome) -> f32 {
+    match outcome {
+        InteractionOutcome::Chat => 0.1,
+        InteractionOutcome::ShareFood => 0.3,
+        InteractionOutcome::ShareInformation => 0.2,
+        InteractionOutcome::Fight => -0.4,
+    }
+}
+
+/// What two agents make of each other. Symmetric.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Relationship {
+    /// 0..1; how well they know each other.
+    pub familiarity: f32,
+    /// -1..1; how much they like each other.
+    pub affinity: f32,
+    /// Completed interactions between them.
+    pub interactions: u32,
+}
+
+impl Relationship {
+    pub fn record(&mut self, outcome: InteractionOutcome) {
+        self.familiarity += (1.0 - self.familiarity) * FAMILIARITY_GAIN;
+        self.affinity = (self.affinity + affinity_change(outcome)).clamp(-1.0, 1.0);
+        self.interactions += 1;
+    }
+}
+
+/// Degree and clustering of the graph, averaged over `agents` agents (those without
+/// relationships count as degree 0 and clustering 0).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SocialMetrics {
+    pub relationships: usize,
+    pub mean_degree: f64,
+    pub clustering: f64,
+}
+
+/// ECS resource: every relationship, in the order the pairs first met (so iteration,
+/// exports and metrics are deterministic).
+#[derive(Clone, Debug, Default)]
+pub struct SocialGraph {
+    edges: Vec<(Entity, Entity, Relationship)>,
+    /// Edge position by pair, under both orders.
+    index: HashMap<(Entity, Entity), usize>,
+}
+
+impl SocialGraph {
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn get(&self, a: Entity, b: Entity) -> Option<&Relationship> {
+        self.index.get(&(a, b)).map(|&i| &self.edges[i].2)
+    }
+
+    /// `a`'s affinity for `b`; 0 for strangers.
+    pub fn affinity(&self, a: Entity, b: Entity) -> f32 {
+        self.get(a, b).map_or(0.0, |r| r.affinity)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &(Entity, Entity, Relationship)> {
+        self.edges.iter()
+    }
+
+    /// Sets the relationship between `a` and `b` outright (used when restoring snapshots).
+    pub fn insert(&mut self, a: Entity, b: Entity, relationship: Relationship) {
+        match self.index.get(&(a, b)) {
+            Some(&i) => self.edges[i].2 = relationship,
+            None => {
+                self.index.insert((a, b), self.edges.len());
+                self.index.insert((b, a), self.edges.len());
+                self.edges.push((a, b, relationship));
+            }
+        }
+    }
+
+    /// Records a completed interaction between `a` and `b`.
+    pub fn record(&mut self, a: Entity, b: Entity, outcome: InteractionOutcome) {
+        if !self.index.contains_key(&(a, b)) {
+            self.insert(a, b, Relationship::default());
+        }
+        let i = self.index[&(a, b)];
+        self.edges[i].2.record(outcome);
+    }
+
+    /// One tick passes: every relationship fades, and the faintest are forgotten.
+    pub fn decay(&mut self) {
+        for (_, _, r) in self.edges.iter_mut() {
+            r.familiarity *= 1.0 - RELATIONSHIP_DECAY;
+            r.affinity *= 1.0 - RELATIONSHIP_DECAY;
+        }
+        if self.edges.iter().any(|(_, _, r)| r.familiarity < FORGET_BELOW) {
+            self.retain(|_, _, r| r.familiarity >= FORGET_BELOW);
+        }
+    }
+
+    /// Drops every relationship of an agent that no longer exists.
+    pub fn remove_agent(&mut self, agent: Entity) {
+        self.retain(|a, b, _| a != agent && b != agent);
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(Entity, Entity, &Relationship) -> bool) {
+        self.edges.retain(|(a, b, r)| keep(*a, *b, r));
+        self.index.clear();
+        for (i, (a, b, _)) in self.edges.iter().enumerate() {
+            self.index.insert((*a, *b), i);
+            self.index.insert((*b, *a), i);
+        }
+    }
+
+    /// Each agent's neighbours, agents in order of first appearance.
+    fn adjacency(&self) -> Vec<(Entity, Vec<Entity>)> {
+        let mut order: HashMap<Entity, usize> = HashMap::new();
+        let mut adjacency: Vec<(Entity, Vec<Entity>)> = Vec::new();
+        for (a, b, _) in &self.edges {
+            for (agent, other) in [(*a, *b), (*b, *a)] {
+                let i = *order.entry(agent).or_insert_with(|| {
+                    adjacency.push((agent, Vec::new()));
+                    adjacency.len() - 1
+                });
+                adjacency[i].1.push(other);
+            }
+        }
+        adjacency
+    }
+
+    pub fn degree(&self, agent: Entity) -> usize {
+        self.edges.iter().filter(|(a, b, _)| *a == agent || *b == agent).count()
+    }
+
+    /// Local clustering coefficient: the share of pairs of `agent`'s neighbours that
+    /// know each other. 0 with fewer than two neighbours.
+    pub fn clustering(&self, agent: Entity) -> f64 {
+        let neighbours: Vec<Entity> = self.edges.iter()
+            .filter_map(|(a, b, _)| if *a == agent { Some(*b) } else if *b == agent { Some(*a) } else { None })
+            .collect();
+        self.clustering_of(&neighbours)
+    }
+
+    fn clustering_of(&self, neighbours: &[Entity]) -> f64 {
+        let k = neighbours.len();
+        if k < 2 {
+            return 0.0;
+        }
+        let mut links = 0;
+        for (i, a) in neighbours.iter().enumerate() {
+            for b in &neighbours[i + 1..] {
+                if self.index.contains_key(&(*a, *b)) {
+                    links += 1;
+                }
+            }
+        }
+        links as f64 / (k * (k - 1) / 2) as f64
+    }
+
+    /// Graph metrics over a population of `agents` agents.
+    pub fn metrics(&self, agents: usize) -> SocialMetrics {
+        if agents == 0 {
+            return SocialMetrics { relationships: self.len(), ..Default::default() };
+        }
+        let total_clustering: f64 = self.adjacency().iter().map(|(_, neighbours)| self.clustering_of(neighbours)).sum();
+        SocialMetrics {
+            relationships: self.len(),
+            mean_degree: 2.0 * self.len() as f64 / agents as f64,
+            clustering: total_clustering / agents as f64,
+        }
+    }
+
+    /// The agent `agent` likes most (at least `FRIEND_AFFINITY`) among `candidates`.
+    pub fn best_friend(&self, agent: Entity, candidates: impl IntoIterator<Item = Entity>) -> Option<Entity> {
+        let mut best: Option<(Entity, f32)> = None;
+        for other in candidates {
+            let affinity = self.affinity(agent, other);
+            if other != agent && affinity >= FRIEND_AFFINITY && best.is_none_or(|(_, a)| affinity > a) {
+                best = Some((other, affinity));
+            }
+        }
+        best.map(|(friend, _)| friend)
+    }
+
+    /// Edge-list CSV: `source,target,familiarity,affinity,interactions`.
+    pub fn to_csv(&self, ids: &mut StableIds) -> String {
+        let mut csv = String::from("source,target,familiarity,affinity,interactions\n");
+        for (a, b, r) in &self.edges {
+            csv.push_str(&format!("{},{},{:.4},{:.4},{}\n", ids.get_or_assign(*a), ids.get_or_assign(*b), r.familiarity, r.affinity, r.interactions));
+        }
+        csv
+    }
+
+    /// GraphML with an `agent_type` attribute on nodes and the relationship on edges.
+    /// `names` gives each living agent's type name.
+    pub fn to_graphml(&self, ids: &mut StableIds, names: &HashMap<Entity, String>) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        xml.push_str("  <key id=\"agent_type\" for=\"node\" attr.name=\"agent_type\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"familiarity\" for=\"edge\" attr.name=\"familiarity\" attr.type=\"double\"/>\n");
+        xml.push_str("  <key id=\"affinity\" for=\"edge\" attr.name=\"affinity\" attr.type=\"double\"/>\n");
+        xml.push_str("  <key id=\"interactions\" for=\"edge\" attr.name=\"interactions\" attr.type=\"int\"/>\n");
+        xml.push_str("  <graph id=\"social\" edgedefault=\"undirected\">\n");
+        for (agent, _) in self.adjacency() {
+            let id = ids.get_or_assign(agent);
+            match names.get(&agent) {
+                Some(name) => xml.push_str(&format!("    <node id=\"n{}\"><data key=\"agent_type\">{}</data></node>\n", id, escape_xml(name))),
+                None => xml.push_str(&format!("    <node id=\"n{}\"/>\n", id)),
+            }
+        }
+        for (a, b, r) in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"familiarity\">{:.4}</data><data key=\"affinity\">{:.4}</data><data key=\"interactions\">{}</data></edge>\n",
+                ids.get_or_assign(*a), ids.get_or_assign(*b), r.familiarity, r.affinity, r.interactions
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Writes the graph to `path`: GraphML if it ends in `.graphml`, an edge-list CSV otherwise.
+pub fn write_social_graph<P: AsRef<Path>>(path: P, graph: &SocialGraph, world: &World, ids: &mut StableIds) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("graphml")) {
+        let names: HashMap<Entity, String> = <(Entity, &AgentType)>::query().iter(world).map(|(e, t)| (*e, t.name.clone())).collect();
+        graph.to_graphml(ids, &names)
+    } else {
+        graph.to_csv(ids)
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(contents.as_bytes())?;
+    writer.flush()
+}
+
+/// Decays the graph, then records the interactions completed this tick (the fresh entries
+/// in `recent_partners`, seen from the agent that comes first in query order).
+pub fn social_graph_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("SocialGraphSystem")
+        .write_resource::<SocialGraph>()
+        .with_query(<(Entity, &InteractionState)>::query())
+        .build(|_cmd, world, graph, query| {
+            graph.decay();
+            let mut seen = HashSet::new();
+            for (entity, state) in query.iter(world) {
+                seen.insert(*entity);
+                for recent in state.recent_partners.iter().filter(|r| r.ticks_since == 0) {
+                    if let (Some(partner), Some(outcome)) = (recent.partner, recent.outcome) {
+                        if !seen.contains(&partner) {
+                            graph.record(*entity, partner, outcome);
+                        }
+                    }
+                }
+            }
+        })
+}
@@ -0,0 +1,163 @@
+//! Uniform-grid spatial index for neighbour queries.
+//!
+//! `SpatialIndex` is an ECS resource holding one `SpatialGrid` for agents and one for
+//! food, whose entries carry the food's kind. `spatial_index_system` rebuilds both from `Position` each time it runs (a sort
+//! of the entities plus one pass over the cells). Queries only visit the cells that
+//! overlap the search radius, so interaction and vision checks don't scan every entity.
+
+use legion::{component, Entity, IntoQuery, SystemBuilder};
+use crate::agent::AgentType;
+use crate::ecs_components::Position;
+use crate::food::{Food, FoodKind};
+use crate::map::Map;
+
+/// Side of a grid cell in tiles. Most queries (interaction, vision) cover a few cells.
+pub const CELL_SIZE: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridEntry<T = ()> {
+    pub entity: Entity,
+    pub x: f32,
+    pub y: f32,
+    /// What the grid keeps about the entity besides its position.
+    pub data: T,
+}
+
+impl<T> GridEntry<T> {
+    fn dist_sq(&self, x: f32, y: f32) -> f32 {
+        (self.x - x).powi(2) + (self.y - y).powi(2)
+    }
+}
+
+/// Entries bucketed by cell, stored contiguously: the entries of cell `c` are
+/// `entries[cell_start[c]..cell_start[c + 1]]`, in insertion order.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid<T = ()> {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cell_start: Vec<u32>,
+    entries: Vec<GridEntry<T>>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    /// An empty grid covering a `width` x `height` map. Positions outside the map are
+    /// kept in the nearest edge cell.
+    pub fn new(width: i32, height: i32, cell_size: f32) -> Self {
+        let cols = ((width.max(1) as f32 / cell_size).ceil() as usize).max(1);
+        let rows = ((height.max(1) as f32 / cell_size).ceil() as usize).max(1);
+        Self { cell_size, cols, rows, cell_start: vec![0; cols * rows + 1], entries: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn col(&self, x: f32) -> usize {
+        ((x / self.cell_size).floor().max(0.0) as usize).min(self.cols - 1)
+    }
+
+    fn row(&self, y: f32) -> usize {
+        ((y / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1)
+    }
+
+    /// Replaces the contents with `items`.
+    pub fn rebuild(&mut self, items: impl IntoIterator<Item = (Entity, f32, f32, T)>) {
+        let mut items: Vec<(usize, GridEntry<T>)> = items.into_iter()
+            .map(|(entity, x, y, data)| (self.row(y) * self.cols + self.col(x), GridEntry { entity, x, y, data }))
+            .collect();
+        // Stable, so entries keep insertion order within a cell.
+        items.sort_by_key(|(cell, _)| *cell);
+        self.cell_start.iter_mut().for_each(|c| *c = 0);
+        for (cell, _) in &items {
+            self.cell_start[cell + 1] += 1;
+        }
+        for c in 1..self.cell_start.len() {
+            self.cell_start[c] += self.cell_start[c - 1];
+        }
+        self.entries.clear();
+        self.entries.extend(items.into_iter().map(|(_, entry)| entry));
+    }
+
+    /// Calls `f` for every entry within `radius` of (`x`, `y`), cell by cell.
+    pub fn for_each_within(&self, x: f32, y: f32, radius: f32, mut f: impl FnMut(&GridEntry<T>)) {
+        let radius_sq = radius * radius;
+        let (c0, c1) = (self.col(x - radius), self.col(x + radius));
+        let (r0, r1) = (self.row(y - radius), self.row(y + radius));
+        for row in r0..=r1 {
+            let start = self.cell_start[row * self.cols + c0] as usize;
+            let end = self.cell_start[row * self.cols + c1 + 1] as usize;
+            for entry in &self.entries[start..end] {
+                if entry.dist_sq(x, y) <= radius_sq {
+                    f(entry);
+                }
+            }
+        }
+    }
+
+    /// Entries within `radius` of (`x`, `y`).
+    pub fn within(&self, x: f32, y: f32, radius: f32) -> Vec<GridEntry<T>> {
+        let mut found = Vec::new();
+        self.for_each_within(x, y, radius, |e| found.push(*e));
+        found
+    }
+
+    pub fn count_within(&self, x: f32, y: f32, radius: f32) -> usize {
+        let mut count = 0;
+        self.for_each_within(x, y, radius, |_| count += 1);
+        count
+    }
+
+    /// Closest entry within `max_radius` that passes `filter`. Searches outward in
+    /// doubling rings, so nearby hits are cheap.
+    pub fn nearest(&self, x: f32, y: f32, max_radius: f32, mut filter: impl FnMut(&GridEntry<T>) -> bool) -> Option<GridEntry<T>> {
+        let mut radius = self.cell_size.min(max_radius);
+        loop {
+            let mut best: Option<(f32, GridEntry<T>)> = None;
+            self.for_each_within(x, y, radius, |e| {
+                let d = e.dist_sq(x, y);
+                if best.is_none_or(|(bd, _)| d < bd) && filter(e) {
+                    best = Some((d, *e));
+                }
+            });
+            if best.is_some() || radius >= max_radius {
+                return best.map(|(_, e)| e);
+            }
+            radius = (radius * 2.0).min(max_radius);
+        }
+    }
+}
+
+/// ECS resource: where every agent and food item was when the index was last rebuilt.
+#[derive(Clone, Debug)]
+pub struct SpatialIndex {
+    pub agents: SpatialGrid,
+    /// Food items, with their kind.
+    pub food: SpatialGrid<FoodKind>,
+}
+
+impl SpatialIndex {
+    pub fn new(map: &Map) -> Self {
+        Self {
+            agents: SpatialGrid::new(map.width, map.height, CELL_SIZE),
+            food: SpatialGrid::new(map.width, map.height, CELL_SIZE),
+        }
+    }
+}
+
+/// Rebuilds `SpatialIndex` from current positions. Scheduled at the start of the tick
+/// and again before interactions, since agents move in between.
+pub fn spatial_index_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("SpatialIndexSystem")
+        .write_resource::<SpatialIndex>()
+        .with_query(<(Entity, &Position)>::query().filter(component::<AgentType>()))
+        .with_query(<(Entity, &Position, &Food)>::query())
+        .build(|_cmd, world, index, (agent_query, food_query)| {
+            index.agents.rebuild(agent_query.iter(world).map(|(e, p)| (*e, p.x, p.y, ())));
+            index.food.rebuild(food_query.iter(world).map(|(e, p, food)| (*e, p.x, p.y, food.kind)));
+        })
+}
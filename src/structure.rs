@@ -0,0 +1,289 @@
+//! Structures. Builders (see `agent::work`) lay mined stone into `Structure` entities that
+//! cover a rectangle of tiles; a structure takes effect once its `progress` reaches 1:
+//! - Houses shelter tired agents, who walk to one within vision and rest faster there.
+//! - Storehouses take in the harvest of nearby fields and put it out again one item at a time.
+//! - Farms turn their tiles into fields.
+//! - Roads halve the cost of walking over them for pathfinding.
+//!
+//! `Map::structures` records which structure covers each tile, so terrain code can look
+//! them up without a world query.
+
+use std::collections::{BTreeMap, HashMap};
+use legion::{Entity, IntoQuery, SystemBuilder, World};
+use serde::{Serialize, Deserialize};
+use crate::ecs_components::{spawn_food, Position};
+use crate::food::ecology::FoodEcology;
+use crate::map::{Map, Terrain};
+use crate::sim_rng::{RngStream, SimRng};
+use crate::spatial::SpatialIndex;
+
+/// Energy recovered per tick of rest is multiplied by this inside a house.
+pub const HOUSE_REST_BONUS: f32 = 2.0;
+/// Pathfinding cost of a road tile, relative to its terrain.
+pub const ROAD_COST_FACTOR: f32 = 0.5;
+/// Food items a storehouse holds.
+pub const STOREHOUSE_CAPACITY: usize = 20;
+/// Fields at most this many tiles (on each axis) from a storehouse deliver to it.
+pub const STOREHOUSE_RANGE: i32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructureKind {
+    House,
+    Storehouse,
+    Farm,
+    Road,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 4] = [StructureKind::House, StructureKind::Storehouse, StructureKind::Farm, StructureKind::Road];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StructureKind::House => "house",
+            StructureKind::Storehouse => "storehouse",
+            StructureKind::Farm => "farm",
+            StructureKind::Road => "road",
+        }
+    }
+
+    /// Width and height in tiles.
+    pub fn footprint(&self) -> (i32, i32) {
+        match self {
+            StructureKind::House | StructureKind::Storehouse => (2, 2),
+            StructureKind::Farm => (3, 3),
+            StructureKind::Road => (1, 1),
+        }
+    }
+
+    /// Stone needed to finish one.
+    pub fn cost(&self) -> f32 {
+        match self {
+            StructureKind::House => 50.0,
+            StructureKind::Storehouse => 80.0,
+            StructureKind::Farm => 30.0,
+            StructureKind::Road => 10.0,
+        }
+    }
+
+    /// How many of this kind builders put up relative to the others.
+    fn share(&self) -> f32 {
+        match self {
+            StructureKind::House => 4.0,
+            StructureKind::Storehouse => 1.0,
+            StructureKind::Farm => 2.0,
+            StructureKind::Road => 3.0,
+        }
+    }
+
+    /// ASCII glyph: upper case once built, lower case while under construction.
+    pub fn to_char(&self, built: bool) -> char {
+        match (self, built) {
+            (StructureKind::House, true) => 'H',
+            (StructureKind::House, false) => 'h',
+            (StructureKind::Storehouse, true) => 'S',
+            (StructureKind::Storehouse, false) => 's',
+            (StructureKind::Farm, true) => 'F',
+            (StructureKind::Farm, false) => 'f',
+            (StructureKind::Road, true) => '=',
+            (StructureKind::Road, false) => '-',
+        }
+    }
+
+    /// Whether a structure of this kind can stand on `terrain`.
+    pub fn fits_on(&self, terrain: Terrain) -> bool {
+        match self {
+            StructureKind::Farm => terrain == Terrain::Grass,
+            _ => matches!(terrain, Terrain::Grass | Terrain::Forest),
+        }
+    }
+
+    /// Kinds in the order builders should try to start them, given how many of each exist
+    /// already: the one furthest behind its share first.
+    pub fn build_order(counts: &BTreeMap<StructureKind, usize>) -> Vec<StructureKind> {
+        let need = |kind: &StructureKind| counts.get(kind).copied().unwrap_or(0) as f32 / kind.share();
+        let mut kinds = StructureKind::ALL.to_vec();
+        kinds.sort_by(|a, b| need(a).total_cmp(&need(b)));
+        kinds
+    }
+}
+
+/// ECS component: a structure covering `footprint()` tiles from (`x`, `y`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structure {
+    pub kind: StructureKind,
+    /// Top-left tile.
+    pub x: i32,
+    pub y: i32,
+    /// Agent that started it.
+    pub owner: Option<Entity>,
+    /// 0..1; the structure works once this reaches 1.
+    pub progress: f32,
+    /// Storehouses: food items held.
+    pub food: usize,
+}
+
+impl Structure {
+    pub fn new(kind: StructureKind, x: i32, y: i32, owner: Option<Entity>) -> Self {
+        Self { kind, x, y, owner, progress: 0.0, food: 0 }
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.progress >= 1.0
+    }
+
+    /// Every tile the structure covers.
+    pub fn tiles(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (x, y) = (self.x, self.y);
+        let (w, h) = self.kind.footprint();
+        (0..h).flat_map(move |dy| (0..w).map(move |dx| (x + dx, y + dy)))
+    }
+}
+
+/// One tile of `Map::structures`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StructureTile {
+    pub entity: Entity,
+    pub kind: StructureKind,
+    pub built: bool,
+}
+
+/// Which structure covers which tile.
+#[derive(Clone, Debug, Default)]
+pub struct StructureLayer {
+    tiles: BTreeMap<(i32, i32), StructureTile>,
+}
+
+impl StructureLayer {
+    pub fn get(&self, x: i32, y: i32) -> Option<&StructureTile> {
+        self.tiles.get(&(x, y))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Marks the tiles of `structure` (entity `entity`) as covered.
+    pub fn insert(&mut self, entity: Entity, structure: &Structure) {
+        let tile = StructureTile { entity, kind: structure.kind, built: structure.is_built() };
+        for pos in structure.tiles() {
+            self.tiles.insert(pos, tile);
+        }
+    }
+
+    /// Marks the tiles of `structure` as finished.
+    pub fn set_built(&mut self, structure: &Structure) {
+        for pos in structure.tiles() {
+            if let Some(tile) = self.tiles.get_mut(&pos) {
+                tile.built = true;
+            }
+        }
+    }
+
+    /// Whether (`x`, `y`) is inside a finished house.
+    pub fn is_shelter(&self, x: i32, y: i32) -> bool {
+        self.get(x, y).is_some_and(|t| t.built && t.kind == StructureKind::House)
+    }
+
+    /// Multiplier on the pathfinding cost of stepping onto (`x`, `y`).
+    pub fn path_cost_factor(&self, x: i32, y: i32) -> f32 {
+        match self.get(x, y) {
+            Some(t) if t.built && t.kind == StructureKind::Road => ROAD_COST_FACTOR,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Whether a `kind` structure with its top-left corner at (`x`, `y`) fits on the map
+/// without overlapping another one.
+pub fn fits(map: &Map, kind: StructureKind, x: i32, y: i32) -> bool {
+    Structure::new(kind, x, y, None).tiles().all(|(tx, ty)| {
+        tx >= 0 && ty >= 0 && tx < map.width && ty < map.height
+            && kind.fits_on(map.tiles[ty as usize][tx as usize])
+            && map.structures.get(tx, ty).is_none()
+    })
+}
+
+/// Spawns a new structure site for `kind` at (`x`, `y`) and records it on the map.
+/// Goes through the command buffer, so the entity exists from the next flush on.
+pub fn place_structure(cmd: &mut legion::systems::CommandBuffer, map: &mut Map, kind: StructureKind, x: i32, y: i32, owner: Option<Entity>) -> Entity {
+    let structure = Structure::new(kind, x, y, owner);
+    let entity = cmd.push((structure.clone(),));
+    map.structures.insert(entity, &structure);
+    entity
+}
+
+/// Built structures of each kind, and how many are still under construction.
+pub fn structure_counts(world: &World) -> (BTreeMap<StructureKind, usize>, usize) {
+    let mut built = BTreeMap::new();
+    let mut unfinished = 0;
+    for structure in <&Structure>::query().iter(world) {
+        if structure.is_built() {
+            *built.entry(structure.kind).or_insert(0) += 1;
+        } else {
+            unfinished += 1;
+        }
+    }
+    (built, unfinished)
+}
+
+/// "3 built (house 2, storehouse 0, farm 1, road 0), 1 under construction, 4 food stored"
+pub fn structure_summary(world: &World) -> String {
+    let (built, unfinished) = structure_counts(world);
+    let food: usize = <&Structure>::query().iter(world).map(|s| s.food).sum();
+    let kinds: Vec<String> = StructureKind::ALL.iter()
+        .map(|kind| format!("{} {}", kind.as_str(), built.get(kind).copied().unwrap_or(0)))
+        .collect();
+    format!("{} built ({}), {} under construction, {} food stored", built.values().sum::<usize>(), kinds.join(", "), unfinished, food)
+}
+
+/// Storehouses with food put one item out on their corner tile whenever the last one has
+/// been taken.
+pub fn storehouse_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("StorehouseSystem")
+        .read_resource::<SpatialIndex>()
+        .read_resource::<FoodEcology>()
+        .write_resource::<SimRng>()
+        .with_query(<&mut Structure>::query())
+        .build(|cmd, world, (index, ecology, sim_rng), query| {
+            let rng = sim_rng.stream(RngStream::Work);
+            for structure in query.iter_mut(world) {
+                if structure.kind != StructureKind::Storehouse || !structure.is_built() || structure.food == 0 {
+                    continue;
+                }
+                let (x, y) = (structure.x as f32 + 0.5, structure.y as f32 + 0.5);
+                if index.food.within(x, y, 0.5).is_empty() {
+                    structure.food -= 1;
+                    spawn_food(cmd, Position { x, y }, ecology, rng);
+                }
+            }
+        })
+}
+
+/// Serializable form of a `Structure`, with the owner given as a snapshot agent index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StructureSnapshot {
+    pub kind: StructureKind,
+    pub x: i32,
+    pub y: i32,
+    pub owner: Option<usize>,
+    pub progress: f32,
+    pub food: usize,
+}
+
+impl StructureSnapshot {
+    pub fn capture(structure: &Structure, ids: &HashMap<Entity, usize>) -> Self {
+        Self {
+            kind: structure.kind,
+            x: structure.x,
+            y: structure.y,
+            owner: structure.owner.and_then(|e| ids.get(&e).copied()),
+            progress: structure.progress,
+            food: structure.food,
+        }
+    }
+
+    pub fn restore(&self, lookup: impl Fn(Option<usize>) -> Option<Entity>) -> Structure {
+        Structure { kind: self.kind, x: self.x, y: self.y, owner: lookup(self.owner), progress: self.progress, food: self.food }
+    }
+}
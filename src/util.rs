@@ -1,5 +1,16 @@
 use crate::agent::{AgentType, MovementProfile, MovementEffect, DecisionEngineConfig};
 use crate::agent::mlp::MLPConfig;
+use crate::agent::reproduction::ReproductionConfig;
+use crate::agent::genome::GeneticsConfig;
+use crate::agent::aging::LifespanConfig;
+use crate::agent::disease::DiseaseConfig;
+use crate::food::Diet;
+use crate::agent::work::WorkRole;
+use crate::interaction::InteractionConfig;
+use crate::agent::decision::OBSERVATION_SIZE;
+use crate::map::Terrain;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 
@@ -44,6 +55,24 @@ pub fn load_agent_types(path: &str) -> Vec<AgentType> {
             _ => MovementEffect::None,
         };
         let movement_profile = MovementProfile { speed, effect };
+        // Per-terrain rules: `movement_profile: { Mountain: "Slow(2.0)", Water: "Blocked" }`
+        let mut terrain_effects = BTreeMap::new();
+        if let Some(map) = raw["movement_profile"].as_mapping() {
+            for (terrain, effect) in map {
+                let terrain = match serde_yaml::from_value::<Terrain>(terrain.clone()) {
+                    Ok(terrain) => terrain,
+                    Err(_) => {
+                        log::warn!("[CONFIG] {}: unknown terrain {:?} in movement_profile", name, terrain);
+                        continue;
+                    }
+                };
+                match effect.as_str().map(str::parse::<MovementEffect>) {
+                    Some(Ok(effect)) => { terrain_effects.insert(terrain, effect); }
+                    Some(Err(e)) => log::warn!("[CONFIG] {}: {}", name, e),
+                    None => log::warn!("[CONFIG] {}: movement_profile.{:?} must be a string", name, terrain),
+                }
+            }
+        }
         // --- DecisionEngineConfig: parse from YAML ---
         let decision_engine = if let Some(decision_engine_val) = raw.get("decision_engine") {
             if decision_engine_val.is_null() {
@@ -55,9 +84,21 @@ pub fn load_agent_types(path: &str) -> Vec<AgentType> {
                 }
             } else if decision_engine_val.is_mapping() {
                 // Try to parse as MLPConfig (tagged or tagless)
-                serde_yaml::from_value::<MLPConfig>(decision_engine_val.clone())
-                    .map(DecisionEngineConfig::MLP)
-                    .unwrap_or(DecisionEngineConfig::Simple)
+                match serde_yaml::from_value::<MLPConfig>(decision_engine_val.clone())
+                    .map_err(|e| e.to_string())
+                    .and_then(|config| config.validate().map(|_| config))
+                {
+                    Ok(config) => {
+                        if config.input_size != OBSERVATION_SIZE {
+                            log::warn!("[CONFIG] {}: MLP takes {} inputs but observations have {}; agents will use the simple engine", name, config.input_size, OBSERVATION_SIZE);
+                        }
+                        DecisionEngineConfig::MLP(config)
+                    }
+                    Err(e) => {
+                        log::warn!("[CONFIG] {}: invalid MLP decision engine ({}), using Simple", name, e);
+                        DecisionEngineConfig::Simple
+                    }
+                }
             } else {
                 DecisionEngineConfig::Simple
             }
@@ -67,6 +108,77 @@ pub fn load_agent_types(path: &str) -> Vec<AgentType> {
         // --- Parse hunger_rate from YAML ---
         let hunger_rate = raw["hunger_rate"].as_f64().unwrap_or(0.01) as f32;
         let hunger_threshold = raw["hunger_threshold"].as_f64().unwrap_or(50.0) as f32;
+        let digestion_efficiency = raw["digestion_efficiency"].as_f64().unwrap_or(1.0) as f32;
+        let hunger_cap = raw["hunger_cap"].as_f64().unwrap_or(100.0) as f32;
+        let overeating = raw["overeating"].as_bool().unwrap_or(false);
+        let stamina = raw["stamina"].as_f64().unwrap_or(100.0) as f32;
+        let strength = raw["strength"].as_f64().unwrap_or(1.0) as f32;
+        let vision = raw["vision"].as_f64().unwrap_or(5.0) as f32;
+        let work_rate = raw["work_rate"].as_f64().unwrap_or(1.0) as f32;
+        let icon = raw["icon"].as_str().unwrap_or("").to_string();
+        let damping = raw["damping"].as_f64().unwrap_or(1.0) as f32;
+        let move_probability = raw["move_probability"].as_f64().unwrap_or(1.0) as f32;
+        let reproduction = match raw.get("reproduction") {
+            Some(value) if !value.is_null() => match serde_yaml::from_value::<ReproductionConfig>(value.clone()) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    log::warn!("[CONFIG] {}: invalid reproduction block ({}); this type won't reproduce", name, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let genetics = match raw.get("genetics") {
+            Some(value) if !value.is_null() => serde_yaml::from_value::<GeneticsConfig>(value.clone()).unwrap_or_else(|e| {
+                log::warn!("[CONFIG] {}: invalid genetics block ({}); using defaults", name, e);
+                GeneticsConfig::default()
+            }),
+            _ => GeneticsConfig::default(),
+        };
+        let lifespan = match raw.get("lifespan") {
+            Some(value) if !value.is_null() => match serde_yaml::from_value::<LifespanConfig>(value.clone()) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    log::warn!("[CONFIG] {}: invalid lifespan block ({}); this type won't age out", name, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let role = match raw.get("role") {
+            Some(value) if !value.is_null() => match serde_yaml::from_value::<WorkRole>(value.clone()) {
+                Ok(role) => Some(role),
+                Err(e) => {
+                    log::warn!("[CONFIG] {}: invalid role ({}); this type won't work", name, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let interaction = match raw.get("interaction") {
+            Some(value) if !value.is_null() => serde_yaml::from_value::<InteractionConfig>(value.clone()).unwrap_or_else(|e| {
+                log::warn!("[CONFIG] {}: invalid interaction block ({}); using defaults", name, e);
+                InteractionConfig::default()
+            }),
+            _ => InteractionConfig::default(),
+        };
+        let disease = match raw.get("disease") {
+            Some(value) if !value.is_null() => match serde_yaml::from_value::<DiseaseConfig>(value.clone()) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    log::warn!("[CONFIG] {}: invalid disease block ({}); this type won't fall ill", name, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let diet = match raw.get("diet") {
+            Some(value) if !value.is_null() => serde_yaml::from_value::<Diet>(value.clone()).unwrap_or_else(|e| {
+                log::warn!("[CONFIG] {}: invalid diet block ({}); this type will eat anything", name, e);
+                Diet::default()
+            }),
+            _ => Diet::default(),
+        };
         AgentType {
             name,
             color,
@@ -74,6 +186,123 @@ pub fn load_agent_types(path: &str) -> Vec<AgentType> {
             decision_engine,
             hunger_rate,
             hunger_threshold,
+            digestion_efficiency,
+            hunger_cap,
+            overeating,
+            stamina,
+            strength,
+            vision,
+            work_rate,
+            role,
+            icon,
+            damping,
+            move_probability,
+            terrain_effects,
+            reproduction,
+            genetics,
+            lifespan,
+            interaction,
+            disease,
+            diet,
         }
     }).collect()
 }
+
+/// One entry of `config/agent_types.yaml`, in the shape `load_agent_types` reads.
+#[derive(Serialize)]
+struct AgentTypeEntry<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    type_name: &'a str,
+    color: String,
+    move_speed: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    move_effect: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    move_cost: Option<f32>,
+    strength: f32,
+    stamina: f32,
+    vision: f32,
+    work_rate: f32,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    icon: &'a str,
+    damping: f32,
+    move_probability: f32,
+    hunger_rate: f32,
+    hunger_threshold: f32,
+    digestion_efficiency: f32,
+    hunger_cap: f32,
+    overeating: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    movement_profile: BTreeMap<Terrain, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reproduction: Option<&'a ReproductionConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    genetics: Option<&'a GeneticsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lifespan: Option<&'a LifespanConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<WorkRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interaction: Option<&'a InteractionConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disease: Option<&'a DiseaseConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diet: Option<&'a Diet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decision_engine: Option<&'a MLPConfig>,
+}
+
+impl<'a> From<&'a AgentType> for AgentTypeEntry<'a> {
+    fn from(a: &'a AgentType) -> Self {
+        let (move_effect, move_cost) = match a.movement_profile.effect {
+            MovementEffect::None => (None, None),
+            MovementEffect::Blocked => (Some("blocked"), None),
+            MovementEffect::Slowed(cost) => (Some("slowed"), Some(cost)),
+        };
+        Self {
+            name: &a.name,
+            type_name: &a.name,
+            color: format!("#{:02X}{:02X}{:02X}", a.color.0, a.color.1, a.color.2),
+            move_speed: a.movement_profile.speed,
+            move_effect,
+            move_cost,
+            strength: a.strength,
+            stamina: a.stamina,
+            vision: a.vision,
+            work_rate: a.work_rate,
+            icon: &a.icon,
+            damping: a.damping,
+            move_probability: a.move_probability,
+            hunger_rate: a.hunger_rate,
+            hunger_threshold: a.hunger_threshold,
+            digestion_efficiency: a.digestion_efficiency,
+            hunger_cap: a.hunger_cap,
+            overeating: a.overeating,
+            movement_profile: a.terrain_effects.iter().map(|(terrain, effect)| (*terrain, effect.to_string())).collect(),
+            reproduction: a.reproduction.as_ref(),
+            genetics: Some(&a.genetics).filter(|g| **g != GeneticsConfig::default()),
+            lifespan: a.lifespan.as_ref(),
+            role: a.role,
+            interaction: Some(&a.interaction).filter(|i| **i != InteractionConfig::default()),
+            disease: a.disease.as_ref(),
+            diet: Some(&a.diet).filter(|d| !d.is_omnivore()),
+            decision_engine: match &a.decision_engine {
+                DecisionEngineConfig::MLP(config) => Some(config),
+                DecisionEngineConfig::Simple => None,
+            },
+        }
+    }
+}
+
+/// Writes agent types in the `config/agent_types.yaml` format, so the output can be
+/// read back with `load_agent_types` (used by `--evolve` to save evolved brains).
+pub fn agent_types_to_yaml(agent_types: &[AgentType]) -> String {
+    let entries: Vec<AgentTypeEntry> = agent_types.iter().map(AgentTypeEntry::from).collect();
+    serde_yaml::to_string(&entries).expect("agent types serialize")
+}
+
+/// Linear interpolation from `a` (`t` = 0) to `b` (`t` = 1).
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
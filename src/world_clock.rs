@@ -0,0 +1,258 @@
+//! World clock: time of day and season, derived from the tick counter. Runs start at 06:00
+//! on day 1 of spring. At night agents see less far and move more slowly; seasons scale
+//! how much food spawns, and winter makes agents hungrier. `clock_factor_system` applies
+//! the agent factors to every agent's expressed traits.
+//!
+//! The cycle is configured in `config/world_clock.yaml`; without that file the world is
+//! timeless (always midday, no seasons) and nothing changes.
+
+use std::path::Path;
+use legion::{Entity, IntoQuery, SystemBuilder};
+use serde::{Serialize, Deserialize};
+use crate::agent::AgentType;
+use crate::util::lerp;
+
+/// Where headless and GUI runs read the clock configuration from.
+pub const CLOCK_CONFIG_PATH: &str = "config/world_clock.yaml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+}
+
+/// Food spawn multiplier for each season.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalFood {
+    #[serde(default = "default_one")]
+    pub spring: f32,
+    #[serde(default = "default_summer_food")]
+    pub summer: f32,
+    #[serde(default = "default_one")]
+    pub autumn: f32,
+    #[serde(default = "default_winter_food")]
+    pub winter: f32,
+}
+
+impl SeasonalFood {
+    pub fn get(&self, season: Season) -> f32 {
+        match season {
+            Season::Spring => self.spring,
+            Season::Summer => self.summer,
+            Season::Autumn => self.autumn,
+            Season::Winter => self.winter,
+        }
+    }
+}
+
+impl Default for SeasonalFood {
+    fn default() -> Self {
+        Self { spring: 1.0, summer: default_summer_food(), autumn: 1.0, winter: default_winter_food() }
+    }
+}
+
+/// Day length, season length and what they do (`config/world_clock.yaml`).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClockConfig {
+    /// Ticks per day; 0 turns the clock off.
+    #[serde(default = "default_day_length")]
+    pub day_length: u32,
+    /// Days per season; 0 means no seasons.
+    #[serde(default = "default_days_per_season")]
+    pub days_per_season: u32,
+    /// Vision multiplier in the dead of night.
+    #[serde(default = "default_night_vision")]
+    pub night_vision: f32,
+    /// Movement speed multiplier in the dead of night.
+    #[serde(default = "default_night_speed")]
+    pub night_speed: f32,
+    #[serde(default)]
+    pub food: SeasonalFood,
+    /// Hunger rate multiplier in winter.
+    #[serde(default = "default_winter_hunger")]
+    pub winter_hunger: f32,
+}
+
+fn default_one() -> f32 { 1.0 }
+fn default_summer_food() -> f32 { 1.5 }
+fn default_winter_food() -> f32 { 0.5 }
+fn default_day_length() -> u32 { 240 }
+fn default_days_per_season() -> u32 { 10 }
+fn default_night_vision() -> f32 { 0.5 }
+fn default_night_speed() -> f32 { 0.7 }
+fn default_winter_hunger() -> f32 { 1.5 }
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            day_length: default_day_length(),
+            days_per_season: default_days_per_season(),
+            night_vision: default_night_vision(),
+            night_speed: default_night_speed(),
+            food: SeasonalFood::default(),
+            winter_hunger: default_winter_hunger(),
+        }
+    }
+}
+
+/// ECS resource: the current tick's time of day and season.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorldClock {
+    /// Ticks since the start of the run.
+    pub tick: u64,
+    /// `None` for a timeless world.
+    pub config: Option<ClockConfig>,
+}
+
+impl WorldClock {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn new(config: ClockConfig) -> Self {
+        Self { tick: 0, config: (config.day_length > 0).then_some(config) }
+    }
+
+    /// Reads a `ClockConfig` from `path`. A missing file gives a timeless world; an
+    /// invalid one is logged and does too.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(contents) = std::fs::read_to_string(&path) else { return Self::disabled() };
+        match serde_yaml::from_str::<ClockConfig>(&contents) {
+            Ok(config) => Self::new(config),
+            Err(e) => {
+                log::warn!("[CONFIG] {}: invalid world clock ({}); the world will be timeless", path.as_ref().display(), e);
+                Self::disabled()
+            }
+        }
+    }
+
+    /// Ticks since midnight of day 1 (the run starts at 06:00).
+    fn elapsed(&self, config: &ClockConfig) -> u64 {
+        self.tick + config.day_length as u64 / 4
+    }
+
+    /// 1-based day number, or `None` for a timeless world.
+    pub fn day(&self) -> Option<u64> {
+        self.config.as_ref().map(|c| self.elapsed(c) / c.day_length as u64 + 1)
+    }
+
+    /// Fraction of the day gone, from 0 at midnight; midday for a timeless world.
+    pub fn time_of_day(&self) -> f32 {
+        match &self.config {
+            Some(c) => (self.elapsed(c) % c.day_length as u64) as f32 / c.day_length as f32,
+            None => 0.5,
+        }
+    }
+
+    /// 1 by day (08:00 to 16:00), 0 by night (20:00 to 04:00), in between at dawn and dusk.
+    pub fn daylight(&self) -> f32 {
+        (0.5 - (std::f32::consts::TAU * self.time_of_day()).cos()).clamp(0.0, 1.0)
+    }
+
+    pub fn season(&self) -> Option<Season> {
+        let config = self.config.as_ref().filter(|c| c.days_per_season > 0)?;
+        let day = self.elapsed(config) / config.day_length as u64;
+        Some(Season::ALL[(day / config.days_per_season as u64 % 4) as usize])
+    }
+
+    /// Multiplier on agents' vision.
+    pub fn vision_factor(&self) -> f32 {
+        self.config.as_ref().map_or(1.0, |c| lerp(c.night_vision, 1.0, self.daylight()))
+    }
+
+    /// Multiplier on agents' movement speed.
+    pub fn speed_factor(&self) -> f32 {
+        self.config.as_ref().map_or(1.0, |c| lerp(c.night_speed, 1.0, self.daylight()))
+    }
+
+    /// Multiplier on agents' hunger rate.
+    pub fn hunger_factor(&self) -> f32 {
+        match (&self.config, self.season()) {
+            (Some(c), Some(Season::Winter)) => c.winter_hunger,
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier on the number of food items spawned per tick.
+    pub fn food_factor(&self) -> f32 {
+        match (&self.config, self.season()) {
+            (Some(c), Some(season)) => c.food.get(season),
+            _ => 1.0,
+        }
+    }
+
+    /// "Day 3, 14:30, summer"; "timeless" without a clock.
+    pub fn label(&self) -> String {
+        let Some(day) = self.day() else { return "timeless".to_string() };
+        let minutes = (self.time_of_day() * 24.0 * 60.0) as u32;
+        let mut label = format!("Day {}, {:02}:{:02}", day, minutes / 60, minutes % 60);
+        if let Some(season) = self.season() {
+            label.push_str(&format!(", {}", season.as_str()));
+        }
+        label
+    }
+}
+
+/// ECS component: an agent's speed, vision and hunger rate before the clock's factors
+/// (genome and old age only). Added by `clock_factor_system` the first time it sees the agent.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BaseTraits {
+    pub speed: f32,
+    pub vision: f32,
+    pub hunger_rate: f32,
+}
+
+impl BaseTraits {
+    pub fn of(agent_type: &AgentType) -> Self {
+        Self { speed: agent_type.movement_profile.speed, vision: agent_type.vision, hunger_rate: agent_type.hunger_rate }
+    }
+}
+
+/// Sets every agent's expressed speed, vision and hunger rate to its `BaseTraits` scaled by
+/// the current time of day and season. Does nothing in a timeless world.
+pub fn clock_factor_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("ClockFactorSystem")
+        .read_resource::<WorldClock>()
+        .with_query(<(Entity, &mut AgentType, Option<&BaseTraits>)>::query())
+        .build(|cmd, world, clock, query| {
+            if clock.config.is_none() {
+                return;
+            }
+            let (speed, vision, hunger) = (clock.speed_factor(), clock.vision_factor(), clock.hunger_factor());
+            for (entity, agent_type, base) in query.iter_mut(world) {
+                let base = base.copied().unwrap_or_else(|| {
+                    let base = BaseTraits::of(agent_type);
+                    cmd.add_component(*entity, base);
+                    base
+                });
+                agent_type.movement_profile.speed = base.speed * speed;
+                agent_type.vision = base.vision * vision;
+                agent_type.hunger_rate = base.hunger_rate * hunger;
+            }
+        })
+}
+
+/// Moves the clock on at the end of each tick.
+pub fn world_clock_system() -> impl legion::systems::Runnable {
+    SystemBuilder::new("WorldClockSystem")
+        .write_resource::<WorldClock>()
+        .build(|_cmd, _world, clock, _| {
+            clock.tick += 1;
+        })
+}
@@ -0,0 +1,43 @@
+//! Every attribute in config/agent_types.yaml ends up on AgentType.
+
+use community_sim::agent::{AgentType, MovementEffect};
+use community_sim::map::Terrain;
+use community_sim::util::load_agent_types;
+
+#[test]
+fn test_movement_effect_parsing() {
+    assert_eq!("Normal".parse::<MovementEffect>(), Ok(MovementEffect::None));
+    assert_eq!("Blocked".parse::<MovementEffect>(), Ok(MovementEffect::Blocked));
+    assert_eq!(" Slow(2.5) ".parse::<MovementEffect>(), Ok(MovementEffect::Slowed(2.5)));
+    assert!("Slow(fast)".parse::<MovementEffect>().is_err());
+    assert!("Slow(0)".parse::<MovementEffect>().is_err());
+    assert!("Crawl".parse::<MovementEffect>().is_err());
+    assert_eq!(MovementEffect::Slowed(2.5).to_string(), "Slow(2.5)");
+}
+
+#[test]
+fn test_load_agent_types_reads_all_fields() {
+    let agent_types = load_agent_types("config/agent_types.yaml");
+    let soldier = agent_types.iter().find(|a| a.name == "soldier").expect("soldier type");
+    assert_eq!(soldier.strength, 8.0);
+    assert_eq!(soldier.stamina, 80.0);
+    assert_eq!(soldier.vision, 8.0);
+    assert_eq!(soldier.work_rate, 2.0);
+    assert_eq!(soldier.icon, "X");
+    assert_eq!(soldier.damping, 0.95);
+    assert_eq!(soldier.move_probability, 0.7);
+    assert_eq!(soldier.terrain_effect(Terrain::Mountain), MovementEffect::Slowed(3.0));
+    assert_eq!(soldier.terrain_effect(Terrain::Grass), MovementEffect::None);
+    assert_eq!(soldier.speed_on(Terrain::Mountain), 1.0);
+
+    let farmer = agent_types.iter().find(|a| a.name == "farmer").expect("farmer type");
+    assert_eq!(farmer.terrain_effect(Terrain::Mountain), MovementEffect::None);
+}
+
+#[test]
+fn test_terrain_effects_survive_json_round_trip() {
+    let agent_type = load_agent_types("config/agent_types.yaml").remove(0);
+    let json = serde_json::to_string(&agent_type).unwrap();
+    let back: AgentType = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, agent_type);
+}
@@ -13,6 +13,7 @@ fn agent_event_log_to_gui_system_pushes_events() {
         agent: dummy_agent,
         agent_type: "TestType".to_string(),
         pos: (1.0, 2.0),
+        parents: vec![],
     });
     let mut event_log = EventLog::new(10);
     resources.insert(agent_event_log);
@@ -13,6 +13,7 @@ fn agent_event_log_records_events() {
         agent: dummy_agent,
         agent_type: "test_type".to_string(),
         pos: (1.0, 2.0),
+        parents: vec![],
     });
     log.push(AgentEvent::AteFood {
         agent: dummy_agent,
@@ -0,0 +1,111 @@
+//! Aging: agents grow old, slow down and get hungrier, and die of old age; every death
+//! is recorded with its cause.
+
+mod common;
+
+use community_sim::agent::{AgentType, Energy, Hunger};
+use community_sim::agent::aging::{aging_system, Age, LifespanConfig};
+use community_sim::agent::event::{AgentEvent, AgentEventLog, DeathCause};
+use community_sim::agent::systems::{agent_death_system, death_cause};
+use community_sim::ecs_components::Demographics;
+use community_sim::map::{Map, Terrain};
+use legion::{Entity, EntityStore, Schedule, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use common::{add_agent, grass, run, setup, yaml_round_trip};
+
+const LIFESPAN: LifespanConfig = LifespanConfig { mean: 10, spread: 0, old_age: 0.5, old_speed: 0.5, old_hunger: 2.0 };
+
+/// Grass with water at (8, 5).
+fn pond() -> Map {
+    let mut map = grass(10);
+    map.tiles[5][8] = Terrain::Water;
+    map
+}
+
+fn schedule() -> Schedule {
+    Schedule::builder()
+        .add_system(aging_system())
+        .add_system(agent_death_system())
+        .build()
+}
+
+#[test]
+fn test_lifespan_is_drawn_within_spread() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mortal = AgentType { lifespan: Some(LifespanConfig { mean: 100, spread: 20, ..Default::default() }), ..Default::default() };
+    for _ in 0..50 {
+        let lifespan = Age::new(&mortal, &mut rng).lifespan.unwrap();
+        assert!((80..=120).contains(&lifespan));
+    }
+    assert_eq!(Age::new(&AgentType::default(), &mut rng), Age { ticks: 0, lifespan: None });
+}
+
+#[test]
+fn test_old_agents_decline_then_die_of_old_age() {
+    let (mut world, mut resources, map) = setup(pond());
+    let agent_type = AgentType { move_probability: 0.0, lifespan: Some(LIFESPAN), ..Default::default() };
+    let (speed, hunger_rate) = (agent_type.movement_profile.speed, agent_type.hunger_rate);
+    let agent = add_agent(&mut world, &map, agent_type, 2.5, 5.5);
+    let mut schedule = schedule();
+    let expressed = |world: &World| {
+        let entry = world.entry_ref(agent).unwrap();
+        let agent_type = entry.get_component::<AgentType>().unwrap();
+        (agent_type.movement_profile.speed, agent_type.hunger_rate)
+    };
+    run(&mut world, &mut resources, &mut schedule, 5);
+    assert_eq!(expressed(&world), (speed, hunger_rate));
+    run(&mut world, &mut resources, &mut schedule, 3);
+    // Age 8 of 10, old age from 5: 60% of the way to the end-of-life multipliers.
+    let (old_speed, old_hunger_rate) = expressed(&world);
+    assert!((old_speed - speed * 0.7).abs() < 1e-5);
+    assert!((old_hunger_rate - hunger_rate * 1.6).abs() < 1e-5);
+    run(&mut world, &mut resources, &mut schedule, 2);
+    assert!(!world.contains(agent));
+    let demographics = resources.get::<Demographics>().unwrap();
+    assert_eq!(demographics.deaths_of(DeathCause::OldAge), 1);
+    assert_eq!(demographics.mean_age_at_death(), Some(10.0));
+    let log = resources.get::<AgentEventLog>().unwrap();
+    assert!(log.0.iter().any(|e| matches!(e, AgentEvent::Died { agent: a, cause: DeathCause::OldAge, age: 10, .. } if *a == agent)));
+}
+
+#[test]
+fn test_death_causes() {
+    let (mut world, mut resources, map) = setup(pond());
+    let plain = AgentType { move_probability: 0.0, ..Default::default() };
+    let starving = add_agent(&mut world, &map, plain.clone(), 1.5, 5.5);
+    let exhausted = add_agent(&mut world, &map, plain.clone(), 3.5, 5.5);
+    let drowning = add_agent(&mut world, &map, plain.clone(), 8.5, 5.5);
+    let healthy = add_agent(&mut world, &map, plain, 5.5, 5.5);
+    world.entry(starving).unwrap().get_component_mut::<Hunger>().unwrap().value = 0.0;
+    world.entry(exhausted).unwrap().get_component_mut::<Energy>().unwrap().value = 0.0;
+    world.entry(drowning).unwrap().get_component_mut::<Energy>().unwrap().value = -1.0;
+    schedule().execute(&mut world, &mut resources);
+
+    assert!(world.contains(healthy));
+    let log = resources.get::<AgentEventLog>().unwrap();
+    let mut deaths: Vec<(Entity, DeathCause)> = log.0.iter().filter_map(|e| match e {
+        AgentEvent::Died { agent, cause, .. } => Some((*agent, *cause)),
+        _ => None,
+    }).collect();
+    deaths.sort_by_key(|(_, cause)| *cause);
+    assert_eq!(deaths, vec![(starving, DeathCause::Starvation), (exhausted, DeathCause::Exhaustion), (drowning, DeathCause::Drowning)]);
+    let demographics = resources.get::<Demographics>().unwrap();
+    assert_eq!(demographics.total_deaths(), 3);
+    assert_eq!(demographics.deaths_summary(), "starvation 1, exhaustion 1, drowning 1, old_age 0");
+
+    // Starvation wins over everything else.
+    let expired = Age { ticks: 5, lifespan: Some(5) };
+    let (hungry, tired) = (Hunger { value: 0.0, threshold: 50.0 }, Energy { value: 0.0 });
+    assert_eq!(death_cause(&hungry, &tired, Terrain::Water, Some(&expired)), Some(DeathCause::Starvation));
+    let (fed, rested) = (Hunger { value: 50.0, threshold: 50.0 }, Energy { value: 10.0 });
+    assert_eq!(death_cause(&fed, &rested, Terrain::Grass, Some(&expired)), Some(DeathCause::OldAge));
+    assert_eq!(death_cause(&fed, &rested, Terrain::Grass, None), None);
+}
+
+#[test]
+fn test_lifespan_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Worker\"\n  type: \"worker\"\n  lifespan:\n    mean: 400\n    old_speed: 0.25\n- name: \"Scout\"\n  type: \"scout\"\n");
+    assert_eq!(types[0].lifespan, Some(LifespanConfig { mean: 400, old_speed: 0.25, ..Default::default() }));
+    assert_eq!(types[1].lifespan, None);
+}
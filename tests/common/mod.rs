@@ -0,0 +1,57 @@
+//! Fixtures shared by the integration tests: an empty world with the standard resources,
+//! all-grass maps, spawning agents, running schedules, and agent-type YAML round trips.
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use community_sim::agent::AgentType;
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::map::{Map, Terrain};
+use community_sim::sim_rng::SimRng;
+use community_sim::util::{agent_types_to_yaml, load_agent_types};
+use legion::{Entity, Resources, Schedule, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Seed of the `SimRng` in `setup` and of the spawn RNG in `add_agent`.
+pub const SEED: u64 = 1;
+
+/// A `size` by `size` map of grass.
+pub fn grass(size: i32) -> Map {
+    Map::from_tiles(size, size, vec![vec![Terrain::Grass; size as usize]; size as usize])
+}
+
+/// An empty world over `map`, with the standard resources seeded with `SEED`.
+pub fn setup(map: Map) -> (World, Resources, Map) {
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(SEED));
+    (World::default(), resources, map)
+}
+
+/// Spawns an agent of `agent_type` at (`x`, `y`).
+pub fn add_agent(world: &mut World, map: &Map, agent_type: AgentType, x: f32, y: f32) -> Entity {
+    spawn_agent(world, Position { x, y }, agent_type, map, &mut AgentEventLog::default(), &mut StdRng::seed_from_u64(SEED))
+}
+
+/// Runs `schedule` for `ticks` ticks.
+pub fn run(world: &mut World, resources: &mut Resources, schedule: &mut Schedule, ticks: usize) {
+    for _ in 0..ticks {
+        schedule.execute(world, resources);
+    }
+}
+
+/// Loads agent types from `yaml`, checks that writing them back out with
+/// `agent_types_to_yaml` and loading that again gives the same types, and returns them.
+pub fn yaml_round_trip(yaml: &str) -> Vec<AgentType> {
+    static FILES: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!("agent_types_{}_{}.yaml", std::process::id(), FILES.fetch_add(1, Ordering::Relaxed)));
+    std::fs::write(&path, yaml).unwrap();
+    let types = load_agent_types(path.to_str().unwrap());
+    std::fs::write(&path, agent_types_to_yaml(&types)).unwrap();
+    let reloaded = load_agent_types(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert_eq!(reloaded, types);
+    types
+}
@@ -0,0 +1,100 @@
+//! MLP decision engine: observation layout, action choice and wiring into action selection.
+
+use community_sim::agent::{AgentState, AgentType, DecisionEngineConfig, MLPConfig};
+use community_sim::agent::decision::{decide, Action, Observation, OBSERVATION_SIZE};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::{action_selection_system, spawn_agent};
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::food::Food;
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::Target;
+use community_sim::sim_rng::SimRng;
+use community_sim::spatial::spatial_index_system;
+use legion::{IntoQuery, Resources, Schedule, World};
+
+/// A single-layer network that ignores its inputs and always prefers `action`.
+fn always(action: Action) -> MLPConfig {
+    let biases = Action::ALL.iter().map(|a| if *a == action { 1.0 } else { 0.0 }).collect();
+    MLPConfig {
+        input_size: OBSERVATION_SIZE,
+        hidden_sizes: vec![],
+        output_size: 4,
+        weights: vec![vec![vec![0.0; OBSERVATION_SIZE]; 4]],
+        biases: vec![biases],
+    }
+}
+
+fn mlp_agent(action: Action) -> AgentType {
+    AgentType { decision_engine: DecisionEngineConfig::MLP(always(action)), vision: 10.0, ..Default::default() }
+}
+
+#[test]
+fn test_observation_layout() {
+    let agent_type = AgentType { hunger_cap: 100.0, stamina: 50.0, vision: 10.0, ..Default::default() };
+    let observation = Observation {
+        hunger: 25.0,
+        energy: 25.0,
+        nearest_food: Some((0.0, 5.0)),
+        nearby_agents: 3,
+        terrain: Terrain::Forest,
+    };
+    let v = observation.to_vector(&agent_type);
+    assert_eq!(v.len(), OBSERVATION_SIZE);
+    assert_eq!(&v[..6], &[0.25, 0.5, 0.0, 1.0, 0.5, 0.3]);
+    assert_eq!(&v[6..], &[0.0, 1.0, 0.0, 0.0, 1.0]);
+
+    let no_food = Observation { nearest_food: None, ..observation };
+    assert_eq!(&no_food.to_vector(&agent_type)[2..5], &[0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_decide_takes_highest_output() {
+    let agent_type = AgentType::default();
+    let observation = Observation { hunger: 50.0, energy: 50.0, nearest_food: None, nearby_agents: 0, terrain: Terrain::Grass };
+    for action in Action::ALL {
+        assert_eq!(decide(&always(action), &agent_type, &observation), Some(action));
+    }
+    let wrong_size = MLPConfig { input_size: 3, ..always(Action::Rest) };
+    assert_eq!(decide(&wrong_size, &agent_type, &observation), None);
+}
+
+#[test]
+fn test_validate_checks_layer_shapes() {
+    assert!(always(Action::Wander).validate().is_ok());
+    let mut broken = always(Action::Wander);
+    broken.weights[0][2].pop();
+    assert!(broken.validate().is_err());
+    let missing_hidden = MLPConfig { hidden_sizes: vec![5], ..always(Action::Wander) };
+    assert!(missing_hidden.validate().is_err());
+}
+
+fn select_once(agent_type: AgentType) -> (AgentState, Target) {
+    let map = Map::from_tiles(20, 20, vec![vec![Terrain::Grass; 20]; 20]);
+    let mut world = World::default();
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(3));
+    let mut rng = rand::thread_rng();
+    world.push((Position { x: 12.5, y: 5.5 }, Food::new(5.0)));
+    spawn_agent(&mut world, Position { x: 5.5, y: 5.5 }, agent_type, &map, &mut AgentEventLog::default(), &mut rng);
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(action_selection_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    let (state, target) = <(&AgentState, &Target)>::query().iter(&world).next().unwrap();
+    (*state, target.clone())
+}
+
+#[test]
+fn test_mlp_agents_act_on_their_network() {
+    let (state, target) = select_once(mlp_agent(Action::SeekFood));
+    assert_eq!(state, AgentState::Moving);
+    assert_eq!((target.x, target.y), (12.5, 5.5));
+
+    let (state, _) = select_once(mlp_agent(Action::Rest));
+    assert_eq!(state, AgentState::Resting);
+
+    let (state, _) = select_once(mlp_agent(Action::Stay));
+    assert_eq!(state, AgentState::Idle);
+}
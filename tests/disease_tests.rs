@@ -0,0 +1,164 @@
+//! Disease: infection along agent contacts, the course of an illness, doctors curing it,
+//! and the SIR counts.
+
+mod common;
+
+use community_sim::agent::{AgentState, AgentType, Hunger};
+use community_sim::agent::disease::{disease_system, DiseaseConfig, DiseaseSink, DiseaseStats, Health, HealthStatus};
+use community_sim::agent::work::{work_system, WorkRole, WorkStats};
+use community_sim::ecs_components::AgentContacts;
+use community_sim::snapshot::Snapshot;
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Resources, Schedule, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use common::{add_agent, grass, run, setup, yaml_round_trip};
+
+/// A type that always catches the illness on contact: 2 ticks of incubation, 5 of illness.
+fn catching() -> AgentType {
+    let disease = DiseaseConfig { transmission: 1.0, incubation_ticks: 2, recovery_ticks: 5, hunger_multiplier: 3.0, initial_infected: 0.0 };
+    AgentType { move_probability: 0.0, hunger_rate: 0.1, disease: Some(disease), ..Default::default() }
+}
+
+fn health(world: &World, entity: Entity) -> Health {
+    *world.entry_ref(entity).unwrap().get_component::<Health>().unwrap()
+}
+
+fn set_health(world: &mut World, entity: Entity, status: HealthStatus, ticks: u32) {
+    *world.entry(entity).unwrap().get_component_mut::<Health>().unwrap() = Health { status, ticks };
+}
+
+fn schedule() -> Schedule {
+    Schedule::builder().add_system(disease_system()).build()
+}
+
+#[test]
+fn test_illness_spreads_along_contacts_and_runs_its_course() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let sick = add_agent(&mut world, &map, catching(), 5.0, 5.0);
+    let contact = add_agent(&mut world, &map, catching(), 6.0, 5.0);
+    let stranger = add_agent(&mut world, &map, catching(), 15.0, 15.0);
+    resources.insert(AgentContacts(vec![(sick, contact)]));
+
+    // Still incubating: not contagious yet.
+    set_health(&mut world, sick, HealthStatus::Infected, 0);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(health(&world, contact).status, HealthStatus::Susceptible);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(health(&world, contact), Health { status: HealthStatus::Infected, ticks: 0 });
+    assert_eq!(health(&world, stranger).status, HealthStatus::Susceptible);
+    let stats = resources.get::<DiseaseStats>().unwrap().clone();
+    assert_eq!((stats.susceptible, stats.infected, stats.recovered, stats.infections), (1, 2, 0, 1));
+
+    // Ill agents lose an extra (3 - 1) * 0.1 hunger a tick on top of the passive drain.
+    let hunger = |world: &World, e| world.entry_ref(e).unwrap().get_component::<Hunger>().unwrap().value;
+    let before = hunger(&world, sick);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert!((before - hunger(&world, sick) - 0.2).abs() < 1e-4);
+
+    // 7 ticks after infection the first agent recovers, and stays immune.
+    run(&mut world, &mut resources, &mut schedule(), 4);
+    assert_eq!(health(&world, sick).status, HealthStatus::Recovered);
+    run(&mut world, &mut resources, &mut schedule(), 10);
+    assert_eq!(health(&world, sick).status, HealthStatus::Recovered);
+    assert_eq!(health(&world, contact).status, HealthStatus::Recovered);
+    let stats = resources.get::<DiseaseStats>().unwrap().clone();
+    assert_eq!((stats.susceptible, stats.infected, stats.recovered, stats.recoveries), (1, 0, 2, 2));
+    assert_eq!(stats.infected_history.len(), 17);
+}
+
+#[test]
+fn test_types_without_disease_never_fall_ill() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let sick = add_agent(&mut world, &map, catching(), 5.0, 5.0);
+    let immune = add_agent(&mut world, &map, AgentType { move_probability: 0.0, ..Default::default() }, 6.0, 5.0);
+    assert!(world.entry_ref(immune).unwrap().get_component::<Health>().is_err());
+    set_health(&mut world, sick, HealthStatus::Infected, 3);
+    resources.insert(AgentContacts(vec![(immune, sick)]));
+    run(&mut world, &mut resources, &mut schedule(), 3);
+    let stats = resources.get::<DiseaseStats>().unwrap();
+    assert_eq!((stats.infected, stats.infections), (1, 0));
+}
+
+#[test]
+fn test_doctor_cures_the_ill() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let doctor = add_agent(&mut world, &map, AgentType { move_probability: 0.0, role: Some(WorkRole::Doctor), work_rate: 5.0, ..Default::default() }, 5.0, 5.0);
+    let mut patient_type = catching();
+    patient_type.disease.as_mut().unwrap().recovery_ticks = 10;
+    let patient = add_agent(&mut world, &map, patient_type, 5.0, 5.0);
+    set_health(&mut world, patient, HealthStatus::Infected, 2);
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(work_system())
+        .add_system(disease_system())
+        .build();
+    // One tick to start, then each working tick cures 5 of the illness's 12 ticks.
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(*world.entry_ref(doctor).unwrap().get_component::<AgentState>().unwrap(), AgentState::Working);
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(health(&world, patient).status, HealthStatus::Infected);
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(health(&world, patient).status, HealthStatus::Recovered);
+    assert_eq!(resources.get::<WorkStats>().unwrap().cured, 1);
+}
+
+#[test]
+fn test_slow_doctors_still_cure() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    add_agent(&mut world, &map, AgentType { move_probability: 0.0, role: Some(WorkRole::Doctor), work_rate: 0.3, ..Default::default() }, 5.0, 5.0);
+    let treated = add_agent(&mut world, &map, catching(), 5.0, 5.0);
+    let untreated = add_agent(&mut world, &map, catching(), 15.0, 15.0);
+    for patient in [treated, untreated] {
+        set_health(&mut world, patient, HealthStatus::Infected, 2);
+    }
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(work_system())
+        .build();
+    // One tick to start, then each working tick takes at least a tick off the illness.
+    run(&mut world, &mut resources, &mut schedule, 3);
+    assert_eq!(health(&world, treated).ticks, 4);
+    assert_eq!(health(&world, untreated).ticks, 2);
+}
+
+#[test]
+fn test_initial_infections_seed_only_the_first_generation() {
+    let config = DiseaseConfig { initial_infected: 1.0, ..Default::default() };
+    let mut rng = StdRng::seed_from_u64(1);
+    assert_eq!(Health::new(&config, true, &mut rng).status, HealthStatus::Infected);
+    assert_eq!(Health::new(&config, false, &mut rng).status, HealthStatus::Susceptible);
+    assert_eq!(Health::new(&DiseaseConfig::default(), true, &mut rng).status, HealthStatus::Susceptible);
+}
+
+#[test]
+fn test_sir_counts_csv_and_snapshot() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let sick = add_agent(&mut world, &map, catching(), 5.0, 5.0);
+    add_agent(&mut world, &map, catching(), 8.0, 5.0);
+    set_health(&mut world, sick, HealthStatus::Infected, 0);
+    let path = std::env::temp_dir().join(format!("disease_{}.csv", std::process::id()));
+    resources.insert(DiseaseSink::create(&path, 0).unwrap());
+    run(&mut world, &mut resources, &mut schedule(), 8);
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "tick,susceptible,infected,recovered");
+    assert_eq!(lines[1], "0,1,1,0");
+    assert_eq!(lines[8], "7,1,0,1");
+
+    let snapshot = Snapshot::capture(&world, &resources, "disease", 8);
+    let (mut restored, mut restored_resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut restored, &mut restored_resources);
+    let mut statuses: Vec<HealthStatus> = <&Health>::query().iter(&restored).map(|h| h.status).collect();
+    statuses.sort_by_key(|s| *s as u8);
+    assert_eq!(statuses, vec![HealthStatus::Susceptible, HealthStatus::Recovered]);
+    assert_eq!(restored_resources.get::<DiseaseStats>().unwrap().recoveries, 1);
+}
+
+#[test]
+fn test_disease_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Worker\"\n  type: \"worker\"\n  disease:\n    transmission: 0.5\n    recovery_ticks: 40\n- name: \"Scout\"\n  type: \"scout\"\n");
+    assert_eq!(types[0].disease, Some(DiseaseConfig { transmission: 0.5, recovery_ticks: 40, ..Default::default() }));
+    assert_eq!(types[1].disease, None);
+}
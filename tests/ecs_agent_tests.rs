@@ -21,11 +21,12 @@ fn test_ecs_agent_spawn_and_query() {
         decision_engine: DecisionEngineConfig::Simple,
         hunger_rate: 1.0,
         hunger_threshold: 10.0,
+        ..Default::default()
     };
     let pos = Position { x: 10.0, y: 20.0 };
     let map = Map::new(32, 32);
     let mut agent_event_log = AgentEventLog::default();
-    let _entity = spawn_agent(&mut world, pos, agent_type.clone(), &map, &mut agent_event_log);
+    let _entity = spawn_agent(&mut world, pos, agent_type.clone(), &map, &mut agent_event_log, &mut rand::thread_rng());
     // Query for the agent entity
     let mut found = false;
     for (pos, agent_type_ref) in <(legion::Read<Position>, legion::Read<AgentType>)>::query().iter(&world) {
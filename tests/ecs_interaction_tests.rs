@@ -1,16 +1,40 @@
 //! ECS-based agent interaction tests for community-sim
 
-use legion::IntoQuery;
-use community_sim::ecs_components::Position;
-use community_sim::agent::{AgentType, components::{MovementProfile, MovementEffect, DecisionEngineConfig}};
+mod common;
+
+use std::collections::BTreeMap;
+use legion::{Entity, EntityStore, World};
+use community_sim::ecs_components::{agent_interaction_system, entity_interaction_system, InteractionStats, Position};
+use community_sim::agent::{AgentType, Energy, Hunger, InteractionState, components::{MovementProfile, MovementEffect, DecisionEngineConfig}};
 use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::interaction::{fight, share_food, InteractionConfig, InteractionOutcome, MAX_RECENT_PARTNERS};
 use community_sim::map::Map;
-use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::event::{AgentEvent, AgentEventLog};
+use community_sim::sim_rng::SimRng;
+use community_sim::spatial::spatial_index_system;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use common::{add_agent, run, setup, yaml_round_trip};
+
+fn only(outcome: InteractionOutcome) -> AgentType {
+    AgentType {
+        move_probability: 0.0,
+        interaction: InteractionConfig { duration_ticks: 2, cooldown_ticks: 4, outcomes: [(outcome, 1.0)].into_iter().collect() },
+        ..Default::default()
+    }
+}
+
+fn schedule() -> legion::Schedule {
+    legion::Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .add_system(agent_interaction_system())
+        .build()
+}
 
-fn agent_interaction_system() -> impl legion::systems::Runnable {
-    // Dummy system for test compilation; replace with actual system if/when available
-    legion::SystemBuilder::new("DummyAgentInteractionSystem")
-        .build(|_, _, _, _| {})
+fn state(world: &World, agent: Entity) -> InteractionState {
+    world.entry_ref(agent).unwrap().get_component::<InteractionState>().unwrap().clone()
 }
 
 #[test]
@@ -27,17 +51,134 @@ fn test_ecs_agent_interaction_system_runs() {
         decision_engine: DecisionEngineConfig::Simple,
         hunger_rate: 1.0,
         hunger_threshold: 10.0,
+        ..Default::default()
     };
     let map = Map::new(32, 32);
     let mut agent_event_log = AgentEventLog::default();
     // Spawn two agents adjacent to each other
-    spawn_agent(&mut world, Position { x: 5.0, y: 5.0 }, agent_type.clone(), &map, &mut agent_event_log);
-    spawn_agent(&mut world, Position { x: 5.0, y: 6.0 }, agent_type.clone(), &map, &mut agent_event_log);
+    spawn_agent(&mut world, Position { x: 5.0, y: 5.0 }, agent_type.clone(), &map, &mut agent_event_log, &mut rand::thread_rng());
+    spawn_agent(&mut world, Position { x: 5.0, y: 6.0 }, agent_type.clone(), &map, &mut agent_event_log, &mut rand::thread_rng());
     // Run the agent_interaction_system (should not panic, and should process agents)
     let mut schedule = legion::Schedule::builder()
         .add_system(agent_interaction_system())
         .build();
     let mut resources = legion::Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(1));
     schedule.execute(&mut world, &mut resources);
     // (For more detailed assertions, expand ECS agent state and query)
 }
+
+#[test]
+fn test_interaction_lifecycle() {
+    let (mut world, mut resources, map) = setup(Map::new(16, 16));
+    let a = add_agent(&mut world, &map, only(InteractionOutcome::Chat), 5.0, 5.0);
+    let b = add_agent(&mut world, &map, only(InteractionOutcome::Chat), 6.0, 5.0);
+    let mut schedule = schedule();
+
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(state(&world, a).target, Some(b));
+    assert_eq!(state(&world, b).target, Some(a));
+    assert_eq!(resources.get::<InteractionStats>().unwrap().active_interactions, 1);
+
+    schedule.execute(&mut world, &mut resources);
+    let (sa, sb) = (state(&world, a), state(&world, b));
+    assert_eq!((sa.target, sa.cooldown, sa.last_partner), (None, 4, Some(b)));
+    assert_eq!((sb.target, sb.last_partner), (None, Some(a)));
+    assert!(sa.has_met(b) && sb.has_met(a));
+    {
+        let log = resources.get::<AgentEventLog>().unwrap();
+        let interacted: Vec<_> = log.0.iter().filter(|e| matches!(e, AgentEvent::Interacted { .. })).collect();
+        assert_eq!(interacted.len(), 1);
+        assert!(matches!(interacted[0], AgentEvent::Interacted { agent, with, outcome: InteractionOutcome::Chat } if *agent == a && *with == b));
+        assert_eq!(resources.get::<InteractionStats>().unwrap().outcomes_summary(), "chat 1, share_food 0, share_information 0, fight 0");
+    }
+
+    // On cooldown the pair stays apart even though they are still adjacent.
+    for _ in 0..4 {
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(state(&world, a).target, None);
+    }
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(state(&world, a).target, Some(b));
+}
+
+#[test]
+fn test_pair_breaks_off_when_apart() {
+    let (mut world, mut resources, map) = setup(Map::new(16, 16));
+    let a = add_agent(&mut world, &map, only(InteractionOutcome::Chat), 5.0, 5.0);
+    let b = add_agent(&mut world, &map, only(InteractionOutcome::Chat), 6.0, 5.0);
+    let mut schedule = schedule();
+    schedule.execute(&mut world, &mut resources);
+    world.entry(b).unwrap().get_component_mut::<Position>().unwrap().x = 12.0;
+    schedule.execute(&mut world, &mut resources);
+
+    let (sa, sb) = (state(&world, a), state(&world, b));
+    assert_eq!((sa.target, sb.target), (None, None));
+    assert_eq!((sa.cooldown, sb.cooldown), (4, 4));
+    assert!(!sa.has_met(b));
+    assert!(resources.get::<InteractionStats>().unwrap().outcomes.is_empty());
+}
+
+#[test]
+fn test_recent_partners_are_capped() {
+    let mut state = InteractionState { target: None, ticks: 0, last_partner: None, cooldown: 0, recent_partners: Default::default(), food_tip: None };
+    let mut world = World::default();
+    let partners: Vec<Entity> = (0..MAX_RECENT_PARTNERS + 2).map(|_| world.push((0u8,))).collect();
+    for partner in &partners {
+        state.remember(*partner, InteractionOutcome::Chat);
+    }
+    state.remember(partners[3], InteractionOutcome::Chat);
+    assert_eq!(state.recent_partners.len(), MAX_RECENT_PARTNERS);
+    assert!(!state.has_met(partners[0]) && !state.has_met(partners[1]));
+    assert_eq!(state.recent_partners.back().unwrap().partner, Some(partners[3]));
+    assert_eq!(state.last_partner, Some(partners[3]));
+}
+
+#[test]
+fn test_share_food_and_fight() {
+    let agent_type = AgentType { hunger_cap: 100.0, ..Default::default() };
+    let (mut fed, mut hungry) = (Hunger { value: 90.0, threshold: 50.0 }, Hunger { value: 10.0, threshold: 50.0 });
+    assert_eq!(share_food((&mut hungry, &agent_type), (&mut fed, &agent_type)), 20.0);
+    assert_eq!((fed.value, hungry.value), (70.0, 30.0));
+
+    let strong = AgentType { strength: 1.0, ..Default::default() };
+    let weak = AgentType { strength: 0.0, ..Default::default() };
+    let (mut a, mut b) = (Energy { value: 50.0 }, Energy { value: 50.0 });
+    assert!(fight((&mut a, &strong), (&mut b, &weak), &mut StdRng::seed_from_u64(1)));
+    assert!(a.value > b.value);
+}
+
+#[test]
+fn test_choose_outcome_follows_weights() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let config = InteractionConfig { outcomes: [(InteractionOutcome::Fight, 3.0), (InteractionOutcome::Chat, 0.0)].into_iter().collect(), ..Default::default() };
+    assert!((0..50).all(|_| config.choose_outcome(&mut rng) == InteractionOutcome::Fight));
+    let none = InteractionConfig { outcomes: BTreeMap::new(), ..Default::default() };
+    assert_eq!(none.choose_outcome(&mut rng), InteractionOutcome::Chat);
+}
+
+#[test]
+fn test_acquaintances_do_not_fight() {
+    let (mut world, mut resources, map) = setup(Map::new(16, 16));
+    let a = add_agent(&mut world, &map, only(InteractionOutcome::Fight), 5.0, 5.0);
+    let b = add_agent(&mut world, &map, only(InteractionOutcome::Fight), 6.0, 5.0);
+    let mut schedule = schedule();
+    run(&mut world, &mut resources, &mut schedule, 2);
+    assert!(state(&world, a).has_met(b));
+    run(&mut world, &mut resources, &mut schedule, 6);
+    let stats = resources.get::<InteractionStats>().unwrap();
+    assert_eq!(stats.outcomes.get(&InteractionOutcome::Fight), Some(&1));
+    assert_eq!(stats.outcomes.get(&InteractionOutcome::ShareInformation), Some(&1));
+}
+
+#[test]
+fn test_interaction_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Worker\"\n  type: \"worker\"\n  interaction:\n    cooldown_ticks: 50\n    outcomes:\n      chat: 2.0\n      fight: 0.5\n- name: \"Scout\"\n  type: \"scout\"\n");
+    let expected = InteractionConfig {
+        cooldown_ticks: 50,
+        outcomes: [(InteractionOutcome::Chat, 2.0), (InteractionOutcome::Fight, 0.5)].into_iter().collect(),
+        ..Default::default()
+    };
+    assert_eq!(types[0].interaction, expected);
+    assert_eq!(types[1].interaction, InteractionConfig::default());
+}
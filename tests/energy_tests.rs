@@ -0,0 +1,72 @@
+//! Energy: movement drains it by terrain, tired agents rest, resting restores it.
+
+use community_sim::agent::{AgentState, AgentType, Energy};
+use community_sim::agent::energy::{movement_energy, rest_system};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::{action_selection_system, spawn_agent};
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::map::{Map, Terrain};
+use community_sim::sim_rng::SimRng;
+use legion::{IntoQuery, Resources, Schedule, World};
+
+fn grass_map() -> Map {
+    Map::from_tiles(10, 10, vec![vec![Terrain::Grass; 10]; 10])
+}
+
+/// One agent of `agent_type` at (5, 5) with `energy` left.
+fn world_with_agent(agent_type: AgentType, energy: f32) -> (World, Resources) {
+    let map = grass_map();
+    let mut world = World::default();
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(1));
+    let mut agent_event_log = AgentEventLog::default();
+    let mut rng = rand::thread_rng();
+    let entity = spawn_agent(&mut world, Position { x: 5.0, y: 5.0 }, agent_type, &map, &mut agent_event_log, &mut rng);
+    world.entry(entity).unwrap().get_component_mut::<Energy>().unwrap().value = energy;
+    (world, resources)
+}
+
+fn agent_status(world: &World) -> (f32, AgentState) {
+    let (energy, state) = <(&Energy, &AgentState)>::query().iter(world).next().unwrap();
+    (energy.value, *state)
+}
+
+#[test]
+fn test_movement_energy_scales_with_terrain() {
+    let mut map = grass_map();
+    map.tiles[2][3] = Terrain::Forest;
+    let grass = movement_energy(&map, 1.0, 1.0, 1.0);
+    let forest = movement_energy(&map, 3.5, 2.5, 1.0);
+    assert!(grass > 0.0);
+    assert_eq!(forest, grass * 2.0);
+}
+
+#[test]
+fn test_agents_spawn_with_stamina_as_energy() {
+    let (world, _) = world_with_agent(AgentType { stamina: 40.0, ..Default::default() }, 40.0);
+    assert_eq!(agent_status(&world), (40.0, AgentState::Idle));
+}
+
+#[test]
+fn test_tired_agent_rests_until_recovered() {
+    let (mut world, mut resources) = world_with_agent(AgentType { stamina: 100.0, ..Default::default() }, 5.0);
+    let mut schedule = Schedule::builder()
+        .add_system(action_selection_system())
+        .add_system(rest_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    let (energy, state) = agent_status(&world);
+    assert_eq!(state, AgentState::Resting);
+    assert!(energy > 5.0);
+
+    for _ in 0..100 {
+        schedule.execute(&mut world, &mut resources);
+        if agent_status(&world).1 != AgentState::Resting {
+            break;
+        }
+    }
+    let (energy, state) = agent_status(&world);
+    assert_ne!(state, AgentState::Resting);
+    assert!(energy >= 90.0);
+}
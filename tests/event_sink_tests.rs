@@ -0,0 +1,112 @@
+//! `--events-out`: every AgentEvent is written with its tick and a stable entity id.
+
+use community_sim::agent::{AgentType, components::{MovementProfile, MovementEffect, DecisionEngineConfig}};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::event_sink::{EventSink, CSV_HEADER};
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::ecs_simulation::{build_simulation_schedule_profiled, simulation_tick};
+use community_sim::map::Map;
+use community_sim::sim_rng::{RngStream, SimRng};
+use rand::Rng;
+
+fn run_with_sink(seed: u64, path: &std::path::Path, ticks: usize) -> String {
+    let agent_type = AgentType {
+        name: "worker".to_string(),
+        color: (0, 0, 255),
+        movement_profile: MovementProfile { speed: 1.0, effect: MovementEffect::None },
+        decision_engine: DecisionEngineConfig::Simple,
+        hunger_rate: 0.1,
+        hunger_threshold: 10.0,
+        ..Default::default()
+    };
+    let mut sim_rng = SimRng::new(seed);
+    let map = Map::new_with_seed(30, 30, sim_rng.stream(RngStream::Map).gen());
+    let mut world = legion::World::default();
+    let mut resources = legion::Resources::default();
+    let mut agent_event_log = AgentEventLog::default();
+    let rng = sim_rng.stream(RngStream::Spawn);
+    let mut spawned = 0;
+    while spawned < 15 {
+        let (x, y) = (rng.gen_range(0..map.width) as f32, rng.gen_range(0..map.height) as f32);
+        if map.is_passable(x as i32, y as i32, None) {
+            spawn_agent(&mut world, Position { x, y }, agent_type.clone(), &map, &mut agent_event_log, rng);
+            spawned += 1;
+        }
+    }
+    insert_standard_resources(&mut resources, &map, sim_rng);
+    resources.insert(agent_event_log);
+    resources.insert(EventSink::create(path, 0).unwrap());
+    let mut schedule = build_simulation_schedule_profiled();
+    for _ in 0..ticks {
+        simulation_tick(&mut world, &mut resources, &mut schedule);
+    }
+    drop(resources);
+    let contents = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).ok();
+    contents
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("community_sim_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn test_jsonl_events_have_tick_and_stable_ids() {
+    let contents = run_with_sink(5, &temp_path("events.jsonl"), 40);
+    let records: Vec<serde_json::Value> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    let spawned: Vec<_> = records.iter().filter(|r| r["event"] == "Spawned").collect();
+    assert_eq!(spawned.len(), 15);
+    // Agents are numbered in spawn order, all at tick 0.
+    for (i, record) in spawned.iter().enumerate() {
+        assert_eq!(record["agent"], i as u64);
+        assert_eq!(record["tick"], 0);
+    }
+    assert!(records.windows(2).all(|w| w[0]["tick"].as_u64() <= w[1]["tick"].as_u64()));
+}
+
+#[test]
+fn test_same_seed_gives_identical_event_files() {
+    let a = run_with_sink(9, &temp_path("a.jsonl"), 40);
+    let b = run_with_sink(9, &temp_path("b.jsonl"), 40);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_csv_event_file_has_header() {
+    let contents = run_with_sink(5, &temp_path("events.csv"), 5);
+    let mut lines = contents.lines();
+    assert_eq!(lines.next(), Some(CSV_HEADER));
+    let first = lines.next().unwrap();
+    assert!(first.starts_with("0,Spawned,0,"), "unexpected first row: {}", first);
+}
+
+#[test]
+fn test_died_record_has_cause_and_age() {
+    use community_sim::agent::event::{AgentEvent, DeathCause};
+    use community_sim::agent::event_sink::{EventRecord, StableIds};
+    let mut world = legion::World::default();
+    let agent = world.push((Position { x: 1.0, y: 2.0 },));
+    let mut ids = StableIds::default();
+    let event = AgentEvent::Died { agent, pos: (1.0, 2.0), cause: DeathCause::Drowning, age: 42 };
+    let record = EventRecord::from_event(&event, 7, &mut ids);
+    assert_eq!(record.to_csv_row(), "7,Died,0,,,1,2,,,,,,,drowning,42,");
+    // The dead agent's id is released.
+    assert_eq!(ids.get(agent), None);
+}
+
+#[test]
+fn test_picked_up_record_releases_the_food_id() {
+    use community_sim::agent::event::AgentEvent;
+    use community_sim::agent::event_sink::{EventRecord, StableIds};
+    let mut world = legion::World::default();
+    let agent = world.push((Position { x: 1.0, y: 2.0 },));
+    let food = world.push((Position { x: 1.0, y: 2.0 },));
+    let mut ids = StableIds::default();
+    let event = AgentEvent::PickedUpFood { agent, food };
+    let record = EventRecord::from_event(&event, 3, &mut ids);
+    assert_eq!(record.to_csv_row(), "3,PickedUpFood,0,1,,,,,,,,,,,,");
+    assert_eq!(ids.get(food), None);
+    assert_eq!(ids.get(agent), Some(0));
+}
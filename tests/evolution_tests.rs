@@ -0,0 +1,75 @@
+//! Neuroevolution: mutation, selection, YAML output and a short end-to-end run.
+
+use community_sim::agent::{AgentType, DecisionEngineConfig, MLPConfig};
+use community_sim::agent::decision::OBSERVATION_SIZE;
+use community_sim::evolution::{next_generation, run_evolution, EvolutionConfig};
+use community_sim::util::{agent_types_to_yaml, load_agent_types};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("community_sim_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_mutation_respects_rate() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let brain = MLPConfig::random(OBSERVATION_SIZE, &[4], 4, &mut rng);
+    assert!(brain.validate().is_ok());
+
+    let mut unchanged = brain.clone();
+    unchanged.mutate(&mut rng, 0.0, 1.0);
+    assert_eq!(unchanged, brain);
+
+    let mut changed = brain.clone();
+    changed.mutate(&mut rng, 1.0, 1.0);
+    assert!(changed.validate().is_ok());
+    assert_ne!(changed.weights, brain.weights);
+    assert_ne!(changed.biases, brain.biases);
+}
+
+#[test]
+fn test_next_generation_keeps_the_best_brain() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    let brains: Vec<MLPConfig> = (0..5).map(|_| MLPConfig::random(OBSERVATION_SIZE, &[], 4, &mut rng)).collect();
+    let scores = [1.0, 7.0, 3.0, 0.0, 2.0];
+    let next = next_generation(&brains, &scores, &mut rng);
+    assert_eq!(next.len(), brains.len());
+    assert_eq!(next[0], brains[1]);
+}
+
+#[test]
+fn test_agent_types_yaml_round_trip() {
+    let mut agent_types = load_agent_types("config/agent_types.yaml");
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    agent_types[0].decision_engine = DecisionEngineConfig::MLP(MLPConfig::random(OBSERVATION_SIZE, &[3], 4, &mut rng));
+    let dir = temp_dir("yaml_round_trip");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("types.yaml");
+    std::fs::write(&path, agent_types_to_yaml(&agent_types)).unwrap();
+    let loaded: Vec<AgentType> = load_agent_types(path.to_str().unwrap());
+    assert_eq!(loaded, agent_types);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_evolve_writes_fitness_and_loadable_brains() {
+    let dir = temp_dir("evolve");
+    let config = EvolutionConfig { generations: 2, map_size: 20, population: 6, ticks: 30, out_dir: dir.clone() };
+    let agent_types = load_agent_types("config/agent_types.yaml");
+    let history = run_evolution(&config, &agent_types, Some(11)).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|g| g.best >= g.mean && g.mean >= g.worst));
+
+    let csv = std::fs::read_to_string(dir.join("fitness.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    let best = load_agent_types(dir.join("best_gen001.yaml").to_str().unwrap());
+    assert!(matches!(&best[0].decision_engine, DecisionEngineConfig::MLP(c) if c.input_size == OBSERVATION_SIZE));
+
+    // Same seed, same results.
+    let again = run_evolution(&config, &agent_types, Some(11)).unwrap();
+    assert_eq!(again, history);
+    let _ = std::fs::remove_dir_all(&dir);
+}
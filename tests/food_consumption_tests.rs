@@ -0,0 +1,73 @@
+//! Eating applies nutrition to Hunger: digestion efficiency, the hunger cap and overeating.
+
+use community_sim::agent::{AgentType, Hunger};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::{entity_interaction_system, Position};
+use community_sim::food::Food;
+use community_sim::map::{Map, Terrain};
+use community_sim::sim_rng::SimRng;
+use community_sim::spatial::spatial_index_system;
+use legion::{IntoQuery, Resources, Schedule, World};
+
+fn grass_map() -> Map {
+    Map::from_tiles(10, 10, vec![vec![Terrain::Grass; 10]; 10])
+}
+
+/// Spawns one agent per hunger value at (5, 5), one food item of `nutrition` there,
+/// rebuilds the spatial index, runs the interaction system once and returns the agents' hunger and remaining food count.
+fn eat_once(agent_type: AgentType, hungers: &[f32], nutrition: f32) -> (Vec<f32>, usize) {
+    let map = grass_map();
+    let mut world = World::default();
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(1));
+    let mut agent_event_log = AgentEventLog::default();
+    let mut rng = rand::thread_rng();
+    for &value in hungers {
+        let entity = spawn_agent(&mut world, Position { x: 5.0, y: 5.0 }, agent_type.clone(), &map, &mut agent_event_log, &mut rng);
+        world.entry(entity).unwrap().get_component_mut::<Hunger>().unwrap().value = value;
+    }
+    world.push((Position { x: 5.0, y: 5.0 }, Food::new(nutrition)));
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    let hunger = <&Hunger>::query().iter(&world).map(|h| h.value).collect();
+    let food_left = <&Food>::query().iter(&world).count();
+    (hunger, food_left)
+}
+
+#[test]
+fn test_eating_applies_digestion_efficiency() {
+    let agent_type = AgentType { digestion_efficiency: 0.5, ..Default::default() };
+    let (hunger, food_left) = eat_once(agent_type, &[40.0], 10.0);
+    assert_eq!(hunger, vec![45.0]);
+    assert_eq!(food_left, 0);
+}
+
+#[test]
+fn test_eating_is_capped() {
+    let agent_type = AgentType { hunger_cap: 100.0, ..Default::default() };
+    let (hunger, _) = eat_once(agent_type, &[95.0], 10.0);
+    assert_eq!(hunger, vec![100.0]);
+}
+
+#[test]
+fn test_full_agent_leaves_food_unless_overeating() {
+    let (hunger, food_left) = eat_once(AgentType::default(), &[100.0], 10.0);
+    assert_eq!((hunger, food_left), (vec![100.0], 1));
+
+    let overeater = AgentType { overeating: true, ..Default::default() };
+    let (hunger, food_left) = eat_once(overeater, &[100.0], 10.0);
+    assert_eq!((hunger, food_left), (vec![100.0], 0));
+}
+
+#[test]
+fn test_one_food_item_feeds_one_agent() {
+    let (mut hunger, food_left) = eat_once(AgentType::default(), &[20.0, 20.0], 10.0);
+    hunger.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(hunger, vec![20.0, 30.0]);
+    assert_eq!(food_left, 0);
+}
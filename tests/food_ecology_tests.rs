@@ -0,0 +1,164 @@
+//! Food ecology: terrain biomass that regrows, depletes when harvested and gates spawning,
+//! food rotting, and the YAML config.
+
+mod common;
+
+use community_sim::agent::{AgentType, Hunger};
+use community_sim::ecs_components::{entity_interaction_system, FoodStats, Position};
+use community_sim::food::{Food, FoodKind, PendingFoodSpawns};
+use community_sim::food::ecology::{food_decay_system, FoodAge, FoodConfig, FoodEcology};
+use community_sim::food::systems::{collect_food_spawn_positions_system, food_spawn_apply_system};
+use community_sim::map::{Map, Terrain};
+use community_sim::snapshot::Snapshot;
+use community_sim::spatial::spatial_index_system;
+use legion::{IntoQuery, Resources, Schedule, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use common::add_agent;
+
+/// Grass on the left half, forest at the top right, mountains at the bottom right.
+fn mixed_map() -> Map {
+    let tiles = (0..10)
+        .map(|y| (0..10).map(|x| if x < 5 { Terrain::Grass } else if y < 5 { Terrain::Forest } else { Terrain::Mountain }).collect())
+        .collect();
+    Map::from_tiles(10, 10, tiles)
+}
+
+fn setup(config: FoodConfig) -> (World, Resources, Map) {
+    let (world, mut resources, map) = common::setup(mixed_map());
+    resources.insert(FoodEcology::new(config));
+    (world, resources, map)
+}
+
+#[test]
+fn test_biomass_depletes_and_regrows_by_terrain() {
+    let map = mixed_map();
+    let mut ecology = FoodEcology::default();
+    assert_eq!((ecology.biomass(&map, 1, 1), ecology.biomass(&map, 7, 1), ecology.biomass(&map, 7, 7)), (1.0, 1.0, 0.0));
+
+    ecology.deplete(1, 1);
+    ecology.deplete(7, 1);
+    ecology.deplete(7, 1);
+    assert_eq!((ecology.biomass(&map, 1, 1), ecology.biomass(&map, 7, 1)), (0.5, 0.0));
+
+    // Grass regrows 0.005 a tick, forest 0.002.
+    for _ in 0..50 {
+        ecology.regrow(&map);
+    }
+    assert!((ecology.biomass(&map, 1, 1) - 0.75).abs() < 1e-4);
+    assert!((ecology.biomass(&map, 7, 1) - 0.1).abs() < 1e-4);
+    for _ in 0..51 {
+        ecology.regrow(&map);
+    }
+    assert_eq!(ecology.biomass(&map, 1, 1), 1.0);
+    assert_eq!(ecology.depleted.len(), 1);
+
+    let mut rng = StdRng::seed_from_u64(3);
+    let fish = ecology.grow(Terrain::Water, &mut rng).unwrap();
+    assert_eq!(fish.kind, FoodKind::Fish);
+    assert!((8.0..=14.0).contains(&fish.nutrition));
+    assert_eq!(ecology.grow(Terrain::Mountain, &mut rng), None);
+}
+
+#[test]
+fn test_food_spawns_only_where_it_grows() {
+    let config = FoodConfig { min_spawn_attempts: 200, ..Default::default() };
+    let (mut world, mut resources, map) = setup(config);
+    let mut schedule = Schedule::builder()
+        .add_system(collect_food_spawn_positions_system())
+        .add_system(food_spawn_apply_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    let food: Vec<(Position, Food)> = <(&Position, &Food)>::query().iter(&world).map(|(p, f)| (*p, *f)).collect();
+    assert_eq!(food.len(), 200);
+    assert_eq!(resources.get::<FoodStats>().unwrap().spawned_per_tick, 200);
+    for (pos, item) in &food {
+        match (map.terrain_at(pos.x, pos.y), item.kind) {
+            (Terrain::Grass, FoodKind::Grain) => assert!((5.0..=10.0).contains(&item.nutrition)),
+            (Terrain::Forest, FoodKind::Berries) => assert!((6.0..=12.0).contains(&item.nutrition)),
+            (Terrain::Grass | Terrain::Forest, FoodKind::Meat) => assert!((12.0..=20.0).contains(&item.nutrition)),
+            (terrain, kind) => panic!("{:?} spawned on {:?}", kind, terrain),
+        }
+    }
+    // Meat is a tenth as common as grain or berries.
+    let meat = food.iter().filter(|(_, f)| f.kind == FoodKind::Meat).count();
+    assert!(meat > 0 && meat < 50, "{} meat", meat);
+
+    // Barren land bears nothing.
+    let mut ecology = resources.get_mut::<FoodEcology>().unwrap();
+    for y in 0..10 {
+        for x in 0..10 {
+            ecology.deplete(x, y);
+            ecology.deplete(x, y);
+        }
+    }
+    ecology.config.regrowth.clear();
+    drop(ecology);
+    Schedule::builder().add_system(collect_food_spawn_positions_system()).build().execute(&mut world, &mut resources);
+    assert!(resources.get::<PendingFoodSpawns>().unwrap().0.is_empty());
+}
+
+#[test]
+fn test_eating_depletes_the_tile() {
+    let (mut world, mut resources, map) = setup(FoodConfig::default());
+    let agent = add_agent(&mut world, &map, AgentType::default(), 2.0, 3.0);
+    world.entry(agent).unwrap().get_component_mut::<Hunger>().unwrap().value = 20.0;
+    world.push((Position { x: 2.0, y: 3.0 }, Food { nutrition: 10.0, kind: FoodKind::Grain }));
+    Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .build()
+        .execute(&mut world, &mut resources);
+    assert_eq!(<&Food>::query().iter(&world).count(), 0);
+    assert_eq!(resources.get::<FoodEcology>().unwrap().biomass(&map, 2, 3), 0.5);
+}
+
+#[test]
+fn test_uneaten_food_rots() {
+    let mut config = FoodConfig::default();
+    config.kinds.get_mut(&FoodKind::Grain).unwrap().lifetime = 3;
+    let (mut world, mut resources, _) = setup(config);
+    world.push((Position { x: 1.0, y: 1.0 }, Food { nutrition: 5.0, kind: FoodKind::Grain }));
+    let mut schedule = Schedule::builder().add_system(food_decay_system()).build();
+    for _ in 0..3 {
+        schedule.execute(&mut world, &mut resources);
+    }
+    assert_eq!(<&FoodAge>::query().iter(&world).copied().collect::<Vec<_>>(), vec![FoodAge(2)]);
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(<&Food>::query().iter(&world).count(), 0);
+    assert_eq!(resources.get::<FoodStats>().unwrap().rotted, 1);
+
+    // Lifetime 0: food keeps forever.
+    resources.get_mut::<FoodEcology>().unwrap().config.kinds.get_mut(&FoodKind::Grain).unwrap().lifetime = 0;
+    world.push((Position { x: 1.0, y: 1.0 }, Food { nutrition: 5.0, kind: FoodKind::Grain }, FoodAge(5000)));
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(<&Food>::query().iter(&world).count(), 1);
+}
+
+#[test]
+fn test_biomass_and_food_age_survive_snapshots() {
+    let (mut world, resources, _) = setup(FoodConfig::default());
+    world.push((Position { x: 1.0, y: 1.0 }, Food { nutrition: 5.0, kind: FoodKind::Grain }, FoodAge(42)));
+    resources.get_mut::<FoodEcology>().unwrap().deplete(1, 1);
+    let snapshot = Snapshot::capture(&world, &resources, "ecology", 10);
+    let (mut restored, mut restored_resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut restored, &mut restored_resources);
+    assert_eq!(<&FoodAge>::query().iter(&restored).copied().collect::<Vec<_>>(), vec![FoodAge(42)]);
+    assert_eq!(restored_resources.get::<FoodEcology>().unwrap().depleted.get(&(1, 1)), Some(&0.5));
+}
+
+#[test]
+fn test_food_config_yaml() {
+    assert_eq!(FoodConfig::load("no/such/food.yaml"), FoodConfig::default());
+    let path = std::env::temp_dir().join(format!("food_{}.yaml", std::process::id()));
+    std::fs::write(&path, "min_spawn_attempts: 5\nkinds:\n  meat:\n    terrain: [Mountain]\n    nutrition: [2.0, 3.0]\n").unwrap();
+    let config = FoodConfig::load(&path);
+    std::fs::write(&path, "min_spawn_attempts: [oops]\n").unwrap();
+    let invalid = FoodConfig::load(&path);
+    std::fs::remove_file(&path).ok();
+    assert_eq!((config.min_spawn_attempts, config.harvest_depletion, config.regrowth.len()), (5, 0.5, 3));
+    assert_eq!(config.kinds.keys().copied().collect::<Vec<_>>(), vec![FoodKind::Meat]);
+    assert_eq!((config.kinds[&FoodKind::Meat].lifetime, config.kinds[&FoodKind::Meat].abundance), (0, 1.0));
+    assert_eq!(invalid, FoodConfig::default());
+    assert_eq!(serde_yaml::from_str::<FoodConfig>(&std::fs::read_to_string("config/food.yaml").unwrap()).unwrap(), FoodConfig::default());
+}
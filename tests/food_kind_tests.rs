@@ -0,0 +1,125 @@
+//! Food kinds: diets that prefer or refuse kinds, fishing from the shore, and how each
+//! kind is drawn.
+
+mod common;
+
+use community_sim::agent::{AgentState, AgentType, Hunger};
+use community_sim::agent::systems::action_selection_system;
+use community_sim::ecs_components::{entity_interaction_system, Position};
+use community_sim::food::{Diet, Food, FoodClaims, FoodKind};
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::Target;
+use community_sim::render_ascii::render_simulation_ascii;
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Resources, Schedule, World};
+use common::{setup, yaml_round_trip};
+
+/// Grass with a lake over x >= 10 (3 tiles wide, 20 high), then grass again.
+fn lake_map() -> Map {
+    let tiles = (0..20).map(|_| (0..20).map(|x| if (10..13).contains(&x) { Terrain::Water } else { Terrain::Grass }).collect()).collect();
+    Map::from_tiles(20, 20, tiles)
+}
+
+/// A hungry agent that sees 8 tiles and never wanders.
+fn add_agent(world: &mut World, map: &Map, diet: Diet, x: f32, y: f32) -> Entity {
+    let agent_type = AgentType { vision: 8.0, move_probability: 0.0, diet, ..Default::default() };
+    let entity = common::add_agent(world, map, agent_type, x, y);
+    world.entry(entity).unwrap().get_component_mut::<Hunger>().unwrap().value = 10.0;
+    entity
+}
+
+fn add_food(world: &mut World, kind: FoodKind, x: f32, y: f32) -> Entity {
+    world.push((Position { x, y }, Food { nutrition: 10.0, kind }))
+}
+
+fn decide(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(action_selection_system())
+        .build();
+    schedule.execute(world, resources);
+}
+
+fn interact(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .build();
+    schedule.execute(world, resources);
+}
+
+fn target_of(world: &World, agent: Entity) -> (f32, f32, AgentState) {
+    let entry = world.entry_ref(agent).unwrap();
+    let target = entry.get_component::<Target>().unwrap();
+    (target.x, target.y, *entry.get_component::<AgentState>().unwrap())
+}
+
+#[test]
+fn test_diet_prefers_and_avoids_kinds() {
+    let (mut world, mut resources, map) = setup(lake_map());
+    add_food(&mut world, FoodKind::Grain, 4.0, 5.0);
+    let meat = add_food(&mut world, FoodKind::Meat, 2.0, 9.0);
+    let hunter = add_agent(&mut world, &map, Diet { prefers: vec![FoodKind::Meat], avoids: vec![] }, 2.0, 5.0);
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, hunter), (2.0, 9.0, AgentState::Moving));
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(hunter), Some(meat));
+
+    // A vegetarian on the meat's tile walks past it to the grain.
+    let vegetarian = add_agent(&mut world, &map, Diet { prefers: vec![], avoids: vec![FoodKind::Meat] }, 2.0, 9.0);
+    resources.get_mut::<FoodClaims>().unwrap().release_agent(hunter);
+    interact(&mut world, &mut resources);
+    decide(&mut world, &mut resources);
+    assert_eq!(<&Food>::query().iter(&world).count(), 2);
+    assert_eq!(target_of(&world, vegetarian), (4.0, 5.0, AgentState::Moving));
+}
+
+#[test]
+fn test_fish_are_caught_from_the_shore() {
+    let (mut world, mut resources, map) = setup(lake_map());
+    let fish = add_food(&mut world, FoodKind::Fish, 10.0, 5.0);
+    let agent = add_agent(&mut world, &map, Diet::default(), 5.0, 5.0);
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, agent), (9.0, 5.0, AgentState::Moving));
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(agent), Some(fish));
+
+    // From the shore it catches the fish; one further out is out of reach.
+    world.entry(agent).unwrap().get_component_mut::<Position>().unwrap().x = 9.0;
+    let far_fish = add_food(&mut world, FoodKind::Fish, 11.0, 5.0);
+    interact(&mut world, &mut resources);
+    let left: Vec<Entity> = <(Entity, &Food)>::query().iter(&world).map(|(e, _)| *e).collect();
+    assert_eq!(left, vec![far_fish]);
+}
+
+#[test]
+fn test_fish_out_of_reach_of_the_shore_are_ignored() {
+    let (mut world, mut resources, map) = setup(lake_map());
+    add_food(&mut world, FoodKind::Fish, 11.0, 5.0);
+    // Berries on land stay out of reach on the water's tile next to them.
+    add_food(&mut world, FoodKind::Berries, 10.0, 9.0);
+    let agent = add_agent(&mut world, &map, Diet::default(), 6.0, 5.0);
+    decide(&mut world, &mut resources);
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(agent), None);
+}
+
+#[test]
+fn test_each_kind_is_drawn_distinctly() {
+    let (mut world, _, map) = setup(lake_map());
+    for (i, kind) in FoodKind::ALL.into_iter().enumerate() {
+        add_food(&mut world, kind, i as f32, 0.0);
+    }
+    add_food(&mut world, FoodKind::Grain, 5.0, 0.0);
+    add_agent(&mut world, &map, Diet::default(), 5.0, 0.0);
+    let ascii = render_simulation_ascii(&world, &map);
+    assert!(ascii.lines().next().unwrap().starts_with("*,%&.A"));
+    let colors: std::collections::HashSet<_> = FoodKind::ALL.iter().map(|k| k.color()).collect();
+    assert_eq!(colors.len(), 4);
+}
+
+#[test]
+fn test_diet_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Farmer\"\n  type: \"farmer\"\n  diet:\n    prefers: [\"grain\"]\n    avoids: [\"meat\", \"fish\"]\n- name: \"Scout\"\n  type: \"scout\"\n");
+    let expected = Diet { prefers: vec![FoodKind::Grain], avoids: vec![FoodKind::Meat, FoodKind::Fish] };
+    assert_eq!(types[0].diet, expected);
+    assert!(types[1].diet.is_omnivore());
+    assert!(!expected.eats(FoodKind::Fish) && expected.eats(FoodKind::Berries) && expected.prefers(FoodKind::Grain));
+}
@@ -0,0 +1,162 @@
+//! Food seeking: hungry agents head for the nearest reachable, unclaimed food they can see.
+
+use community_sim::agent::{AgentState, AgentType, Hunger};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::{action_selection_system, spawn_agent};
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::food::{Food, FoodClaims};
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::Target;
+use community_sim::sim_rng::SimRng;
+use community_sim::snapshot::Snapshot;
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Resources, Schedule, World};
+
+fn grass_map() -> Map {
+    Map::from_tiles(30, 30, vec![vec![Terrain::Grass; 30]; 30])
+}
+
+/// Hungry, sees 8 tiles, never wanders.
+fn hungry_type() -> AgentType {
+    AgentType { vision: 8.0, move_probability: 0.0, hunger_threshold: 50.0, ..Default::default() }
+}
+
+fn setup(map: &Map) -> (World, Resources) {
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, map, SimRng::new(3));
+    (World::default(), resources)
+}
+
+fn add_agent(world: &mut World, map: &Map, x: f32, y: f32) -> Entity {
+    let mut rng = rand::thread_rng();
+    let entity = spawn_agent(world, Position { x, y }, hungry_type(), map, &mut AgentEventLog::default(), &mut rng);
+    world.entry(entity).unwrap().get_component_mut::<Hunger>().unwrap().value = 10.0;
+    entity
+}
+
+fn add_food(world: &mut World, x: f32, y: f32) -> Entity {
+    world.push((Position { x, y }, Food::new(5.0)))
+}
+
+fn decide(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(action_selection_system())
+        .build();
+    schedule.execute(world, resources);
+}
+
+fn target_of(world: &World, agent: Entity) -> (f32, f32, AgentState) {
+    let entry = world.entry_ref(agent).unwrap();
+    let target = entry.get_component::<Target>().unwrap();
+    (target.x, target.y, *entry.get_component::<AgentState>().unwrap())
+}
+
+#[test]
+fn test_seeks_nearest_food_not_first() {
+    let map = grass_map();
+    let (mut world, mut resources) = setup(&map);
+    add_food(&mut world, 16.5, 10.5);
+    let near = add_food(&mut world, 13.5, 10.5);
+    let agent = add_agent(&mut world, &map, 10.5, 10.5);
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, agent), (13.5, 10.5, AgentState::Moving));
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claimant(near), Some(agent));
+}
+
+#[test]
+fn test_ignores_food_beyond_vision() {
+    let map = grass_map();
+    let (mut world, mut resources) = setup(&map);
+    add_food(&mut world, 25.5, 25.5);
+    let agent = add_agent(&mut world, &map, 5.5, 5.5);
+    decide(&mut world, &mut resources);
+    let (x, y, state) = target_of(&world, agent);
+    assert_eq!(state, AgentState::Idle);
+    assert_ne!((x, y), (25.5, 25.5));
+    assert!(resources.get::<FoodClaims>().unwrap().is_empty());
+}
+
+#[test]
+fn test_skips_food_without_a_route() {
+    let mut map = grass_map();
+    // Food at (13, 10) on a grass tile ringed by water.
+    for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+        map.tiles[(10 + dy) as usize][(13 + dx) as usize] = Terrain::Water;
+    }
+    let (mut world, mut resources) = setup(&map);
+    add_food(&mut world, 13.5, 10.5);
+    add_food(&mut world, 10.5, 15.5);
+    let agent = add_agent(&mut world, &map, 10.5, 10.5);
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, agent), (10.5, 15.5, AgentState::Moving));
+}
+
+#[test]
+fn test_claimed_food_is_left_to_its_claimant() {
+    let map = grass_map();
+    let (mut world, mut resources) = setup(&map);
+    let food = add_food(&mut world, 15.5, 10.5);
+    let a = add_agent(&mut world, &map, 10.5, 10.5);
+    let b = add_agent(&mut world, &map, 20.5, 10.5);
+    decide(&mut world, &mut resources);
+    let claimant = resources.get::<FoodClaims>().unwrap().claimant(food).expect("food is claimed");
+    let other = if claimant == a { b } else { a };
+    assert_eq!(target_of(&world, claimant), (15.5, 10.5, AgentState::Moving));
+    assert_eq!(target_of(&world, other).2, AgentState::Idle);
+}
+
+#[test]
+fn test_food_claims() {
+    let mut world = World::default();
+    let (food1, food2) = (world.push((Food::new(1.0),)), world.push((Food::new(1.0),)));
+    let (a, b) = (world.push((0u8,)), world.push((0u8,)));
+    let mut claims = FoodClaims::default();
+    assert!(claims.claim(food1, a));
+    assert!(!claims.claim(food1, b));
+    assert!(claims.is_claimed_by_other(food1, b));
+    assert!(!claims.is_claimed_by_other(food1, a));
+    // A new claim replaces the agent's old one.
+    assert!(claims.claim(food2, a));
+    assert_eq!((claims.claimant(food1), claims.claim_of(a)), (None, Some(food2)));
+    claims.release_food(food2);
+    assert!(claims.is_empty());
+    assert!(claims.claim(food1, b));
+    claims.release_agent(b);
+    assert_eq!(claims.len(), 0);
+}
+
+#[test]
+fn test_eating_releases_the_claim() {
+    let map = grass_map();
+    let (mut world, mut resources) = setup(&map);
+    let food = add_food(&mut world, 12.5, 10.5);
+    let agent = add_agent(&mut world, &map, 10.5, 10.5);
+    let mut schedule = community_sim::ecs_simulation::build_simulation_schedule_profiled();
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claimant(food), Some(agent));
+    for _ in 0..10 {
+        if !world.contains(food) {
+            break;
+        }
+        schedule.execute(&mut world, &mut resources);
+    }
+    assert!(!world.contains(food), "food was eaten");
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claimant(food), None);
+}
+
+#[test]
+fn test_claims_survive_snapshots() {
+    let map = grass_map();
+    let (mut world, mut resources) = setup(&map);
+    add_agent(&mut world, &map, 10.5, 10.5);
+    add_food(&mut world, 13.5, 10.5);
+    decide(&mut world, &mut resources);
+    let snapshot = Snapshot::capture(&world, &resources, "claims", 1);
+    let (mut world, mut resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut world, &mut resources);
+    let agent = *<(Entity, &AgentType)>::query().iter(&world).next().unwrap().0;
+    let food = *<(Entity, &Food)>::query().iter(&world).next().unwrap().0;
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(agent), Some(food));
+}
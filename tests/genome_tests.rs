@@ -0,0 +1,132 @@
+//! Genomes: per-agent trait values drawn around the type's, inherited with crossover and
+//! mutation, expressed in the agent's own components, and summarised as histograms.
+
+mod common;
+
+use community_sim::agent::{AgentType, Hunger};
+use community_sim::agent::components::SwimmingProfile;
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::genome::{trait_csv_header, trait_histograms, GeneticsConfig, Genome, TraitHistogram, TRAITS, TRAIT_BINS};
+use community_sim::agent::reproduction::{reproduction_system, ReproductionConfig};
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs_components::{entity_interaction_system, Position};
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Schedule, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use common::{add_agent, grass, setup, yaml_round_trip};
+
+fn varied(variance: f32) -> AgentType {
+    AgentType {
+        move_probability: 0.0,
+        genetics: GeneticsConfig { variance, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_sample_spreads_within_variance() {
+    let base = AgentType { vision: 10.0, ..varied(0.2) };
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..100 {
+        let genome = Genome::sample(&base, 20.0, &mut rng);
+        assert!((8.0..=12.0).contains(&genome.vision), "vision {}", genome.vision);
+        assert!((16.0..=24.0).contains(&genome.swim_chance));
+    }
+    // Without variance every agent gets the type's values.
+    let plain = varied(0.0);
+    assert_eq!(Genome::sample(&plain, 20.0, &mut rng), Genome::from_type(&plain, 20.0));
+}
+
+#[test]
+fn test_crossover_and_mutation() {
+    let a = Genome::from_values([1.0, 0.01, 40.0, 4.0, 10.0]);
+    let b = Genome::from_values([2.0, 0.02, 60.0, 8.0, 20.0]);
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..20 {
+        let child = a.crossover(&b, &mut rng);
+        for (i, v) in child.values().into_iter().enumerate() {
+            assert!(v == a.values()[i] || v == b.values()[i]);
+        }
+    }
+    let mut unchanged = a.clone();
+    unchanged.mutate(&GeneticsConfig { mutation_rate: 0.0, ..Default::default() }, &mut rng);
+    assert_eq!(unchanged, a);
+    let mut mutated = a.clone();
+    mutated.mutate(&GeneticsConfig { mutation_rate: 1.0, mutation_scale: 0.5, ..Default::default() }, &mut rng);
+    assert_ne!(mutated, a);
+    for (m, v) in mutated.values().into_iter().zip(a.values()) {
+        assert!((m - v).abs() <= v * 0.5 + 1e-4);
+    }
+}
+
+#[test]
+fn test_spawned_agents_express_their_genome() {
+    let (mut world, _, map) = setup(grass(10));
+    let agent = add_agent(&mut world, &map, varied(0.5), 5.0, 5.0);
+    let entry = world.entry_ref(agent).unwrap();
+    let genome = entry.get_component::<Genome>().unwrap();
+    let agent_type = entry.get_component::<AgentType>().unwrap();
+    assert_ne!(genome, &Genome::from_type(&varied(0.5), genome.swim_chance));
+    assert_eq!(agent_type.movement_profile.speed, genome.speed);
+    assert_eq!(agent_type.hunger_rate, genome.hunger_rate);
+    assert_eq!(agent_type.vision, genome.vision);
+    assert_eq!(entry.get_component::<Hunger>().unwrap().threshold, genome.hunger_threshold);
+    assert_eq!(entry.get_component::<SwimmingProfile>().unwrap().swim_chance_percent, genome.swim_chance_percent());
+}
+
+#[test]
+fn test_newborn_inherits_from_parents() {
+    let (mut world, mut resources, map) = setup(grass(10));
+    let breeder = AgentType {
+        reproduction: Some(ReproductionConfig { chance: 1.0, gestation_ticks: 1, ..Default::default() }),
+        genetics: GeneticsConfig { variance: 0.5, mutation_rate: 0.0, ..Default::default() },
+        ..varied(0.5)
+    };
+    // One RNG for both parents, so their genomes differ.
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut add = |world: &mut World, x: f32| spawn_agent(world, Position { x, y: 5.0 }, breeder.clone(), &map, &mut AgentEventLog::default(), &mut rng);
+    let (mother, father) = (add(&mut world, 5.0), add(&mut world, 5.5));
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .add_system(reproduction_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    schedule.execute(&mut world, &mut resources);
+
+    let genome = |e: Entity| world.entry_ref(e).unwrap().get_component::<Genome>().unwrap().clone();
+    let (m, f) = (genome(mother), genome(father));
+    let child = <(Entity, &Genome)>::query().iter(&world).map(|(e, _)| *e).find(|e| *e != mother && *e != father).expect("child born");
+    let c = genome(child);
+    for i in 0..TRAITS.len() {
+        assert!(c.values()[i] == m.values()[i] || c.values()[i] == f.values()[i]);
+    }
+    let child_type = world.entry_ref(child).unwrap().get_component::<AgentType>().unwrap().clone();
+    assert_eq!(child_type.vision, c.vision);
+}
+
+#[test]
+fn test_trait_histograms() {
+    let h = TraitHistogram::from_values(&[0.0, 1.0, 1.0, 10.0]);
+    assert_eq!((h.count, h.mean, h.min, h.max), (4, 3.0, 0.0, 10.0));
+    assert_eq!(h.bins.iter().sum::<usize>(), 4);
+    assert_eq!((h.bins[0], h.bins[1], h.bins[TRAIT_BINS - 1]), (1, 2, 1));
+
+    let (mut world, _, map) = setup(grass(10));
+    for (i, name) in ["b", "a", "b"].into_iter().enumerate() {
+        add_agent(&mut world, &map, AgentType { name: name.to_string(), ..varied(0.1) }, i as f32, 1.0);
+    }
+    let rows = trait_histograms(&world);
+    assert_eq!(rows.len(), 2 * TRAITS.len());
+    assert_eq!((rows[0].0.as_str(), rows[0].1, rows[0].2.count), ("a", "speed", 1));
+    assert_eq!((rows[TRAITS.len()].0.as_str(), rows[TRAITS.len()].2.count), ("b", 2));
+    assert!(trait_csv_header().starts_with("tick,agent_type,trait,count,mean,min,max,bin0"));
+}
+
+#[test]
+fn test_genetics_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Worker\"\n  type: \"worker\"\n  genetics:\n    variance: 0.15\n- name: \"Scout\"\n  type: \"scout\"\n");
+    assert_eq!(types[0].genetics, GeneticsConfig { variance: 0.15, ..Default::default() });
+    assert_eq!(types[1].genetics, GeneticsConfig::default());
+}
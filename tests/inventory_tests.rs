@@ -0,0 +1,175 @@
+//! Inventories and stockpiles: capacity by strength, gathering instead of eating, eating
+//! from the pack, depositing, withdrawing and starting stockpiles, and snapshots.
+
+mod common;
+
+use community_sim::agent::{AgentState, AgentType, Hunger};
+use community_sim::agent::event::{AgentEvent, AgentEventLog};
+use community_sim::agent::inventory::{inventory_system, Inventory, Stockpile};
+use community_sim::agent::systems::action_selection_system;
+use community_sim::ecs_components::{entity_interaction_system, FoodStats, Position};
+use community_sim::food::{Diet, Food, FoodClaims, FoodKind};
+use community_sim::map::Map;
+use community_sim::navigation::Target;
+use community_sim::render_ascii::render_simulation_ascii;
+use community_sim::snapshot::Snapshot;
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Resources, Schedule, World};
+use common::{grass, setup};
+
+/// A well-fed agent of `strength` that sees 8 tiles and never wanders.
+fn add_agent(world: &mut World, map: &Map, strength: f32, x: f32, y: f32) -> Entity {
+    let agent_type = AgentType { strength, vision: 8.0, move_probability: 0.0, ..Default::default() };
+    common::add_agent(world, map, agent_type, x, y)
+}
+
+fn food(kind: FoodKind, nutrition: f32) -> Food {
+    Food { nutrition, kind }
+}
+
+fn set_hunger(world: &mut World, agent: Entity, value: f32) {
+    world.entry(agent).unwrap().get_component_mut::<Hunger>().unwrap().value = value;
+}
+
+fn inventory_of(world: &World, agent: Entity) -> Inventory {
+    world.entry_ref(agent).unwrap().get_component::<Inventory>().unwrap().clone()
+}
+
+fn target_of(world: &World, agent: Entity) -> (f32, f32) {
+    let entry = world.entry_ref(agent).unwrap();
+    let target = entry.get_component::<Target>().unwrap();
+    (target.x, target.y)
+}
+
+fn decide(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(action_selection_system())
+        .build();
+    schedule.execute(world, resources);
+}
+
+fn interact(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .build();
+    schedule.execute(world, resources);
+}
+
+fn use_inventories(world: &mut World, resources: &mut Resources) {
+    let mut schedule = Schedule::builder().add_system(inventory_system()).build();
+    schedule.execute(world, resources);
+}
+
+#[test]
+fn test_capacity_scales_with_strength_and_meals_follow_the_diet() {
+    assert_eq!(Inventory::for_strength(5.0).capacity, 5);
+    assert_eq!(Inventory::for_strength(2.4).capacity, 2);
+    assert_eq!(Inventory::for_strength(0.2).capacity, 1);
+
+    let mut inventory = Inventory { items: vec![food(FoodKind::Meat, 20.0), food(FoodKind::Berries, 6.0), food(FoodKind::Grain, 9.0)], capacity: 3 };
+    assert!(!inventory.has_room());
+    assert_eq!(inventory.summary(), "3/3: berries 1, grain 1, meat 1");
+    let farmer = Diet { prefers: vec![FoodKind::Berries], avoids: vec![FoodKind::Meat] };
+    assert_eq!(inventory.take_meal(&farmer), Some(food(FoodKind::Berries, 6.0)));
+    assert_eq!(inventory.take_meal(&farmer), Some(food(FoodKind::Grain, 9.0)));
+    assert_eq!(inventory.take_meal(&farmer), None);
+    assert_eq!(inventory.take_meal(&Diet::default()), Some(food(FoodKind::Meat, 20.0)));
+    assert_eq!(Inventory::for_strength(2.0).summary(), "0/2: empty");
+}
+
+#[test]
+fn test_well_fed_agents_gather_food_instead_of_eating_it() {
+    let (mut world, mut resources, map) = setup(grass(30));
+    let agent = add_agent(&mut world, &map, 2.0, 5.0, 5.0);
+    set_hunger(&mut world, agent, 80.0);
+    let item = world.push((Position { x: 8.0, y: 5.0 }, food(FoodKind::Grain, 7.0)));
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, agent), (8.0, 5.0));
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(agent), Some(item));
+
+    world.entry(agent).unwrap().get_component_mut::<Position>().unwrap().x = 8.0;
+    interact(&mut world, &mut resources);
+    assert_eq!(<&Food>::query().iter(&world).count(), 0);
+    assert_eq!(inventory_of(&world, agent).items, vec![food(FoodKind::Grain, 7.0)]);
+    assert_eq!(world.entry_ref(agent).unwrap().get_component::<Hunger>().unwrap().value, 80.0);
+    assert!(resources.get::<AgentEventLog>().unwrap().0.iter().any(|e| matches!(e, AgentEvent::PickedUpFood { agent: a, food } if *a == agent && *food == item)));
+    let stats = resources.get::<FoodStats>().unwrap().clone();
+    assert_eq!((stats.picked_up, stats.collected_per_tick), (1, 0));
+}
+
+#[test]
+fn test_hungry_agents_eat_from_their_pack_then_the_stockpile() {
+    let (mut world, mut resources, map) = setup(grass(30));
+    let agent = add_agent(&mut world, &map, 2.0, 5.0, 5.0);
+    world.entry(agent).unwrap().get_component_mut::<Inventory>().unwrap().items.push(food(FoodKind::Berries, 10.0));
+    world.push((Position { x: 5.0, y: 5.0 }, Stockpile { items: vec![food(FoodKind::Fish, 12.0)] }));
+    set_hunger(&mut world, agent, 20.0);
+    use_inventories(&mut world, &mut resources);
+    assert_eq!(world.entry_ref(agent).unwrap().get_component::<Hunger>().unwrap().value, 30.0);
+    assert!(inventory_of(&world, agent).is_empty());
+
+    use_inventories(&mut world, &mut resources);
+    assert_eq!(world.entry_ref(agent).unwrap().get_component::<Hunger>().unwrap().value, 42.0);
+    assert!(<&Stockpile>::query().iter(&world).all(|s| s.items.is_empty()));
+    let stats = resources.get::<FoodStats>().unwrap().clone();
+    assert_eq!((stats.eaten_carried, stats.withdrawn, stats.collected_per_tick), (2, 1, 2));
+}
+
+#[test]
+fn test_full_packs_go_back_to_the_nearest_stockpile() {
+    let (mut world, mut resources, map) = setup(grass(30));
+    let agent = add_agent(&mut world, &map, 1.0, 5.0, 5.0);
+    world.entry(agent).unwrap().get_component_mut::<Inventory>().unwrap().items.push(food(FoodKind::Grain, 5.0));
+    world.push((Position { x: 12.0, y: 5.0 }, Stockpile::default()));
+    world.push((Position { x: 28.0, y: 28.0 }, Stockpile::default()));
+    world.push((Position { x: 6.0, y: 5.0 }, food(FoodKind::Grain, 5.0)));
+    decide(&mut world, &mut resources);
+    assert_eq!(target_of(&world, agent), (12.0, 5.0));
+    assert_eq!(resources.get::<FoodClaims>().unwrap().claim_of(agent), None);
+
+    // Once there, it empties its pack.
+    world.entry(agent).unwrap().get_component_mut::<Position>().unwrap().x = 12.0;
+    use_inventories(&mut world, &mut resources);
+    assert!(inventory_of(&world, agent).is_empty());
+    let stored: Vec<(Position, usize)> = <(&Position, &Stockpile)>::query().iter(&world).map(|(p, s)| (*p, s.items.len())).collect();
+    assert_eq!(stored, vec![(Position { x: 12.0, y: 5.0 }, 1), (Position { x: 28.0, y: 28.0 }, 0)]);
+}
+
+#[test]
+fn test_a_full_pack_far_from_any_stockpile_starts_one() {
+    let (mut world, mut resources, map) = setup(grass(30));
+    let agent = add_agent(&mut world, &map, 2.0, 3.4, 4.0);
+    world.entry(agent).unwrap().get_component_mut::<Inventory>().unwrap().items = vec![food(FoodKind::Grain, 5.0), food(FoodKind::Meat, 15.0)];
+    use_inventories(&mut world, &mut resources);
+    let stockpiles: Vec<(Position, Stockpile)> = <(&Position, &Stockpile)>::query().iter(&world).map(|(p, s)| (*p, s.clone())).collect();
+    assert_eq!(stockpiles, vec![(Position { x: 3.0, y: 4.0 }, Stockpile { items: vec![food(FoodKind::Grain, 5.0), food(FoodKind::Meat, 15.0)] })]);
+    assert!(inventory_of(&world, agent).is_empty());
+    assert_eq!(resources.get::<FoodStats>().unwrap().stockpiles_started, 1);
+    assert!(render_simulation_ascii(&world, &map).lines().nth(4).unwrap().starts_with("...A"));
+
+    // A half-full pack is kept until the agent next passes a stockpile.
+    world.entry(agent).unwrap().get_component_mut::<Inventory>().unwrap().items.push(food(FoodKind::Grain, 5.0));
+    world.entry(agent).unwrap().get_component_mut::<Position>().unwrap().x = 20.0;
+    use_inventories(&mut world, &mut resources);
+    assert_eq!(inventory_of(&world, agent).items.len(), 1);
+    assert_eq!(<&Stockpile>::query().iter(&world).count(), 1);
+    assert!(render_simulation_ascii(&world, &map).lines().nth(4).unwrap().starts_with("...P"));
+    assert_eq!(*world.entry_ref(agent).unwrap().get_component::<AgentState>().unwrap(), AgentState::Idle);
+}
+
+#[test]
+fn test_inventories_and_stockpiles_survive_snapshots() {
+    let (mut world, resources, map) = setup(grass(30));
+    let agent = add_agent(&mut world, &map, 3.0, 5.0, 5.0);
+    world.entry(agent).unwrap().get_component_mut::<Inventory>().unwrap().items.push(food(FoodKind::Fish, 11.0));
+    world.push((Position { x: 2.0, y: 2.0 }, Stockpile { items: vec![food(FoodKind::Berries, 8.0)] }));
+    let snapshot = Snapshot::capture(&world, &resources, "inventory", 5);
+    let (mut restored, mut restored_resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut restored, &mut restored_resources);
+    let inventories: Vec<Inventory> = <&Inventory>::query().iter(&restored).cloned().collect();
+    assert_eq!(inventories, vec![Inventory { items: vec![food(FoodKind::Fish, 11.0)], capacity: 3 }]);
+    let stockpiles: Vec<Stockpile> = <&Stockpile>::query().iter(&restored).cloned().collect();
+    assert_eq!(stockpiles, vec![Stockpile { items: vec![food(FoodKind::Berries, 8.0)] }]);
+}
@@ -0,0 +1,67 @@
+//! Path following spends `movement_profile.speed` points per tick, scaled by terrain cost.
+
+use std::collections::VecDeque;
+use community_sim::agent::{AgentState, AgentType, MovementEffect, MovementProfile};
+use community_sim::agent::systems::follow_waypoints;
+use community_sim::ecs_components::Position;
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::Path;
+
+fn straight_path(len: usize) -> Path {
+    Path { waypoints: (1..=len).map(|x| (x as f32 + 0.5, 0.5)).collect::<VecDeque<_>>() }
+}
+
+fn agent(speed: f32) -> AgentType {
+    AgentType { movement_profile: MovementProfile { speed, effect: MovementEffect::None }, ..Default::default() }
+}
+
+fn map(terrain: Terrain) -> Map {
+    Map::from_tiles(10, 1, vec![vec![terrain; 10]])
+}
+
+#[test]
+fn test_faster_agents_cover_more_waypoints() {
+    let grass = map(Terrain::Grass);
+    for (speed, expected_x) in [(2.0, 2.5), (4.0, 4.5)] {
+        let mut pos = Position { x: 0.5, y: 0.5 };
+        let mut path = straight_path(8);
+        let (travelled, _) = follow_waypoints(&mut pos, &mut path, &agent(speed), &AgentState::Moving, &grass);
+        assert_eq!(travelled, speed);
+        assert_eq!(pos.x, expected_x);
+        assert_eq!(path.waypoints.len(), 8 - speed as usize);
+    }
+}
+
+#[test]
+fn test_terrain_cost_slows_movement() {
+    let forest = map(Terrain::Forest);
+    let mut pos = Position { x: 0.5, y: 0.5 };
+    let mut path = straight_path(8);
+    let (travelled, _) = follow_waypoints(&mut pos, &mut path, &agent(3.0), &AgentState::Moving, &forest);
+    assert_eq!(travelled, 1.5);
+    assert_eq!(pos.x, 2.0);
+    assert_eq!(path.waypoints.len(), 7);
+}
+
+#[test]
+fn test_movement_stops_at_end_of_path() {
+    let grass = map(Terrain::Grass);
+    let mut pos = Position { x: 0.5, y: 0.5 };
+    let mut path = straight_path(2);
+    let (travelled, spent) = follow_waypoints(&mut pos, &mut path, &agent(4.0), &AgentState::Moving, &grass);
+    assert_eq!(travelled, 2.0);
+    assert!(spent > 0.0);
+    assert!(path.waypoints.is_empty());
+    assert_eq!(pos.x, 2.5);
+}
+
+#[test]
+fn test_damping_shrinks_the_movement_budget() {
+    let grass = map(Terrain::Grass);
+    let mut pos = Position { x: 0.5, y: 0.5 };
+    let mut path = straight_path(8);
+    let damped = AgentType { damping: 0.5, ..agent(4.0) };
+    let (travelled, _) = follow_waypoints(&mut pos, &mut path, &damped, &AgentState::Moving, &grass);
+    assert_eq!(travelled, 2.0);
+    assert_eq!(pos.x, 2.5);
+}
@@ -0,0 +1,64 @@
+//! A* costs: terrain base cost times the agent type's per-terrain modifier.
+
+use community_sim::agent::{AgentState, AgentType, MovementEffect};
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::pathfinding::{a_star_path, tile_cost};
+
+fn map_from(rows: &[&str]) -> Map {
+    let tiles: Vec<Vec<Terrain>> = rows.iter()
+        .map(|row| row.chars().map(|c| match c {
+            'T' => Terrain::Forest,
+            '^' => Terrain::Mountain,
+            '~' => Terrain::Water,
+            _ => Terrain::Grass,
+        }).collect())
+        .collect();
+    Map::from_tiles(tiles[0].len() as i32, tiles.len() as i32, tiles)
+}
+
+fn climber(effect: MovementEffect) -> AgentType {
+    let mut agent_type = AgentType::default();
+    agent_type.terrain_effects.insert(Terrain::Mountain, effect);
+    agent_type
+}
+
+#[test]
+fn test_tile_cost_combines_terrain_and_profile() {
+    let plain = AgentType::default();
+    assert_eq!(tile_cost(Terrain::Grass, &plain, None), Some(1.0));
+    assert_eq!(tile_cost(Terrain::Forest, &plain, None), Some(2.0));
+    assert_eq!(tile_cost(Terrain::Mountain, &plain, None), None);
+    assert_eq!(tile_cost(Terrain::Water, &plain, None), None);
+    assert!(tile_cost(Terrain::Water, &plain, Some(&AgentState::Swimming)).is_some());
+
+    assert_eq!(tile_cost(Terrain::Mountain, &climber(MovementEffect::None), None), Some(3.0));
+    assert_eq!(tile_cost(Terrain::Mountain, &climber(MovementEffect::Slowed(2.0)), None), Some(6.0));
+    assert_eq!(tile_cost(Terrain::Mountain, &climber(MovementEffect::Blocked), None), None);
+}
+
+#[test]
+fn test_path_bends_around_forest() {
+    let map = map_from(&[
+        ".....",
+        ".TTT.",
+        ".....",
+    ]);
+    let path = a_star_path(&map, &AgentType::default(), &AgentState::Idle, (0, 1), (4, 1), 10).expect("path");
+    assert_eq!(path.len(), 7);
+    assert!(path.iter().all(|&(x, y)| map.terrain_at(x, y) == Terrain::Grass));
+}
+
+#[test]
+fn test_mountains_need_a_profile_entry() {
+    let map = map_from(&[
+        "..^..",
+        "..^..",
+        "..^..",
+    ]);
+    let plain = AgentType::default();
+    assert!(a_star_path(&map, &plain, &AgentState::Idle, (0, 1), (4, 1), 10).is_none());
+
+    let miner = climber(MovementEffect::Slowed(1.2));
+    let path = a_star_path(&map, &miner, &AgentState::Idle, (0, 1), (4, 1), 10).expect("miner crosses");
+    assert!(path.iter().any(|&(x, y)| map.terrain_at(x, y) == Terrain::Mountain));
+}
@@ -0,0 +1,102 @@
+//! Reproduction: well-fed agents of the same type that meet conceive, and the child is
+//! born after the gestation period.
+
+mod common;
+
+use community_sim::agent::{AgentType, Hunger};
+use community_sim::agent::event::{AgentEvent, AgentEventLog};
+use community_sim::agent::reproduction::{reproduction_system, Fertility, ReproductionConfig};
+use community_sim::ecs_components::entity_interaction_system;
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, IntoQuery, Schedule, World};
+use common::{add_agent, grass, run, setup, yaml_round_trip};
+
+const GESTATION: u32 = 3;
+
+fn breeder(name: &str) -> AgentType {
+    AgentType {
+        name: name.to_string(),
+        move_probability: 0.0,
+        reproduction: Some(ReproductionConfig { chance: 1.0, gestation_ticks: GESTATION, cooldown_ticks: 50, ..Default::default() }),
+        ..Default::default()
+    }
+}
+
+fn schedule() -> Schedule {
+    Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .add_system(reproduction_system())
+        .build()
+}
+
+fn agent_count(world: &World) -> usize {
+    <&AgentType>::query().iter(world).count()
+}
+
+#[test]
+fn test_meeting_pair_conceives_and_gives_birth() {
+    let (mut world, mut resources, map) = setup(grass(10));
+    let mother = add_agent(&mut world, &map, breeder("worker"), 5.0, 5.0);
+    let father = add_agent(&mut world, &map, breeder("worker"), 5.5, 5.0);
+    let mut schedule = schedule();
+    schedule.execute(&mut world, &mut resources);
+
+    let fertility = |world: &World, e: Entity| world.entry_ref(e).unwrap().get_component::<Fertility>().unwrap().clone();
+    let hunger = |world: &World, e: Entity| world.entry_ref(e).unwrap().get_component::<Hunger>().unwrap().value;
+    assert_eq!(fertility(&world, mother).pregnancy.map(|p| (p.father, p.ticks_remaining)), Some((Some(father), GESTATION)));
+    assert!(fertility(&world, father).pregnancy.is_none());
+    assert_eq!((fertility(&world, mother).cooldown, fertility(&world, father).cooldown), (50, 50));
+    assert_eq!((hunger(&world, mother), hunger(&world, father)), (80.0, 80.0));
+
+    for _ in 0..GESTATION - 1 {
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(agent_count(&world), 2);
+    }
+    schedule.execute(&mut world, &mut resources);
+    assert_eq!(agent_count(&world), 3);
+    assert!(fertility(&world, mother).pregnancy.is_none());
+
+    let log = resources.get::<AgentEventLog>().unwrap();
+    let births: Vec<_> = log.0.iter().filter_map(|e| match e {
+        AgentEvent::Spawned { agent, parents, pos, .. } if !parents.is_empty() => Some((*agent, parents.clone(), *pos)),
+        _ => None,
+    }).collect();
+    assert_eq!(births.len(), 1);
+    let (child, parents, pos) = &births[0];
+    assert_eq!(parents, &vec![mother, father]);
+    assert_eq!(*pos, (5.0, 5.0));
+    // Newborns start on cooldown.
+    assert_eq!(fertility(&world, *child).cooldown, 50);
+}
+
+#[test]
+fn test_no_conception_across_types_or_when_hungry() {
+    let (mut world, mut resources, map) = setup(grass(10));
+    add_agent(&mut world, &map, breeder("worker"), 2.0, 5.0);
+    add_agent(&mut world, &map, breeder("farmer"), 2.5, 5.0);
+    let hungry_a = add_agent(&mut world, &map, breeder("worker"), 7.0, 5.0);
+    add_agent(&mut world, &map, breeder("worker"), 7.5, 5.0);
+    world.entry(hungry_a).unwrap().get_component_mut::<Hunger>().unwrap().value = 40.0;
+    let mut schedule = schedule();
+    schedule.execute(&mut world, &mut resources);
+    let pregnant = <&Fertility>::query().iter(&world).filter(|f| f.pregnancy.is_some()).count();
+    assert_eq!(pregnant, 0);
+}
+
+#[test]
+fn test_types_without_config_never_reproduce() {
+    let (mut world, mut resources, map) = setup(grass(10));
+    let plain = AgentType { move_probability: 0.0, ..Default::default() };
+    add_agent(&mut world, &map, plain.clone(), 5.0, 5.0);
+    add_agent(&mut world, &map, plain, 5.5, 5.0);
+    run(&mut world, &mut resources, &mut schedule(), 10);
+    assert_eq!(agent_count(&world), 2);
+}
+
+#[test]
+fn test_reproduction_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Worker\"\n  type: \"worker\"\n  reproduction:\n    gestation_ticks: 12\n    chance: 0.25\n- name: \"Scout\"\n  type: \"scout\"\n");
+    assert_eq!(types[0].reproduction, Some(ReproductionConfig { gestation_ticks: 12, chance: 0.25, ..Default::default() }));
+    assert_eq!(types[1].reproduction, None);
+}
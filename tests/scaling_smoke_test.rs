@@ -0,0 +1,16 @@
+//! Smoke test for the scaling benchmarks (`--headless --scale`): a small profile must set
+//! up every resource the schedule needs and run a few ticks.
+
+use community_sim::simulation::run_simulation;
+use community_sim::util::load_agent_types;
+
+#[test]
+fn scaling_profile_smoke_test() {
+    let agent_types = load_agent_types("config/agent_types.yaml");
+    let csv = std::env::temp_dir().join(format!("scaling_smoke_{}.csv", std::process::id()));
+    run_simulation(20, 20, 10, 3, "base", &agent_types, true, csv.to_str().unwrap(), Some(1));
+    let contents = std::fs::read_to_string(&csv).unwrap();
+    std::fs::remove_file(&csv).ok();
+    // Header plus one row per tick.
+    assert_eq!(contents.lines().count(), 4);
+}
@@ -0,0 +1,84 @@
+//! Snapshot save/load: a restored world matches the captured one and keeps simulating the same way.
+
+use community_sim::agent::{AgentType, components::{MovementProfile, MovementEffect, DecisionEngineConfig}};
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::ecs_simulation::{build_simulation_schedule_profiled, simulation_tick, render_simulation_ascii};
+use community_sim::map::Map;
+use community_sim::sim_rng::{RngStream, SimRng};
+use community_sim::snapshot::Snapshot;
+use legion::{Resources, World};
+use rand::Rng;
+
+fn seeded_world(seed: u64) -> (World, Resources) {
+    let agent_type = AgentType {
+        name: "worker".to_string(),
+        color: (0, 0, 255),
+        movement_profile: MovementProfile { speed: 1.0, effect: MovementEffect::None },
+        decision_engine: DecisionEngineConfig::Simple,
+        hunger_rate: 0.1,
+        hunger_threshold: 10.0,
+        ..Default::default()
+    };
+    let mut sim_rng = SimRng::new(seed);
+    let map = Map::new_with_seed(40, 30, sim_rng.stream(RngStream::Map).gen());
+    let mut world = World::default();
+    let mut agent_event_log = AgentEventLog::default();
+    let rng = sim_rng.stream(RngStream::Spawn);
+    let mut spawned = 0;
+    while spawned < 20 {
+        let (x, y) = (rng.gen_range(0..map.width) as f32, rng.gen_range(0..map.height) as f32);
+        if map.is_passable(x as i32, y as i32, None) {
+            spawn_agent(&mut world, Position { x, y }, agent_type.clone(), &map, &mut agent_event_log, rng);
+            spawned += 1;
+        }
+    }
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, sim_rng);
+    (world, resources)
+}
+
+fn run_ticks(world: &mut World, resources: &mut Resources, ticks: usize) {
+    let mut schedule = build_simulation_schedule_profiled();
+    for _ in 0..ticks {
+        simulation_tick(world, resources, &mut schedule);
+    }
+}
+
+#[test]
+fn test_snapshot_file_round_trip() {
+    let (mut world, mut resources) = seeded_world(7);
+    run_ticks(&mut world, &mut resources, 10);
+    let snapshot = Snapshot::capture(&world, &resources, "test", 10);
+    let path = std::env::temp_dir().join(format!("community_sim_snapshot_{}.json", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let loaded = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    let (mut restored_world, mut restored_resources) = (World::default(), Resources::default());
+    loaded.restore(&mut restored_world, &mut restored_resources);
+    let recaptured = Snapshot::capture(&restored_world, &restored_resources, "test", 10);
+    assert_eq!(
+        serde_json::to_string(&snapshot).unwrap(),
+        serde_json::to_string(&recaptured).unwrap(),
+        "restored world does not match the captured one"
+    );
+}
+
+#[test]
+fn test_resumed_run_matches_uninterrupted_run() {
+    let (mut world, mut resources) = seeded_world(11);
+    run_ticks(&mut world, &mut resources, 20);
+    let map = resources.get::<Map>().unwrap().clone();
+    let uninterrupted = render_simulation_ascii(&world, &map);
+
+    let (mut world, mut resources) = seeded_world(11);
+    run_ticks(&mut world, &mut resources, 10);
+    let snapshot = Snapshot::capture(&world, &resources, "test", 10);
+    let (mut world, mut resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut world, &mut resources);
+    run_ticks(&mut world, &mut resources, 10);
+    assert_eq!(render_simulation_ascii(&world, &map), uninterrupted);
+}
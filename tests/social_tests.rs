@@ -0,0 +1,156 @@
+//! Social graph: relationships built from completed interactions, fading over time,
+//! steering wandering agents toward friends, and exported for network analysis.
+
+mod common;
+
+use std::collections::HashMap;
+use community_sim::agent::{AgentType, InteractionState};
+use community_sim::agent::event_sink::StableIds;
+use community_sim::agent::systems::action_selection_system;
+use community_sim::ecs_components::{agent_interaction_system, entity_interaction_system};
+use community_sim::interaction::{InteractionConfig, InteractionOutcome};
+use community_sim::navigation::Target;
+use community_sim::snapshot::Snapshot;
+use community_sim::social::{social_graph_system, Relationship, SocialGraph, FORGET_BELOW};
+use community_sim::spatial::spatial_index_system;
+use legion::{Entity, EntityStore, Resources, Schedule, World};
+use common::{add_agent, grass, setup};
+
+fn chatty() -> AgentType {
+    AgentType {
+        move_probability: 0.0,
+        interaction: InteractionConfig { duration_ticks: 1, outcomes: [(InteractionOutcome::Chat, 1.0)].into_iter().collect(), ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn entities(n: usize) -> Vec<Entity> {
+    let mut world = World::default();
+    (0..n).map(|_| world.push((0u8,))).collect()
+}
+
+fn friends(affinity: f32) -> Relationship {
+    Relationship { familiarity: 0.5, affinity, interactions: 3 }
+}
+
+#[test]
+fn test_interactions_build_relationships() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let a = add_agent(&mut world, &map, chatty(), 5.0, 5.0);
+    let b = add_agent(&mut world, &map, chatty(), 6.0, 5.0);
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(entity_interaction_system())
+        .add_system(agent_interaction_system())
+        .add_system(social_graph_system())
+        .build();
+    // A one-tick interaction completes the tick it starts.
+    schedule.execute(&mut world, &mut resources);
+    {
+        let graph = resources.get::<SocialGraph>().unwrap();
+        assert_eq!(graph.len(), 1);
+        let r = graph.get(b, a).unwrap();
+        assert_eq!(r.interactions, 1);
+        assert!((r.familiarity - 0.2).abs() < 1e-6 && (r.affinity - 0.1).abs() < 1e-6);
+    }
+    let recent = world.entry_ref(a).unwrap().get_component::<InteractionState>().unwrap().recent_partners.clone();
+    assert_eq!(recent.back().unwrap().outcome, Some(InteractionOutcome::Chat));
+
+    // Next tick they are on cooldown; the relationship only fades.
+    schedule.execute(&mut world, &mut resources);
+    let graph = resources.get::<SocialGraph>().unwrap();
+    assert_eq!(graph.get(a, b).unwrap().interactions, 1);
+    assert!(graph.get(a, b).unwrap().familiarity < 0.2);
+}
+
+#[test]
+fn test_relationships_decay_and_are_forgotten() {
+    let e = entities(3);
+    let mut graph = SocialGraph::default();
+    graph.record(e[0], e[1], InteractionOutcome::Fight);
+    graph.record(e[1], e[0], InteractionOutcome::ShareFood);
+    graph.insert(e[1], e[2], Relationship { familiarity: FORGET_BELOW * 1.001, ..Default::default() });
+    assert_eq!(graph.len(), 2);
+    let r = graph.get(e[0], e[1]).unwrap().clone();
+    assert_eq!(r.interactions, 2);
+    assert!((r.familiarity - 0.36).abs() < 1e-6 && (r.affinity + 0.1).abs() < 1e-6);
+
+    graph.decay();
+    assert!(graph.get(e[1], e[2]).is_none());
+    assert!(graph.get(e[0], e[1]).unwrap().familiarity < r.familiarity);
+    graph.remove_agent(e[0]);
+    assert!(graph.is_empty());
+}
+
+#[test]
+fn test_degree_and_clustering() {
+    // A triangle 0-1-2 with 3 hanging off 2; agent 4 knows nobody.
+    let e = entities(5);
+    let mut graph = SocialGraph::default();
+    for (a, b) in [(0, 1), (1, 2), (0, 2), (2, 3)] {
+        graph.record(e[a], e[b], InteractionOutcome::Chat);
+    }
+    assert_eq!((graph.degree(e[2]), graph.degree(e[4])), (3, 0));
+    assert_eq!(graph.clustering(e[0]), 1.0);
+    assert!((graph.clustering(e[2]) - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(graph.clustering(e[3]), 0.0);
+    let metrics = graph.metrics(5);
+    assert_eq!(metrics.relationships, 4);
+    assert!((metrics.mean_degree - 1.6).abs() < 1e-9);
+    assert!((metrics.clustering - (1.0 + 1.0 + 1.0 / 3.0) / 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_wandering_agent_seeks_out_friend() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let wanderer = AgentType { move_probability: 1.0, vision: 10.0, ..Default::default() };
+    let a = add_agent(&mut world, &map, wanderer, 5.0, 5.0);
+    let stranger = add_agent(&mut world, &map, chatty(), 8.0, 5.0);
+    let friend = add_agent(&mut world, &map, chatty(), 12.0, 5.0);
+    {
+        let mut graph = resources.get_mut::<SocialGraph>().unwrap();
+        graph.insert(a, friend, friends(0.8));
+        graph.insert(a, stranger, friends(0.1));
+    }
+    let mut schedule = Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(action_selection_system())
+        .build();
+    schedule.execute(&mut world, &mut resources);
+    let target = world.entry_ref(a).unwrap().get_component::<Target>().unwrap().clone();
+    assert_eq!((target.x, target.y), (12.0, 5.0));
+}
+
+#[test]
+fn test_export_csv_and_graphml() {
+    let e = entities(3);
+    let mut graph = SocialGraph::default();
+    graph.insert(e[0], e[1], friends(0.5));
+    graph.insert(e[2], e[0], friends(-0.25));
+    let mut ids = StableIds::default();
+    ids.get_or_assign(e[2]);
+    assert_eq!(
+        graph.to_csv(&mut ids),
+        "source,target,familiarity,affinity,interactions\n1,2,0.5000,0.5000,3\n0,1,0.5000,-0.2500,3\n"
+    );
+    let names: HashMap<Entity, String> = [(e[0], "A&B".to_string())].into_iter().collect();
+    let xml = graph.to_graphml(&mut ids, &names);
+    assert!(xml.contains("<graph id=\"social\" edgedefault=\"undirected\">"));
+    assert!(xml.contains("<node id=\"n1\"><data key=\"agent_type\">A&amp;B</data></node>"));
+    assert!(xml.contains("<node id=\"n2\"/>"));
+    assert!(xml.contains("<edge source=\"n0\" target=\"n1\"><data key=\"familiarity\">0.5000</data><data key=\"affinity\">-0.2500</data>"));
+}
+
+#[test]
+fn test_snapshot_keeps_relationships() {
+    let (mut world, resources, map) = setup(grass(20));
+    let a = add_agent(&mut world, &map, chatty(), 2.0, 5.0);
+    let b = add_agent(&mut world, &map, chatty(), 9.0, 5.0);
+    resources.get_mut::<SocialGraph>().unwrap().insert(a, b, friends(0.4));
+    let snapshot = Snapshot::capture(&world, &resources, "social", 1);
+    let (mut restored, mut restored_resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut restored, &mut restored_resources);
+    let graph = restored_resources.get::<SocialGraph>().unwrap();
+    let edges: Vec<_> = graph.edges().map(|(_, _, r)| r.clone()).collect();
+    assert_eq!(edges, vec![friends(0.4)]);
+}
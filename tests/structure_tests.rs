@@ -0,0 +1,196 @@
+//! Structures: placement on the map's structure layer, building them up, and what houses,
+//! storehouses, farms and roads do once finished.
+
+mod common;
+
+use std::collections::BTreeMap;
+use community_sim::agent::{AgentState, AgentType, Energy};
+use community_sim::agent::energy::rest_system;
+use community_sim::agent::systems::action_selection_system;
+use community_sim::agent::work::{work_system, Fields, WorkRole, WorkStats};
+use community_sim::ecs_components::Position;
+use community_sim::food::Food;
+use community_sim::map::{Map, Terrain};
+use community_sim::navigation::pathfinding::a_star_path;
+use community_sim::navigation::Target;
+use community_sim::render_ascii::render_simulation_ascii;
+use community_sim::snapshot::Snapshot;
+use community_sim::spatial::spatial_index_system;
+use community_sim::structure::{fits, storehouse_system, Structure, StructureKind, HOUSE_REST_BONUS, STOREHOUSE_CAPACITY};
+use legion::{Entity, EntityStore, IntoQuery, Resources, Schedule, World};
+use common::{add_agent, grass, run, setup};
+
+/// Puts a `kind` structure at (`x`, `y`), `progress` of the way done, into the world and
+/// onto the map resource.
+fn place(world: &mut World, resources: &Resources, kind: StructureKind, x: i32, y: i32, progress: f32) -> Entity {
+    let structure = Structure { progress, ..Structure::new(kind, x, y, None) };
+    let entity = world.push((structure.clone(),));
+    resources.get_mut::<Map>().unwrap().structures.insert(entity, &structure);
+    entity
+}
+
+fn structure(world: &World, entity: Entity) -> Structure {
+    world.entry_ref(entity).unwrap().get_component::<Structure>().unwrap().clone()
+}
+
+fn schedule() -> Schedule {
+    Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(work_system())
+        .add_system(storehouse_system())
+        .build()
+}
+
+#[test]
+fn test_placement_and_build_order() {
+    let (mut world, resources, mut map) = setup(grass(20));
+    map.tiles[0][3] = Terrain::Water;
+    map.tiles[5][5] = Terrain::Forest;
+    assert!(fits(&map, StructureKind::House, 0, 0));
+    assert!(!fits(&map, StructureKind::House, 2, 0), "water under the footprint");
+    assert!(!fits(&map, StructureKind::House, 19, 5), "off the map edge");
+    assert!(fits(&map, StructureKind::House, 4, 4));
+    assert!(!fits(&map, StructureKind::Farm, 4, 4), "farms need grass");
+
+    let house = place(&mut world, &resources, StructureKind::House, 10, 10, 0.0);
+    let map = resources.get::<Map>().unwrap();
+    assert_eq!(map.structures.get(11, 11).map(|t| (t.entity, t.built)), Some((house, false)));
+    assert!(map.structures.get(12, 11).is_none());
+    assert!(!fits(&map, StructureKind::Road, 11, 10));
+
+    let order = |list: &[(StructureKind, usize)]| StructureKind::build_order(&list.iter().copied().collect::<BTreeMap<_, _>>());
+    use StructureKind::*;
+    assert_eq!(order(&[]), vec![House, Storehouse, Farm, Road]);
+    assert_eq!(order(&[(House, 1)]), vec![Storehouse, Farm, Road, House]);
+    assert_eq!(order(&[(House, 1), (Storehouse, 1)]), vec![Farm, Road, House, Storehouse]);
+    assert_eq!(order(&[(House, 2), (Storehouse, 1), (Farm, 1)]), vec![Road, House, Farm, Storehouse]);
+}
+
+#[test]
+fn test_builder_finishes_farm_which_becomes_fields() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let farm = place(&mut world, &resources, StructureKind::Farm, 3, 3, 0.9);
+    add_agent(&mut world, &map, AgentType { move_probability: 0.0, role: Some(WorkRole::Builder), ..Default::default() }, 4.0, 4.0);
+    resources.get_mut::<WorkStats>().unwrap().stone = 100.0;
+    // 3 stone left at 0.25 a tick, after a tick to start.
+    run(&mut world, &mut resources, &mut schedule(), 12);
+    assert!(!structure(&world, farm).is_built());
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert!(structure(&world, farm).is_built());
+    assert!(resources.get::<Map>().unwrap().structures.get(5, 5).unwrap().built);
+    assert_eq!(resources.get::<Fields>().unwrap().0.len(), 9);
+    let stats = resources.get::<WorkStats>().unwrap();
+    assert_eq!(stats.structures_built, 1);
+    assert!((stats.stone - 97.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_storehouse_takes_harvest_and_puts_it_out() {
+    let (mut world, mut resources, _) = setup(grass(20));
+    let store = place(&mut world, &resources, StructureKind::Storehouse, 10, 10, 1.0);
+    resources.get_mut::<Fields>().unwrap().0.insert((14, 13), 0.999);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    let food: Vec<Position> = <(&Position, &Food)>::query().iter(&world).map(|(pos, _)| *pos).collect();
+    assert_eq!(food.len(), 1);
+    assert_eq!((food[0].x, food[0].y), (10.5, 10.5), "put out at the storehouse, not on the field");
+    assert_eq!(structure(&world, store).food, 0);
+
+    // While its item lies uneaten, further harvests pile up inside.
+    resources.get_mut::<Fields>().unwrap().0.insert((14, 13), 0.999);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(structure(&world, store).food, 1);
+    assert_eq!(<&Food>::query().iter(&world).count(), 1);
+
+    // A full storehouse leaves the harvest on the field.
+    world.entry(store).unwrap().get_component_mut::<Structure>().unwrap().food = STOREHOUSE_CAPACITY;
+    resources.get_mut::<Fields>().unwrap().0.insert((14, 13), 0.999);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(structure(&world, store).food, STOREHOUSE_CAPACITY);
+    assert_eq!(<&Food>::query().iter(&world).count(), 2);
+    assert_eq!(resources.get::<WorkStats>().unwrap().food_grown, 3);
+}
+
+#[test]
+fn test_tired_agent_walks_to_house_and_rests_faster_there() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    place(&mut world, &resources, StructureKind::House, 6, 2, 1.0);
+    let tired = add_agent(&mut world, &map, AgentType { move_probability: 0.0, vision: 5.0, ..Default::default() }, 2.0, 2.0);
+    world.entry(tired).unwrap().get_component_mut::<Energy>().unwrap().value = 10.0;
+    let mut schedule = Schedule::builder().add_system(action_selection_system()).build();
+    schedule.execute(&mut world, &mut resources);
+    let entry = world.entry_ref(tired).unwrap();
+    assert_eq!(*entry.get_component::<AgentState>().unwrap(), AgentState::Moving);
+    let target = entry.get_component::<Target>().unwrap();
+    assert_eq!((target.x, target.y), (6.5, 2.5));
+
+    // Inside the house it rests at the house rate; outside it would rest at the normal one.
+    let sheltered = add_agent(&mut world, &map, AgentType::default(), 7.0, 3.0);
+    let outside = add_agent(&mut world, &map, AgentType::default(), 12.0, 3.0);
+    for agent in [sheltered, outside] {
+        let mut entry = world.entry(agent).unwrap();
+        entry.get_component_mut::<Energy>().unwrap().value = 10.0;
+        *entry.get_component_mut::<AgentState>().unwrap() = AgentState::Resting;
+    }
+    let mut schedule = Schedule::builder().add_system(rest_system()).build();
+    schedule.execute(&mut world, &mut resources);
+    let energy = |agent| world.entry_ref(agent).unwrap().get_component::<Energy>().unwrap().value;
+    assert_eq!(energy(outside), 15.0);
+    assert_eq!(energy(sheltered), 10.0 + 5.0 * HOUSE_REST_BONUS);
+}
+
+#[test]
+fn test_roads_attract_paths() {
+    let (mut world, resources, _) = setup(grass(20));
+    for (x, y) in [(1, 0), (2, 0), (3, 0), (3, 1), (3, 2)] {
+        place(&mut world, &resources, StructureKind::Road, x, y, 1.0);
+    }
+    // An unfinished road doesn't count.
+    place(&mut world, &resources, StructureKind::Road, 0, 1, 0.5);
+    let map = resources.get::<Map>().unwrap();
+    assert_eq!(map.structures.path_cost_factor(2, 0), 0.5);
+    assert_eq!(map.structures.path_cost_factor(0, 1), 1.0);
+    let path = a_star_path(&map, &AgentType::default(), &AgentState::Idle, (0, 0), (3, 3), 20).unwrap();
+    let tiles: Vec<(i32, i32)> = path.iter().map(|(x, y)| (*x as i32, *y as i32)).collect();
+    assert_eq!(tiles, vec![(0, 0), (1, 0), (2, 0), (3, 0), (3, 1), (3, 2), (3, 3)]);
+}
+
+#[test]
+fn test_road_detour_cheaper_than_grass_is_taken() {
+    let (mut world, resources, _) = setup(grass(20));
+    // Down two, along six and back up: nine road tiles at 0.5 plus the goal, 5.5 in all,
+    // against 6.0 straight across the grass.
+    let road = [(0, 1), (0, 2), (1, 2), (2, 2), (3, 2), (4, 2), (5, 2), (6, 2), (6, 1)];
+    for (x, y) in road {
+        place(&mut world, &resources, StructureKind::Road, x, y, 1.0);
+    }
+    let map = resources.get::<Map>().unwrap();
+    let path = a_star_path(&map, &AgentType::default(), &AgentState::Idle, (0, 0), (6, 0), 20).unwrap();
+    let tiles: Vec<(i32, i32)> = path.iter().map(|(x, y)| (*x as i32, *y as i32)).collect();
+    let expected: Vec<(i32, i32)> = std::iter::once((0, 0)).chain(road).chain(std::iter::once((6, 0))).collect();
+    assert_eq!(tiles, expected);
+}
+
+#[test]
+fn test_ascii_and_snapshot_keep_structures() {
+    let (mut world, resources, map) = setup(grass(20));
+    let owner = add_agent(&mut world, &map, AgentType::default(), 0.0, 0.0);
+    let house = place(&mut world, &resources, StructureKind::House, 2, 1, 0.4);
+    world.entry(house).unwrap().get_component_mut::<Structure>().unwrap().owner = Some(owner);
+    place(&mut world, &resources, StructureKind::Road, 5, 1, 1.0);
+    let ascii = render_simulation_ascii(&world, &map);
+    assert!(ascii.lines().nth(1).unwrap().starts_with("..hh.="));
+    assert!(resources.get::<Map>().unwrap().render_ascii().lines().nth(2).unwrap().starts_with("..hh.."));
+
+    let snapshot = Snapshot::capture(&world, &resources, "structures", 5);
+    let (mut restored, mut restored_resources) = (World::default(), Resources::default());
+    snapshot.restore(&mut restored, &mut restored_resources);
+    let structures: Vec<Structure> = <&Structure>::query().iter(&restored).cloned().collect();
+    assert_eq!(structures.len(), 2);
+    let house = structures.iter().find(|s| s.kind == StructureKind::House).unwrap();
+    assert_eq!((house.x, house.y, house.progress), (2, 1, 0.4));
+    let new_owner = <(Entity, &AgentType)>::query().iter(&restored).next().map(|(e, _)| *e);
+    assert_eq!(house.owner, new_owner);
+    let map = restored_resources.get::<Map>().unwrap();
+    assert!(map.structures.get(3, 2).is_some_and(|t| t.kind == StructureKind::House && !t.built));
+    assert_eq!(map.structures.path_cost_factor(5, 1), 0.5);
+}
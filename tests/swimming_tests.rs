@@ -0,0 +1,78 @@
+//! Swimming: idle agents walk to the shore, swim for a while and come back to land.
+
+use community_sim::agent::{AgentState, AgentType, MovementHistory};
+use community_sim::agent::components::SwimmingProfile;
+use community_sim::agent::event::AgentEventLog;
+use community_sim::agent::swimming::{find_shore, SWIM_TICKS};
+use community_sim::agent::systems::spawn_agent;
+use community_sim::ecs::resources::insert_standard_resources;
+use community_sim::ecs_components::Position;
+use community_sim::ecs_simulation::build_simulation_schedule_profiled;
+use community_sim::map::{Map, Terrain};
+use community_sim::sim_rng::SimRng;
+use legion::{Entity, EntityStore, IntoQuery, Resources, World};
+
+/// 20x20 grass with a 6x6 lake at (10..16, 4..10).
+fn lake_map() -> Map {
+    let mut tiles = vec![vec![Terrain::Grass; 20]; 20];
+    for row in tiles.iter_mut().take(10).skip(4) {
+        for tile in row.iter_mut().take(16).skip(10) {
+            *tile = Terrain::Water;
+        }
+    }
+    Map::from_tiles(20, 20, tiles)
+}
+
+fn swimmer(world: &mut World, map: &Map, x: f32, y: f32) -> Entity {
+    let agent_type = AgentType { move_probability: 0.0, ..Default::default() };
+    let mut rng = rand::thread_rng();
+    let entity = spawn_agent(world, Position { x, y }, agent_type, map, &mut AgentEventLog::default(), &mut rng);
+    world.entry(entity).unwrap().get_component_mut::<SwimmingProfile>().unwrap().swim_chance_percent = 100;
+    entity
+}
+
+#[test]
+fn test_spawned_agents_carry_swimming_profile() {
+    let map = lake_map();
+    let mut world = World::default();
+    let agent = swimmer(&mut world, &map, 2.5, 2.5);
+    let entry = world.entry_ref(agent).unwrap();
+    assert!(entry.get_component::<SwimmingProfile>().is_ok());
+    assert!(entry.get_component::<MovementHistory>().is_ok());
+    assert_eq!(world.len(), 1);
+}
+
+#[test]
+fn test_find_shore() {
+    let map = lake_map();
+    let plain = AgentType::default();
+    // Nearest water from (6, 6) is (10, 6); the tile beside it toward the agent is (9, 6).
+    assert_eq!(find_shore(&map, &plain, 6.5, 6.5), Some(((10, 6), (9, 6))));
+    assert_eq!(map.find_nearest_water_within(2, 18, 5), None);
+    assert!(map.find_nearest_water(2, 18).is_some());
+}
+
+#[test]
+fn test_agent_swims_and_returns_to_land() {
+    let map = lake_map();
+    let mut world = World::default();
+    let mut resources = Resources::default();
+    insert_standard_resources(&mut resources, &map, SimRng::new(5));
+    let agent = swimmer(&mut world, &map, 6.5, 6.5);
+    let mut schedule = build_simulation_schedule_profiled();
+    let mut swim_ticks = 0;
+    let mut back_on_land = false;
+    for _ in 0..60 {
+        schedule.execute(&mut world, &mut resources);
+        let (pos, state) = <(&Position, &AgentState)>::query().get(&world, agent).unwrap();
+        if *state == AgentState::Swimming {
+            swim_ticks += 1;
+        } else if swim_ticks > 0 {
+            assert_ne!(map.terrain_at(pos.x, pos.y), Terrain::Water);
+            back_on_land = true;
+            break;
+        }
+    }
+    assert!(swim_ticks >= *SWIM_TICKS.start(), "swam for {} ticks", swim_ticks);
+    assert!(back_on_land);
+}
@@ -0,0 +1,156 @@
+//! Work roles: farmers grow food on fields, miners mine stone, builders turn stone into
+//! structures and doctors restore tired agents' energy.
+
+mod common;
+
+use community_sim::agent::{AgentState, AgentType, Energy, Hunger};
+use community_sim::agent::work::{work_system, Fields, Work, WorkRole, WorkStats, WORK_SESSION_TICKS};
+use community_sim::ecs_components::{FoodStats, Position};
+use community_sim::food::ecology::FoodEcology;
+use community_sim::food::{Food, FoodKind};
+use community_sim::map::Terrain;
+use community_sim::spatial::spatial_index_system;
+use community_sim::structure::{Structure, StructureKind};
+use legion::{Entity, EntityStore, IntoQuery, Schedule, World};
+use common::{add_agent, grass, run, setup, yaml_round_trip};
+
+fn worker(role: WorkRole, work_rate: f32) -> AgentType {
+    AgentType { move_probability: 0.0, role: Some(role), work_rate, ..Default::default() }
+}
+
+fn schedule() -> Schedule {
+    Schedule::builder()
+        .add_system(spatial_index_system())
+        .add_system(work_system())
+        .build()
+}
+
+fn state(world: &World, entity: Entity) -> AgentState {
+    *world.entry_ref(entity).unwrap().get_component::<AgentState>().unwrap()
+}
+
+#[test]
+fn test_farmer_fields_yield_food() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let farmer = add_agent(&mut world, &map, worker(WorkRole::Farmer, 10.0), 5.0, 5.0);
+    resources.get_mut::<FoodEcology>().unwrap().config.kinds.get_mut(&FoodKind::Grain).unwrap().nutrition = [3.0, 3.0];
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(state(&world, farmer), AgentState::Working);
+    assert!(world.entry_ref(farmer).unwrap().get_component::<Work>().is_ok());
+
+    // Tending adds 0.1 a tick on top of natural growth, so the field ripens in 10 ticks.
+    run(&mut world, &mut resources, &mut schedule(), 10);
+    // Field grain comes out as the grain config says.
+    let food: Vec<(Position, Food)> = <(&Position, &Food)>::query().iter(&world).map(|(pos, food)| (*pos, *food)).collect();
+    assert_eq!(food, vec![(Position { x: 5.5, y: 5.5 }, Food { nutrition: 3.0, kind: FoodKind::Grain })]);
+    assert_eq!(resources.get::<FoodStats>().unwrap().spawned_per_tick, 1);
+    assert_eq!(resources.get::<WorkStats>().unwrap().food_grown, 1);
+    assert_eq!(resources.get::<Fields>().unwrap().0.len(), 1);
+
+    // The uneaten harvest holds the field at ripe instead of piling up more food.
+    run(&mut world, &mut resources, &mut schedule(), 15);
+    assert_eq!(<&Food>::query().iter(&world).count(), 1);
+    assert_eq!(resources.get::<Fields>().unwrap().0[&(5, 5)], 1.0);
+}
+
+#[test]
+fn test_miner_walks_to_mountain_and_mines_stone() {
+    let mut map = grass(20);
+    map.tiles[5][9] = Terrain::Mountain;
+    let (mut world, mut resources, map) = setup(map);
+    let miner = add_agent(&mut world, &map, worker(WorkRole::Miner, 2.0), 5.0, 5.0);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(state(&world, miner), AgentState::Moving);
+    assert_eq!(world.entry_ref(miner).unwrap().get_component::<Work>().unwrap().site, Some((8, 5)));
+
+    // Standing on the mine face, it mines 0.2 stone a tick.
+    world.entry(miner).unwrap().get_component_mut::<Position>().unwrap().x = 8.0;
+    *world.entry(miner).unwrap().get_component_mut::<AgentState>().unwrap() = AgentState::Idle;
+    run(&mut world, &mut resources, &mut schedule(), 11);
+    let stats = resources.get::<WorkStats>().unwrap();
+    assert!((stats.stone_mined - 2.0).abs() < 1e-4);
+    assert_eq!(stats.stone, stats.stone_mined);
+}
+
+#[test]
+fn test_builder_turns_stone_into_structures() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let builder = add_agent(&mut world, &map, worker(WorkRole::Builder, 4.0), 5.0, 5.0);
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(state(&world, builder), AgentState::Idle, "no stone, nothing to build");
+
+    resources.get_mut::<WorkStats>().unwrap().stone = 60.0;
+    // The first structure is a house on the builder's own tile; each working tick lays
+    // 1 stone of its 50, with a pause between sessions.
+    run(&mut world, &mut resources, &mut schedule(), 55);
+    let house = <&Structure>::query().iter(&world).find(|s| s.kind == StructureKind::House).unwrap().clone();
+    assert_eq!((house.x, house.y, house.owner), (5, 5, Some(builder)));
+    assert!(house.is_built());
+    let stats = resources.get::<WorkStats>().unwrap().clone();
+    assert_eq!(stats.structures_built, 1);
+    // The house took exactly its cost; the 10 left are short of a storehouse, needed next.
+    assert_eq!(stats.stone, 60.0 - StructureKind::House.cost());
+    assert_eq!(<&Structure>::query().iter(&world).count(), 1);
+
+    resources.get_mut::<WorkStats>().unwrap().stone = 0.0;
+    run(&mut world, &mut resources, &mut schedule(), WORK_SESSION_TICKS as usize);
+    assert_eq!(state(&world, builder), AgentState::Idle);
+}
+
+#[test]
+fn test_doctor_heals_tired_agents() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let doctor = add_agent(&mut world, &map, worker(WorkRole::Doctor, 1.0), 5.0, 5.0);
+    let patient = add_agent(&mut world, &map, AgentType { move_probability: 0.0, ..Default::default() }, 7.0, 5.0);
+    world.entry(patient).unwrap().get_component_mut::<Energy>().unwrap().value = 20.0;
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(state(&world, doctor), AgentState::Moving);
+    assert_eq!(world.entry_ref(doctor).unwrap().get_component::<Work>().unwrap().site, Some((7, 5)));
+
+    world.entry(doctor).unwrap().get_component_mut::<Position>().unwrap().x = 7.0;
+    *world.entry(doctor).unwrap().get_component_mut::<AgentState>().unwrap() = AgentState::Idle;
+    run(&mut world, &mut resources, &mut schedule(), 5);
+    let energy = world.entry_ref(patient).unwrap().get_component::<Energy>().unwrap().value;
+    assert!((energy - 22.0).abs() < 1e-4, "4 working ticks at 0.5 energy each, got {}", energy);
+    assert!((resources.get::<WorkStats>().unwrap().energy_healed - 2.0).abs() < 1e-4);
+
+    // Once nobody needs healing the doctor stops.
+    world.entry(patient).unwrap().get_component_mut::<Energy>().unwrap().value = 90.0;
+    run(&mut world, &mut resources, &mut schedule(), 2);
+    assert_eq!(state(&world, doctor), AgentState::Idle);
+}
+
+#[test]
+fn test_work_rate_scales_output_and_hungry_agents_stop() {
+    let mut map = grass(20);
+    map.tiles[2][3] = Terrain::Mountain;
+    map.tiles[12][3] = Terrain::Mountain;
+    let (mut world, mut resources, map) = setup(map);
+    let slow = add_agent(&mut world, &map, worker(WorkRole::Miner, 1.0), 2.0, 2.0);
+    let fast = add_agent(&mut world, &map, worker(WorkRole::Miner, 3.0), 2.0, 12.0);
+    run(&mut world, &mut resources, &mut schedule(), 5);
+    assert!((resources.get::<WorkStats>().unwrap().stone_mined - 4.0 * (0.1 + 0.3)).abs() < 1e-4);
+
+    world.entry(fast).unwrap().get_component_mut::<Hunger>().unwrap().value = 10.0;
+    run(&mut world, &mut resources, &mut schedule(), 1);
+    assert_eq!(state(&world, fast), AgentState::Idle);
+    assert_eq!(state(&world, slow), AgentState::Working);
+    assert_eq!(world.entry_ref(fast).unwrap().get_component::<Work>().unwrap().site, None);
+}
+
+#[test]
+fn test_agents_without_a_role_do_not_work() {
+    let (mut world, mut resources, map) = setup(grass(20));
+    let idler = add_agent(&mut world, &map, AgentType { move_probability: 0.0, ..Default::default() }, 5.0, 5.0);
+    run(&mut world, &mut resources, &mut schedule(), 3);
+    assert!(world.entry_ref(idler).unwrap().get_component::<Work>().is_err());
+    assert_eq!(state(&world, idler), AgentState::Idle);
+    assert!(resources.get::<Fields>().unwrap().0.is_empty());
+}
+
+#[test]
+fn test_role_yaml_round_trip() {
+    let types = yaml_round_trip("- name: \"Farmer\"\n  type: \"farmer\"\n  role: \"farmer\"\n  work_rate: 4\n- name: \"Scout\"\n  type: \"scout\"\n");
+    assert_eq!((types[0].role, types[0].work_rate), (Some(WorkRole::Farmer), 4.0));
+    assert_eq!(types[1].role, None);
+}
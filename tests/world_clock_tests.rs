@@ -0,0 +1,114 @@
+//! World clock: time of day, seasons, and their effect on agents and food spawning.
+
+mod common;
+
+use community_sim::agent::AgentType;
+use community_sim::agent::aging::{aging_system, Age, LifespanConfig};
+use community_sim::agent::genome::Genome;
+use community_sim::ecs_components::Position;
+use community_sim::food::PendingFoodSpawns;
+use community_sim::food::systems::collect_food_spawn_positions_system;
+use community_sim::map::Map;
+use community_sim::world_clock::{clock_factor_system, world_clock_system, ClockConfig, Season, WorldClock};
+use legion::{EntityStore, Resources, Schedule, World};
+use common::{add_agent, grass};
+
+/// 24-tick days, so a tick is an hour; 2-day seasons.
+fn clock_at(tick: u64) -> WorldClock {
+    WorldClock { tick, ..WorldClock::new(ClockConfig { day_length: 24, days_per_season: 2, ..Default::default() }) }
+}
+
+fn setup(clock: WorldClock) -> (World, Resources, Map) {
+    let (world, mut resources, map) = common::setup(grass(20));
+    resources.insert(clock);
+    (world, resources, map)
+}
+
+#[test]
+fn test_time_of_day_and_seasons() {
+    let dawn = clock_at(0);
+    assert_eq!(dawn.label(), "Day 1, 06:00, spring");
+    assert!((dawn.daylight() - 0.5).abs() < 1e-4);
+
+    let noon = clock_at(6);
+    assert_eq!(noon.daylight(), 1.0);
+    assert_eq!((noon.vision_factor(), noon.speed_factor()), (1.0, 1.0));
+
+    let midnight = clock_at(18);
+    assert_eq!(midnight.label(), "Day 2, 00:00, spring");
+    assert_eq!(midnight.daylight(), 0.0);
+    assert_eq!((midnight.vision_factor(), midnight.speed_factor()), (0.5, 0.7));
+
+    let seasons: Vec<Season> = (0..5).filter_map(|day| clock_at(18 + day * 48).season()).collect();
+    assert_eq!(seasons, vec![Season::Spring, Season::Summer, Season::Autumn, Season::Winter, Season::Spring]);
+    let winter = clock_at(18 + 3 * 48);
+    assert_eq!((winter.hunger_factor(), winter.food_factor()), (1.5, 0.5));
+    assert_eq!((clock_at(66).hunger_factor(), clock_at(66).food_factor()), (1.0, 1.5));
+}
+
+#[test]
+fn test_timeless_world_without_config() {
+    let clock = WorldClock::load("no/such/world_clock.yaml");
+    assert_eq!(clock, WorldClock::disabled());
+    assert_eq!(clock.label(), "timeless");
+    assert_eq!((clock.daylight(), clock.vision_factor(), clock.hunger_factor(), clock.food_factor()), (1.0, 1.0, 1.0, 1.0));
+    assert_eq!(WorldClock::new(ClockConfig { day_length: 0, ..Default::default() }), WorldClock::disabled());
+
+    let path = std::env::temp_dir().join(format!("world_clock_{}.yaml", std::process::id()));
+    std::fs::write(&path, "day_length: 100\nfood:\n  winter: 0.2\n").unwrap();
+    let config = WorldClock::load(&path).config.unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!((config.day_length, config.days_per_season, config.food.winter, config.food.summer), (100, 10, 0.2, 1.5));
+}
+
+#[test]
+fn test_night_and_winter_change_expressed_traits() {
+    let (mut world, mut resources, map) = setup(clock_at(18 + 3 * 48));
+    let agent = add_agent(&mut world, &map, AgentType::default(), 5.0, 5.0);
+    let plain = world.push((Position { x: 7.0, y: 5.0 }, AgentType::default()));
+    let mut schedule = Schedule::builder().add_system(clock_factor_system()).add_system(world_clock_system()).build();
+    schedule.execute(&mut world, &mut resources);
+    let plain_type = world.entry_ref(plain).unwrap().get_component::<AgentType>().unwrap().clone();
+    assert_eq!(plain_type.vision, AgentType::default().vision * 0.5, "agents without a genome are scaled too");
+    let entry = world.entry_ref(agent).unwrap();
+    let (genome, agent_type) = (entry.get_component::<Genome>().unwrap().clone(), entry.get_component::<AgentType>().unwrap().clone());
+    assert_eq!(agent_type.vision, genome.vision * 0.5);
+    assert_eq!(agent_type.movement_profile.speed, genome.speed * 0.7);
+    assert_eq!(agent_type.hunger_rate, genome.hunger_rate * 1.5);
+    assert_eq!(resources.get::<WorldClock>().unwrap().tick, 18 + 3 * 48 + 1);
+
+    // By the next midday (and spring) everything is back to the genome's values.
+    resources.get_mut::<WorldClock>().unwrap().tick = 6 + 4 * 48;
+    schedule.execute(&mut world, &mut resources);
+    let agent_type = world.entry_ref(agent).unwrap().get_component::<AgentType>().unwrap().clone();
+    assert_eq!((agent_type.vision, agent_type.movement_profile.speed, agent_type.hunger_rate), (genome.vision, genome.speed, genome.hunger_rate));
+}
+
+#[test]
+fn test_old_age_and_night_combine() {
+    let (mut world, mut resources, map) = setup(clock_at(18));
+    let lifespan = LifespanConfig { mean: 10, spread: 0, old_age: 0.5, old_speed: 0.5, old_hunger: 1.0 };
+    let agent = add_agent(&mut world, &map, AgentType { lifespan: Some(lifespan), ..Default::default() }, 5.0, 5.0);
+    world.entry(agent).unwrap().get_component_mut::<Age>().unwrap().ticks = 5;
+    let mut schedule = Schedule::builder().add_system(aging_system()).add_system(clock_factor_system()).build();
+    // Old age sets in at 5 ticks; two ticks later speed is down 20%, and night takes 30%.
+    schedule.execute(&mut world, &mut resources);
+    schedule.execute(&mut world, &mut resources);
+    let entry = world.entry_ref(agent).unwrap();
+    let genome = entry.get_component::<Genome>().unwrap().clone();
+    assert_eq!(entry.get_component::<AgentType>().unwrap().movement_profile.speed, genome.speed * 0.8 * 0.7);
+}
+
+#[test]
+fn test_seasons_scale_food_spawning() {
+    let spawned = |clock: WorldClock| {
+        let (mut world, mut resources, _) = setup(clock);
+        Schedule::builder().add_system(collect_food_spawn_positions_system()).build().execute(&mut world, &mut resources);
+        let pending = resources.get::<PendingFoodSpawns>().unwrap().0.len();
+        pending
+    };
+    assert_eq!(spawned(WorldClock::disabled()), 2);
+    assert_eq!(spawned(clock_at(0)), 2);
+    assert_eq!(spawned(clock_at(48)), 3);
+    assert_eq!(spawned(clock_at(144)), 1);
+}